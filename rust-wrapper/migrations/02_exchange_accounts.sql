@@ -0,0 +1,24 @@
+CREATE TABLE IF NOT EXISTS exchange_accounts (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    exchange TEXT NOT NULL,
+    label TEXT,
+    api_key TEXT NOT NULL,
+    encrypted_secret TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS exchange_accounts_user_id_idx ON exchange_accounts(user_id);
+
+-- Переносим ключи, которые раньше хранились прямо в users
+INSERT INTO exchange_accounts (id, user_id, exchange, api_key, encrypted_secret, created_at)
+SELECT id, id, exchange, api_key, encrypted_secret, created_at FROM users
+ON CONFLICT (id) DO NOTHING;
+
+ALTER TABLE strategies ADD COLUMN IF NOT EXISTS account_id UUID REFERENCES exchange_accounts(id) ON DELETE CASCADE;
+UPDATE strategies SET account_id = user_id WHERE account_id IS NULL;
+ALTER TABLE strategies ALTER COLUMN account_id SET NOT NULL;
+
+ALTER TABLE users DROP COLUMN IF EXISTS api_key;
+ALTER TABLE users DROP COLUMN IF EXISTS encrypted_secret;
+ALTER TABLE users DROP COLUMN IF EXISTS exchange;
@@ -0,0 +1,13 @@
+UPDATE users SET created_at = now() WHERE created_at IS NULL;
+UPDATE exchange_accounts SET created_at = now() WHERE created_at IS NULL;
+UPDATE strategies SET created_at = now() WHERE created_at IS NULL;
+
+ALTER TABLE users ALTER COLUMN created_at SET NOT NULL;
+ALTER TABLE exchange_accounts ALTER COLUMN created_at SET NOT NULL;
+ALTER TABLE strategies ALTER COLUMN created_at SET NOT NULL;
+
+-- Индексы под keyset-пагинацию (сортировка + id как тай-брейкер)
+CREATE INDEX IF NOT EXISTS users_created_at_id_idx ON users(created_at, id);
+CREATE INDEX IF NOT EXISTS exchange_accounts_user_created_at_idx ON exchange_accounts(user_id, created_at, id);
+CREATE INDEX IF NOT EXISTS strategies_user_created_at_idx ON strategies(user_id, created_at, id);
+CREATE INDEX IF NOT EXISTS strategies_user_name_idx ON strategies(user_id, strategy_name, id);
@@ -0,0 +1,9 @@
+ALTER TABLE api_tokens ADD COLUMN IF NOT EXISTS role TEXT NOT NULL DEFAULT 'admin'
+    CHECK (role IN ('admin', 'operator', 'viewer'));
+ALTER TABLE api_tokens ALTER COLUMN role DROP DEFAULT;
+
+-- Удаление выделено в отдельные права; раньше оно входило в *:write
+UPDATE api_tokens SET scopes = array_append(scopes, 'users:delete')
+WHERE 'users:write' = ANY(scopes) AND NOT 'users:delete' = ANY(scopes);
+UPDATE api_tokens SET scopes = array_append(scopes, 'strategies:delete')
+WHERE 'strategies:write' = ANY(scopes) AND NOT 'strategies:delete' = ANY(scopes);
@@ -0,0 +1,19 @@
+-- Каждая расшифровка ключей биржи. Без внешних ключей, чтобы история
+-- сохранялась и после удаления аккаунта или стратегии.
+CREATE TABLE IF NOT EXISTS credential_access (
+    id UUID PRIMARY KEY,
+    accessed_at TIMESTAMP NOT NULL DEFAULT now(),
+    user_id UUID NOT NULL,
+    account_id UUID NOT NULL,
+    purpose TEXT NOT NULL CHECK (purpose IN ('balance', 'signal', 'validation')),
+    success BOOLEAN NOT NULL,
+    caller_kind TEXT NOT NULL,
+    caller_id TEXT,
+    caller_name TEXT,
+    strategy_id UUID,
+    signal_id TEXT,
+    request_id TEXT NOT NULL,
+    ip TEXT
+);
+
+CREATE INDEX IF NOT EXISTS credential_access_user_idx ON credential_access(user_id, accessed_at, id);
@@ -0,0 +1,18 @@
+-- Текущие настройки стратегии; каждая запись настроек получает новую версию
+ALTER TABLE strategies ADD COLUMN IF NOT EXISTS config JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE strategies ADD COLUMN IF NOT EXISTS config_version INTEGER NOT NULL DEFAULT 1;
+-- Время последнего принятого сигнала (для cooldown)
+ALTER TABLE strategies ADD COLUMN IF NOT EXISTS last_signal_at TIMESTAMP;
+
+-- История версий настроек
+CREATE TABLE IF NOT EXISTS strategy_config_versions (
+    strategy_id UUID NOT NULL REFERENCES strategies(id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    config JSONB NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    PRIMARY KEY (strategy_id, version)
+);
+
+INSERT INTO strategy_config_versions (strategy_id, version, config)
+SELECT id, config_version, config FROM strategies
+ON CONFLICT DO NOTHING;
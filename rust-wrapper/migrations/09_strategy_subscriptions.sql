@@ -0,0 +1,19 @@
+-- Опубликованные стратегии видны в каталоге, на них можно подписаться
+ALTER TABLE strategies ADD COLUMN IF NOT EXISTS published BOOLEAN NOT NULL DEFAULT false;
+
+CREATE INDEX IF NOT EXISTS strategies_published_idx ON strategies(created_at, id) WHERE published;
+
+-- Подписка аккаунта последователя на чужую стратегию
+CREATE TABLE IF NOT EXISTS strategy_subscriptions (
+    id UUID PRIMARY KEY,
+    strategy_id UUID NOT NULL REFERENCES strategies(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    account_id UUID NOT NULL REFERENCES exchange_accounts(id) ON DELETE CASCADE,
+    sizing_multiplier DOUBLE PRECISION NOT NULL DEFAULT 1 CHECK (sizing_multiplier > 0),
+    active BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMP NOT NULL DEFAULT now(),
+    UNIQUE (strategy_id, account_id)
+);
+
+CREATE INDEX IF NOT EXISTS strategy_subscriptions_user_idx ON strategy_subscriptions(user_id, created_at, id);
+CREATE INDEX IF NOT EXISTS strategy_subscriptions_strategy_idx ON strategy_subscriptions(strategy_id) WHERE active;
@@ -0,0 +1,12 @@
+-- Вебхук стратегии адресуется случайным токеном, а не id.
+-- Для существующих стратегий токеном становится id, чтобы уже настроенные алерты
+-- продолжали работать до первой ротации.
+ALTER TABLE strategies ADD COLUMN IF NOT EXISTS webhook_token TEXT;
+UPDATE strategies SET webhook_token = id::text WHERE webhook_token IS NULL;
+ALTER TABLE strategies ALTER COLUMN webhook_token SET NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS strategies_webhook_token_idx ON strategies(webhook_token);
+
+-- Предыдущий токен после ротации действует до previous_webhook_token_expires_at
+ALTER TABLE strategies ADD COLUMN IF NOT EXISTS previous_webhook_token TEXT;
+ALTER TABLE strategies ADD COLUMN IF NOT EXISTS previous_webhook_token_expires_at TIMESTAMP;
+CREATE UNIQUE INDEX IF NOT EXISTS strategies_previous_webhook_token_idx ON strategies(previous_webhook_token);
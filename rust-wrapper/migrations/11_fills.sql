@@ -0,0 +1,29 @@
+-- Исполнения ордеров, о которых сообщает исполнитель (NATS `order-fills`)
+CREATE TABLE IF NOT EXISTS fills (
+    id UUID PRIMARY KEY,
+    account_id UUID NOT NULL REFERENCES exchange_accounts(id) ON DELETE CASCADE,
+    strategy_id UUID REFERENCES strategies(id) ON DELETE SET NULL,
+    subscription_id UUID REFERENCES strategy_subscriptions(id) ON DELETE SET NULL,
+    order_id TEXT NOT NULL,
+    exchange_fill_id TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    side TEXT NOT NULL CHECK (side IN ('buy', 'sell')),
+    quantity DOUBLE PRECISION NOT NULL CHECK (quantity > 0),
+    price DOUBLE PRECISION NOT NULL CHECK (price > 0),
+    fee DOUBLE PRECISION NOT NULL DEFAULT 0,
+    fee_currency TEXT,
+    filled_at TIMESTAMP NOT NULL,
+    recorded_at TIMESTAMP NOT NULL DEFAULT now(),
+    UNIQUE (account_id, exchange_fill_id)
+);
+
+CREATE INDEX IF NOT EXISTS fills_strategy_idx ON fills(strategy_id, filled_at, id);
+CREATE INDEX IF NOT EXISTS fills_account_idx ON fills(account_id, filled_at, id);
+
+-- Исполнение на аккаунте автора стратегии. Отдельный признак: `subscription_id` обнуляется при удалении
+-- подписки, и по нему одному исполнения бывшего подписчика попали бы в статистику автора
+ALTER TABLE fills ADD COLUMN IF NOT EXISTS is_author BOOLEAN;
+UPDATE fills SET is_author = subscription_id IS NULL
+    AND (strategy_id IS NULL OR account_id = (SELECT account_id FROM strategies WHERE strategies.id = fills.strategy_id))
+WHERE is_author IS NULL;
+ALTER TABLE fills ALTER COLUMN is_author SET NOT NULL;
@@ -0,0 +1,7 @@
+-- Право на импорт/экспорт получают токены, которые уже управляют токенами (администраторы)
+UPDATE api_tokens SET scopes = array_append(scopes, 'data:transfer')
+WHERE role = 'admin' AND 'tokens:manage' = ANY(scopes) AND NOT 'data:transfer' = ANY(scopes);
+
+ALTER TABLE credential_access DROP CONSTRAINT IF EXISTS credential_access_purpose_check;
+ALTER TABLE credential_access ADD CONSTRAINT credential_access_purpose_check
+    CHECK (purpose IN ('balance', 'signal', 'validation', 'transfer'));
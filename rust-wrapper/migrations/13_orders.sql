@@ -0,0 +1,4 @@
+-- Запрос состояния ордера через API: ключи расшифровываются с целью 'order'
+ALTER TABLE credential_access DROP CONSTRAINT IF EXISTS credential_access_purpose_check;
+ALTER TABLE credential_access ADD CONSTRAINT credential_access_purpose_check
+    CHECK (purpose IN ('balance', 'signal', 'validation', 'transfer', 'order'));
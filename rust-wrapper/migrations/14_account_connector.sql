@@ -0,0 +1,3 @@
+-- Через что аккаунт ходит на биржу: trading-gateway (ccxt) или встроенный коннектор
+ALTER TABLE exchange_accounts ADD COLUMN IF NOT EXISTS connector TEXT NOT NULL DEFAULT 'gateway'
+    CHECK (connector IN ('gateway', 'native'));
@@ -0,0 +1,16 @@
+-- Принятые сигналы стратегий и решение проверки отклонения цены
+CREATE TABLE IF NOT EXISTS signals (
+    id UUID PRIMARY KEY,
+    strategy_id UUID NOT NULL REFERENCES strategies(id) ON DELETE CASCADE,
+    signal_id TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    side TEXT NOT NULL CHECK (side IN ('buy', 'sell')),
+    signal_price DOUBLE PRECISION,
+    live_price DOUBLE PRECISION,
+    deviation_pct DOUBLE PRECISION,
+    slippage_decision TEXT NOT NULL CHECK (slippage_decision IN ('unchecked', 'passed', 'rejected', 'limited')),
+    order_price DOUBLE PRECISION,
+    received_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS signals_strategy_idx ON signals(strategy_id, received_at);
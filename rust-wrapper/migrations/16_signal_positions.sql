@@ -0,0 +1,5 @@
+-- Рынок сигнала и что он делает с позицией (открытие/закрытие лонга или шорта)
+ALTER TABLE signals ADD COLUMN IF NOT EXISTS market_type TEXT NOT NULL DEFAULT 'spot'
+    CHECK (market_type IN ('spot', 'perpetual', 'margin'));
+ALTER TABLE signals ADD COLUMN IF NOT EXISTS position_action TEXT NOT NULL DEFAULT 'open_long'
+    CHECK (position_action IN ('open_long', 'close_long', 'open_short', 'close_short'));
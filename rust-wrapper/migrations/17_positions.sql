@@ -0,0 +1,31 @@
+-- Позиции по исполнениям: чистый объём, средняя цена входа и реализованный PnL
+-- по аккаунту, стратегии (NULL — ордера вне стратегий) и символу.
+-- Позиции удалённой стратегии удаляются; её исполнения при перестроении попадут в позицию без стратегии.
+-- Позиции на споте, фьючерсах и марже по одному символу ведутся отдельно.
+-- Уже записанные исполнения загружаются в журнал при запуске сервиса, пока он пуст.
+ALTER TABLE fills ADD COLUMN IF NOT EXISTS market_type TEXT NOT NULL DEFAULT 'spot'
+    CHECK (market_type IN ('spot', 'perpetual', 'margin'));
+
+CREATE TABLE IF NOT EXISTS positions (
+    id UUID PRIMARY KEY,
+    account_id UUID NOT NULL REFERENCES exchange_accounts(id) ON DELETE CASCADE,
+    strategy_id UUID REFERENCES strategies(id) ON DELETE CASCADE,
+    symbol TEXT NOT NULL,
+    symbol_key TEXT NOT NULL,
+    market_type TEXT NOT NULL DEFAULT 'spot' CHECK (market_type IN ('spot', 'perpetual', 'margin')),
+    quantity DOUBLE PRECISION NOT NULL DEFAULT 0,
+    avg_entry_price DOUBLE PRECISION,
+    realized_pnl DOUBLE PRECISION NOT NULL DEFAULT 0,
+    fees DOUBLE PRECISION NOT NULL DEFAULT 0,
+    fill_count INTEGER NOT NULL DEFAULT 0,
+    last_fill_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT now(),
+    -- В порядке постраничного списка позиций
+    UNIQUE NULLS NOT DISTINCT (account_id, symbol_key, market_type, strategy_id)
+);
+
+CREATE INDEX IF NOT EXISTS positions_strategy_idx ON positions(strategy_id);
+
+-- Право на перестроение журнала получают токены, которые уже управляют токенами (администраторы)
+UPDATE api_tokens SET scopes = array_append(scopes, 'positions:rebuild')
+WHERE role = 'admin' AND 'tokens:manage' = ANY(scopes) AND NOT 'positions:rebuild' = ANY(scopes);
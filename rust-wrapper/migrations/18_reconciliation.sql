@@ -0,0 +1,23 @@
+-- Расхождения журнала позиций с балансами на биржах, найденные сверкой
+CREATE TABLE IF NOT EXISTS reconciliation_discrepancies (
+    id UUID PRIMARY KEY,
+    run_id UUID NOT NULL,
+    account_id UUID NOT NULL REFERENCES exchange_accounts(id) ON DELETE CASCADE,
+    asset TEXT NOT NULL,
+    expected DOUBLE PRECISION NOT NULL,
+    actual DOUBLE PRECISION NOT NULL,
+    difference_pct DOUBLE PRECISION NOT NULL,
+    severity TEXT NOT NULL CHECK (severity IN ('info', 'warning', 'critical')),
+    auto_disabled BOOLEAN NOT NULL DEFAULT false,
+    detected_at TIMESTAMP NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS reconciliation_discrepancies_detected_idx ON reconciliation_discrepancies(detected_at, id);
+CREATE INDEX IF NOT EXISTS reconciliation_discrepancies_account_idx ON reconciliation_discrepancies(account_id, detected_at);
+
+-- Когда сверка выключила стратегию (`enabled = false`); сбрасывается при включении
+ALTER TABLE strategies ADD COLUMN IF NOT EXISTS auto_disabled_at TIMESTAMP;
+
+ALTER TABLE credential_access DROP CONSTRAINT IF EXISTS credential_access_purpose_check;
+ALTER TABLE credential_access ADD CONSTRAINT credential_access_purpose_check
+    CHECK (purpose IN ('balance', 'signal', 'validation', 'transfer', 'order', 'reconciliation'));
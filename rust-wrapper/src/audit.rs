@@ -0,0 +1,203 @@
+use serde_json::{Map, Value};
+use sqlx::{PgConnection, PgExecutor};
+use uuid::Uuid;
+
+use crate::crypto::decrypt_secret;
+
+/// Поля, значения которых никогда не попадают в журнал
+const SECRET_FIELDS: [&str; 6] = [
+    "api_key",
+    "encrypted_secret",
+    "secret_key",
+    "token_hash",
+    "webhook_token",
+    "previous_webhook_token",
+];
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Кто и откуда выполняет действие
+#[derive(Debug, Clone)]
+pub struct AuditActor {
+    /// `token`, `admin_token`, `telegram`, `webhook` или `system`
+    pub kind: &'static str,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub request_id: String,
+    pub ip: Option<String>,
+}
+
+impl AuditActor {
+    /// Входящий вебхук TradingView: аутентификации нет, известны только id запроса и IP
+    pub fn webhook(request_id: String, ip: Option<String>) -> Self {
+        AuditActor { kind: "webhook", id: None, name: None, request_id, ip }
+    }
+
+    /// Фоновая задача сервиса (`name` — например, `reconciliation`); `request_id` — id её запуска
+    pub fn system(name: &str, request_id: String) -> Self {
+        AuditActor { kind: "system", id: None, name: Some(name.to_string()), request_id, ip: None }
+    }
+}
+
+/// Снимок строки таблицы в виде JSON (`to_jsonb`) для записи в журнал
+pub async fn snapshot(conn: &mut PgConnection, table: &'static str, id: Uuid) -> Result<Option<Value>, sqlx::Error> {
+    sqlx::query_scalar(&format!("SELECT to_jsonb(t) FROM {table} t WHERE id = $1"))
+        .bind(id)
+        .fetch_optional(conn)
+        .await
+}
+
+/// Оставляет в снимках только изменившиеся поля; значения секретов заменяются на `[REDACTED]`.
+///
+/// Если снимка нет (создание или удаление), второй сохраняется целиком, но тоже без секретов.
+pub fn diff(before: Option<Value>, after: Option<Value>) -> (Option<Value>, Option<Value>) {
+    match (before, after) {
+        (Some(Value::Object(before)), Some(Value::Object(after))) => {
+            let mut changed_before = Map::new();
+            let mut changed_after = Map::new();
+
+            for key in before.keys().chain(after.keys()) {
+                let (old, new) = (before.get(key), after.get(key));
+                if old != new && !changed_after.contains_key(key) {
+                    changed_before.insert(key.clone(), old.cloned().unwrap_or(Value::Null));
+                    changed_after.insert(key.clone(), new.cloned().unwrap_or(Value::Null));
+                }
+            }
+
+            (
+                Some(redact(Value::Object(changed_before))),
+                Some(redact(Value::Object(changed_after))),
+            )
+        }
+        (before, after) => (before.map(redact), after.map(redact)),
+    }
+}
+
+fn redact(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    if SECRET_FIELDS.contains(&key.as_str()) && !value.is_null() {
+                        (key, Value::String(REDACTED.to_string()))
+                    } else {
+                        (key, redact(value))
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(redact).collect()),
+        other => other,
+    }
+}
+
+/// Записывает событие в `audit_events`.
+///
+/// Вызывается в той же транзакции, что и само изменение, чтобы журнал не расходился с данными.
+pub async fn record(
+    conn: &mut PgConnection,
+    actor: &AuditActor,
+    action: &str,
+    target_type: &str,
+    target_id: Option<Uuid>,
+    before: Option<Value>,
+    after: Option<Value>,
+) -> Result<(), sqlx::Error> {
+    let (before, after) = diff(before, after);
+
+    sqlx::query!(
+        "INSERT INTO audit_events
+            (id, actor_kind, actor_id, actor_name, action, target_type, target_id, before, after, request_id, ip)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+        Uuid::new_v4(),
+        actor.kind,
+        actor.id,
+        actor.name,
+        action,
+        target_type,
+        target_id,
+        before,
+        after,
+        actor.request_id,
+        actor.ip
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Зачем расшифровываются ключи биржи
+#[derive(Debug, Clone, Copy)]
+pub enum CredentialPurpose {
+    Balance,
+    Signal,
+    Validation,
+    /// Перешифровка при выгрузке или сравнение при загрузке
+    Transfer,
+    /// Запрос состояния ордера через API
+    Order,
+    /// Сверка журнала позиций с балансом на бирже
+    Reconciliation,
+}
+
+impl CredentialPurpose {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CredentialPurpose::Balance => "balance",
+            CredentialPurpose::Signal => "signal",
+            CredentialPurpose::Validation => "validation",
+            CredentialPurpose::Transfer => "transfer",
+            CredentialPurpose::Order => "order",
+            CredentialPurpose::Reconciliation => "reconciliation",
+        }
+    }
+}
+
+/// Чьи ключи, зачем и в связи с какой стратегией/сигналом они расшифровываются
+pub struct CredentialAccess<'a> {
+    pub user_uid: Uuid,
+    pub account_uid: Uuid,
+    pub purpose: CredentialPurpose,
+    pub strategy_uid: Option<Uuid>,
+    pub signal_id: Option<&'a str>,
+}
+
+/// Расшифровывает секрет аккаунта, записывая обращение в `credential_access`.
+///
+/// Все расшифровки ключей бирж должны идти через эту функцию, а не через `decrypt_secret` напрямую.
+/// Запись делается и при неудачной расшифровке; если записать не удалось — секрет не отдаётся.
+/// Внутри транзакции передаётся её соединение: запись откатывается вместе с ней.
+pub async fn decrypt_credentials(
+    conn: impl PgExecutor<'_>,
+    actor: &AuditActor,
+    access: CredentialAccess<'_>,
+    encrypted_secret: &str,
+    master_key: &str,
+) -> Result<String, String> {
+    let decrypted = decrypt_secret(encrypted_secret, master_key).map_err(|e| format!("Decryption error: {e}"));
+
+    sqlx::query!(
+        "INSERT INTO credential_access
+            (id, user_id, account_id, purpose, success, caller_kind, caller_id, caller_name,
+             strategy_id, signal_id, request_id, ip)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+        Uuid::new_v4(),
+        access.user_uid,
+        access.account_uid,
+        access.purpose.as_str(),
+        decrypted.is_ok(),
+        actor.kind,
+        actor.id,
+        actor.name,
+        access.strategy_uid,
+        access.signal_id,
+        actor.request_id,
+        actor.ip
+    )
+    .execute(conn)
+    .await
+    .map_err(|e| format!("Audit error: {e}"))?;
+
+    decrypted
+}
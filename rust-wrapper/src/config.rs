@@ -3,17 +3,163 @@ use std::env;
 
 pub struct Config {
     pub domain: String,
-    pub admin_token: String,
+    /// Бутстрап-токен со всеми scope'ами — для выпуска первых API-токенов (необязателен)
+    pub admin_token: Option<String>,
     pub salt_key: String,
+    /// Токен Telegram-бота для проверки `initData` из mini-app (если не задан — вход через Telegram выключен)
+    pub telegram_bot_token: Option<String>,
+    /// Сколько секунд `initData` считается действительной
+    pub telegram_init_data_max_age: u64,
+    /// Адрес trading-gateway
+    pub gateway_url: String,
+    /// Таймаут одного запроса к trading-gateway, секунды
+    pub gateway_timeout_secs: u64,
+    /// Сколько раз повторять запрос при сетевой ошибке или 5xx
+    pub gateway_retries: u32,
+    /// Сколько подряд неудачных запросов к бирже размыкают предохранитель
+    pub breaker_failure_threshold: u32,
+    /// На сколько секунд предохранитель размыкается до пробного запроса
+    pub breaker_open_secs: u64,
+    /// Максимум одновременных запросов к одной бирже
+    pub gateway_max_concurrency: usize,
+    /// Сколько запрос ждёт свободного слота, прежде чем получить отказ, мс
+    pub gateway_queue_timeout_ms: u64,
+    /// Цена исполнения бумажных ордеров: `fixed:<price>`, `csv:<path>` или `last_signal`
+    pub paper_price_source: String,
+    /// Комиссия бумажной биржи, % от суммы сделки
+    pub paper_fee_pct: f64,
+    /// Слиппедж бумажной биржи, % от цены
+    pub paper_slippage_pct: f64,
+    /// Стартовый баланс бумажного счёта: `USDT:10000,BTC:0.5`
+    pub paper_initial_balance: String,
+    /// REST API Binance spot для встроенного коннектора (можно указать testnet или мок)
+    pub binance_base_url: String,
+    /// `recvWindow` подписанных запросов Binance, мс
+    pub binance_recv_window_ms: u64,
+    /// Как часто обновлять кэш торгуемых пар и их фильтров, секунды
+    pub markets_refresh_secs: u64,
+    /// Источник котировок: `none`, `binance` или `replay:<path>`
+    pub market_data_feed: String,
+    /// Символы, на которые подписывается источник котировок: `BTCUSDT,ETHUSDT`
+    pub market_data_symbols: String,
+    /// Адрес потоков Binance для источника котировок `binance`
+    pub binance_ws_url: String,
+    /// Пауза между строками источника `replay`, мс
+    pub market_data_replay_interval_ms: u64,
+    /// Как часто сверять журнал позиций с балансами на биржах, секунды; 0 — сверка выключена
+    pub reconcile_interval_secs: u64,
+    /// Расхождение до этого процента не записывается
+    pub reconcile_tolerance_pct: f64,
+    /// Нехватка актива больше этого процента — критическое расхождение
+    pub reconcile_critical_pct: f64,
+    /// Останавливать стратегии и подписки на аккаунте при критическом расхождении
+    pub reconcile_auto_disable: bool,
 }
 
 impl Config {
     pub fn from_env() -> Self {
         dotenv().ok();
         let domain = env::var("DOMAIN").expect("DOMAIN must be set");
-        let admin_token = env::var("ADMIN_TOKEN").expect("ADMIN_TOKEN must be set");
+        let admin_token = env::var("ADMIN_TOKEN").ok();
         let salt_key = env::var("SALT_KEY").expect("SALT_KEY must be set");
+        let telegram_bot_token = env::var("TELEGRAM_BOT_TOKEN").ok();
+        let telegram_init_data_max_age = env::var("TELEGRAM_INIT_DATA_MAX_AGE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(86400);
+        let gateway_url = env::var("GATEWAY_URL").unwrap_or_else(|_| "http://localhost:3000".to_string());
+        let gateway_timeout_secs = env::var("GATEWAY_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(15);
+        let gateway_retries = env::var("GATEWAY_RETRIES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(2);
+        let breaker_failure_threshold = env::var("GATEWAY_BREAKER_FAILURES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5);
+        let breaker_open_secs = env::var("GATEWAY_BREAKER_OPEN_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30);
+        let gateway_max_concurrency = env::var("GATEWAY_MAX_CONCURRENCY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(8);
+        let gateway_queue_timeout_ms = env::var("GATEWAY_QUEUE_TIMEOUT_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(2000);
+        let paper_price_source = env::var("PAPER_PRICE_SOURCE").unwrap_or_else(|_| "last_signal".to_string());
+        let paper_fee_pct = env::var("PAPER_FEE_PCT")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.1);
+        let paper_slippage_pct = env::var("PAPER_SLIPPAGE_PCT")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.05);
+        let paper_initial_balance = env::var("PAPER_INITIAL_BALANCE").unwrap_or_else(|_| "USDT:10000".to_string());
+        let binance_base_url = env::var("BINANCE_BASE_URL").unwrap_or_else(|_| "https://api.binance.com".to_string());
+        let binance_recv_window_ms = env::var("BINANCE_RECV_WINDOW_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5000);
+        let markets_refresh_secs = env::var("MARKETS_REFRESH_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(900);
+        let market_data_feed = env::var("MARKET_DATA_FEED").unwrap_or_else(|_| "none".to_string());
+        let market_data_symbols = env::var("MARKET_DATA_SYMBOLS").unwrap_or_default();
+        let binance_ws_url = env::var("BINANCE_WS_URL").unwrap_or_else(|_| "wss://stream.binance.com:9443".to_string());
+        let market_data_replay_interval_ms = env::var("MARKET_DATA_REPLAY_INTERVAL_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1000);
+        let reconcile_interval_secs = env::var("RECONCILE_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3600);
+        let reconcile_tolerance_pct = env::var("RECONCILE_TOLERANCE_PCT")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1.0);
+        let reconcile_critical_pct = env::var("RECONCILE_CRITICAL_PCT")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10.0);
+        let reconcile_auto_disable = env::var("RECONCILE_AUTO_DISABLE").is_ok_and(|v| v == "true" || v == "1");
 
-        Config { domain, admin_token, salt_key}
+        Config {
+            domain,
+            admin_token,
+            salt_key,
+            telegram_bot_token,
+            telegram_init_data_max_age,
+            gateway_url,
+            gateway_timeout_secs,
+            gateway_retries,
+            breaker_failure_threshold,
+            breaker_open_secs,
+            gateway_max_concurrency,
+            gateway_queue_timeout_ms,
+            paper_price_source,
+            paper_fee_pct,
+            paper_slippage_pct,
+            paper_initial_balance,
+            binance_base_url,
+            binance_recv_window_ms,
+            markets_refresh_secs,
+            market_data_feed,
+            market_data_symbols,
+            binance_ws_url,
+            market_data_replay_interval_ms,
+            reconcile_interval_secs,
+            reconcile_tolerance_pct,
+            reconcile_critical_pct,
+            reconcile_auto_disable,
+        }
     }
 }
@@ -1,5 +1,5 @@
 use aes_gcm::{
-    aead::{Aead, KeyInit, OsRng, generic_array::GenericArray},
+    aead::{Aead, KeyInit, OsRng},
     Aes256Gcm, // Можно взять 128/256
     Key, Nonce // 96-битный уникальный nonce
 };
@@ -28,7 +28,9 @@ pub fn encrypt_secret(plain_secret: &str, master_key: &str) -> Result<String, Bo
     let nonce = Nonce::from_slice(&nonce_bytes);
 
     // 3. Шифруем
-    let ciphertext = cipher.encrypt(nonce, plain_secret.as_bytes()).unwrap(); //TODO PROPER ERRORS
+    let ciphertext = cipher
+        .encrypt(nonce, plain_secret.as_bytes())
+        .map_err(|_| "Encryption failed")?;
 
     // 4. Формируем результирующую строку:
     // Первые 12 байт -> nonce
@@ -46,7 +48,7 @@ pub fn encrypt_secret(plain_secret: &str, master_key: &str) -> Result<String, Bo
 /// - `encrypted_hex` — результат `encrypt_secret`
 /// - `master_key` — ваш SALT_KEY из `.env`
 pub fn decrypt_secret(encrypted_hex: &str, master_key: &str) -> Result<String, Box<dyn Error>> {
-    let data = hex::decode(encrypted_hex).unwrap(); //TODO PROPER ERRORS
+    let data = hex::decode(encrypted_hex)?;
 
     // 1. Выделяем первые 12 байт под nonce
     if data.len() < 12 {
@@ -64,8 +66,32 @@ pub fn decrypt_secret(encrypted_hex: &str, master_key: &str) -> Result<String, B
     let nonce = Nonce::from_slice(nonce_bytes);
 
     // 3. Дешифруем
-    let plaintext = cipher.decrypt(nonce, ciphertext.as_ref()).unwrap(); //TODO PROPER ERRORS
-    let secret_str = String::from_utf8(plaintext); //TODO PROPER ERRORS
+    // Неверный SALT_KEY или повреждённые данные не проходят проверку тега GCM
+    let plaintext = cipher
+        .decrypt(nonce, ciphertext.as_ref())
+        .map_err(|_| "Decryption failed")?;
+    let secret_str = String::from_utf8(plaintext)?;
 
-    Ok(secret_str.unwrap())
+    Ok(secret_str)
+}
+
+/// Генерация нового API-токена: `mm_` + 32 случайных байта в hex
+pub fn generate_api_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    format!("mm_{}", hex::encode(bytes))
+}
+
+/// Генерация токена вебхука стратегии: 24 случайных байта в hex
+pub fn generate_webhook_token() -> String {
+    let mut bytes = [0u8; 24];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Хэш API-токена для хранения в базе
+///
+/// Токены случайные и длинные, поэтому быстрого хэша достаточно (соль не нужна).
+pub fn hash_api_token(token: &str) -> String {
+    blake3::hash(token.as_bytes()).to_hex().to_string()
 }
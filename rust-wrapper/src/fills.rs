@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use async_nats::Client;
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::gateway::{MarketType, Order, OrderSide, TradeRequest};
+use crate::positions::{apply_fill, LedgerFill};
+use crate::stats::StatsCache;
+
+/// Топик ордеров по сигналам. Сервис только публикует в него; ордера на живых аккаунтах
+/// выставляет внешний исполнитель (бумажные исполняются в процессе и сюда не попадают).
+pub const SIGNALS_TOPIC: &str = "trading-signals";
+
+/// Топик, в который исполнитель сообщает об исполнении ордеров.
+///
+/// Контракт исполнителя: на каждое исполнение (trade) ордера из [`SIGNALS_TOPIC`] — одно сообщение
+/// [`FillEvent`] в JSON. `accountUid`, `strategyUid`, `subscriptionUid` и `marketType` копируются из сигнала,
+/// `orderId` — id ордера на бирже, `fillId` — id исполнения на бирже. Частичные исполнения
+/// сообщаются по отдельности; повтор того же `fillId` безопасен. Без этих сообщений статистика
+/// стратегий, журнал позиций и сверка с биржей для живых аккаунтов остаются пустыми.
+pub const FILLS_TOPIC: &str = "order-fills";
+
+/// Сообщение исполнителя об исполнении (полном или частичном) ордера
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FillEvent {
+    /// `accountUid` из сигнала в `trading-signals`
+    pub account_uid: Uuid,
+    /// `strategyUid` из сигнала; `null` — ордер вне стратегий
+    pub strategy_uid: Option<Uuid>,
+    /// `subscriptionUid` из сигнала; `null` — аккаунт автора стратегии
+    pub subscription_uid: Option<Uuid>,
+    /// Id ордера на бирже
+    pub order_id: String,
+    /// Id исполнения на бирже; повторное сообщение с тем же id игнорируется
+    pub fill_id: String,
+    /// `symbol` из сигнала (`BTC/USDT`)
+    pub symbol: String,
+    /// `marketType` из сигнала; без него исполнение считается спотовым
+    #[serde(default)]
+    pub market_type: MarketType,
+    /// `buy` или `sell`
+    pub side: String,
+    pub quantity: f64,
+    pub price: f64,
+    /// Комиссия в валюте котировки
+    #[serde(default)]
+    pub fee: f64,
+    pub fee_currency: Option<String>,
+    /// Время исполнения на бирже (RFC 3339)
+    pub filled_at: DateTime<Utc>,
+}
+
+impl FillEvent {
+    /// Сообщение об исполнении ордера, исполненного в процессе (бумажная биржа), целиком одним fill
+    pub fn from_order(
+        order: &Order,
+        trade: &TradeRequest,
+        account_uid: Uuid,
+        strategy_uid: Option<Uuid>,
+        subscription_uid: Option<Uuid>,
+    ) -> Self {
+        let fee = order.fee.as_ref();
+        FillEvent {
+            account_uid,
+            strategy_uid,
+            subscription_uid,
+            order_id: order.id.clone(),
+            fill_id: order.id.clone(),
+            symbol: order.symbol.clone(),
+            market_type: trade.market_type,
+            side: if order.side == OrderSide::Buy { "buy" } else { "sell" }.to_string(),
+            quantity: order.filled.unwrap_or(trade.amount),
+            price: order.average.or(order.price).unwrap_or_default(),
+            fee: fee.and_then(|fee| fee.cost).unwrap_or_default(),
+            fee_currency: fee.and_then(|fee| fee.currency.clone()),
+            filled_at: Utc::now(),
+        }
+    }
+}
+
+/// Сохраняет исполнение и применяет его к журналу позиций. Возвращает `false`, если оно уже было записано.
+pub async fn record_fill(pool: &PgPool, fill: &FillEvent) -> Result<bool, String> {
+    let side = fill.side.to_lowercase();
+    if side != "buy" && side != "sell" {
+        return Err(format!("Invalid side: {}", fill.side));
+    }
+    if !(fill.quantity > 0.0 && fill.price > 0.0) {
+        return Err("Quantity and price must be positive".to_string());
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| format!("Database error: {e}"))?;
+    let inserted = sqlx::query!(
+        "INSERT INTO fills
+            (id, account_id, strategy_id, subscription_id, is_author, order_id, exchange_fill_id,
+             symbol, market_type, side, quantity, price, fee, fee_currency, filled_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+         ON CONFLICT (account_id, exchange_fill_id) DO NOTHING",
+        Uuid::new_v4(),
+        fill.account_uid,
+        fill.strategy_uid,
+        fill.subscription_uid,
+        fill.subscription_uid.is_none(),
+        fill.order_id,
+        fill.fill_id,
+        fill.symbol,
+        fill.market_type.as_str(),
+        side,
+        fill.quantity,
+        fill.price,
+        fill.fee,
+        fill.fee_currency,
+        fill.filled_at.naive_utc()
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Database error: {e}"))?
+    .rows_affected();
+    if inserted == 0 {
+        return Ok(false);
+    }
+
+    let ledger_fill = LedgerFill {
+        account_uid: fill.account_uid,
+        strategy_uid: fill.strategy_uid,
+        symbol: &fill.symbol,
+        market_type: fill.market_type,
+        side: &side,
+        quantity: fill.quantity,
+        price: fill.price,
+        fee: fill.fee,
+        filled_at: fill.filled_at.naive_utc(),
+    };
+    apply_fill(&mut tx, &ledger_fill).await.map_err(|e| format!("Position ledger error: {e}"))?;
+    tx.commit().await.map_err(|e| format!("Commit error: {e}"))?;
+
+    Ok(true)
+}
+
+/// Подписывается на `order-fills` и записывает исполнения в `fills`
+pub fn spawn_fill_consumer(client: Client, pool: PgPool, stats_cache: Arc<StatsCache>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut subscriber = match client.subscribe(FILLS_TOPIC.to_string()).await {
+            Ok(subscriber) => subscriber,
+            Err(e) => {
+                eprintln!("❌ Failed to subscribe to {FILLS_TOPIC}: {:?}", e);
+                return;
+            }
+        };
+
+        while let Some(message) = subscriber.next().await {
+            let fill: FillEvent = match serde_json::from_slice(&message.payload) {
+                Ok(fill) => fill,
+                Err(e) => {
+                    eprintln!("Invalid fill message: {e}");
+                    continue;
+                }
+            };
+
+            match record_fill(&pool, &fill).await {
+                Ok(true) => {
+                    if let Some(strategy_uid) = fill.strategy_uid {
+                        stats_cache.invalidate(strategy_uid);
+                    }
+                }
+                Ok(false) => {}
+                Err(e) => eprintln!("Failed to record fill {}: {e}", fill.fill_id),
+            }
+        }
+    })
+}
@@ -0,0 +1,663 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Method, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::Sha256;
+
+use super::{
+    AssetBalance, Balance, ExchangeCredentials, ExchangeGateway, GatewayError, Market, MarketType, Order, OrderFee,
+    OrderKind, OrderSide, PositionSettings, TimeInForce, TradeRequest,
+};
+use crate::config::Config;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Встроенный коннектор Binance spot (REST API v3) — для аккаунтов с `connector = "native"`.
+///
+/// Через него идут балансы, состояние ордеров и спотовые пары. Ордера по сигналам и у таких аккаунтов
+/// выставляет внешний исполнитель из `trading-signals` (поле `connector` в сообщении), а не этот коннектор.
+///
+/// Подписанные запросы — HMAC-SHA256 от строки запроса секретом аккаунта, ключ в `X-MBX-APIKEY`.
+/// Адрес настраивается (`BINANCE_BASE_URL`), так что коннектор можно направить на testnet или мок-сервер.
+pub struct BinanceSpot {
+    client: Client,
+    base_url: String,
+    recv_window_ms: u64,
+}
+
+#[derive(Deserialize)]
+struct BinanceError {
+    code: i64,
+    msg: String,
+}
+
+#[derive(Deserialize)]
+struct AccountInfo {
+    balances: Vec<BinanceBalance>,
+}
+
+#[derive(Deserialize)]
+struct BinanceBalance {
+    asset: String,
+    free: String,
+    locked: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceOrder {
+    order_id: i64,
+    client_order_id: Option<String>,
+    price: Option<String>,
+    orig_qty: String,
+    executed_qty: String,
+    cummulative_quote_qty: Option<String>,
+    status: String,
+    #[serde(rename = "type")]
+    order_type: String,
+    side: String,
+    transact_time: Option<i64>,
+    time: Option<i64>,
+    #[serde(default)]
+    fills: Vec<BinanceFill>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceFill {
+    commission: String,
+    commission_asset: String,
+}
+
+#[derive(Deserialize)]
+struct ExchangeInfo {
+    symbols: Vec<SymbolInfo>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SymbolInfo {
+    symbol: String,
+    status: String,
+    base_asset: String,
+    quote_asset: String,
+    #[serde(default)]
+    filters: Vec<Value>,
+}
+
+/// `BTC/USDT` → `BTCUSDT`
+fn exchange_symbol(symbol: &str) -> String {
+    let symbol = symbol.split(':').next().unwrap_or(symbol);
+    symbol.replace('/', "").to_uppercase()
+}
+
+fn number(raw: &str) -> Option<f64> {
+    raw.parse().ok()
+}
+
+/// Статусы Binance в терминах ccxt: `open`, `closed`, `canceled`, `rejected`
+fn order_status(status: &str) -> &'static str {
+    match status {
+        "FILLED" => "closed",
+        "CANCELED" | "PENDING_CANCEL" | "EXPIRED" | "EXPIRED_IN_MATCH" => "canceled",
+        "REJECTED" => "rejected",
+        _ => "open",
+    }
+}
+
+impl BinanceOrder {
+    fn into_order(self, symbol: &str) -> Result<Order, GatewayError> {
+        let side = match self.side.as_str() {
+            "BUY" => OrderSide::Buy,
+            "SELL" => OrderSide::Sell,
+            other => return Err(GatewayError::InvalidResponse(format!("unknown side {other}"))),
+        };
+        let amount = number(&self.orig_qty);
+        let filled = number(&self.executed_qty);
+        let cost = self.cummulative_quote_qty.as_deref().and_then(number);
+        let average = match (cost, filled) {
+            (Some(cost), Some(filled)) if filled > 0.0 => Some(cost / filled),
+            _ => None,
+        };
+        // У рыночных ордеров price = 0
+        let price = self.price.as_deref().and_then(number).filter(|p| *p > 0.0).or(average);
+
+        // Комиссия суммируется, только если все исполнения в одной валюте
+        let fee = match self.fills.first() {
+            Some(first) if self.fills.iter().all(|f| f.commission_asset == first.commission_asset) => Some(OrderFee {
+                cost: Some(self.fills.iter().filter_map(|f| number(&f.commission)).sum()),
+                currency: Some(first.commission_asset.clone()),
+            }),
+            _ => None,
+        };
+
+        Ok(Order {
+            id: self.order_id.to_string(),
+            client_order_id: self.client_order_id,
+            symbol: symbol.to_string(),
+            side,
+            order_type: Some(self.order_type.to_lowercase()),
+            status: Some(order_status(&self.status).to_string()),
+            price,
+            average,
+            amount,
+            filled,
+            remaining: amount.zip(filled).map(|(amount, filled)| amount - filled),
+            cost,
+            fee,
+            timestamp: self.transact_time.or(self.time),
+        })
+    }
+}
+
+fn filter_value(filters: &[Value], filter_type: &str, field: &str) -> Option<f64> {
+    filters
+        .iter()
+        .find(|f| f["filterType"] == filter_type)
+        .and_then(|f| f[field].as_str())
+        .and_then(number)
+}
+
+impl BinanceSpot {
+    pub fn new(base_url: &str, timeout: Duration, recv_window_ms: u64) -> Result<Self, reqwest::Error> {
+        let client = Client::builder()
+            .timeout(timeout)
+            .connect_timeout(timeout.min(Duration::from_secs(5)))
+            .pool_idle_timeout(Duration::from_secs(90))
+            .build()?;
+
+        Ok(BinanceSpot { client, base_url: base_url.trim_end_matches('/').to_string(), recv_window_ms })
+    }
+
+    pub fn from_config(config: &Config) -> Result<Self, reqwest::Error> {
+        BinanceSpot::new(
+            &config.binance_base_url,
+            Duration::from_secs(config.gateway_timeout_secs),
+            config.binance_recv_window_ms,
+        )
+    }
+
+    fn url(&self, path: &str) -> Result<Url, GatewayError> {
+        Url::parse(&format!("{}{}", self.base_url, path)).map_err(|e| GatewayError::Unavailable(format!("Invalid URL: {e}")))
+    }
+
+    /// Подписанный запрос: к параметрам добавляются `timestamp`, `recvWindow` и `signature`
+    async fn signed<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        credentials: &ExchangeCredentials<'_>,
+        params: &[(&str, String)],
+    ) -> Result<T, GatewayError> {
+        let mut url = self.url(path)?;
+        url.query_pairs_mut()
+            .extend_pairs(params)
+            .append_pair("recvWindow", &self.recv_window_ms.to_string())
+            .append_pair("timestamp", &Utc::now().timestamp_millis().to_string());
+
+        let mut mac = HmacSha256::new_from_slice(credentials.secret.as_bytes()).expect("HMAC accepts any key length");
+        mac.update(url.query().unwrap_or_default().as_bytes());
+        let signature = hex::encode(mac.finalize().into_bytes());
+        url.query_pairs_mut().append_pair("signature", &signature);
+
+        let resp = self
+            .client
+            .request(method, url)
+            .header("X-MBX-APIKEY", credentials.api_key)
+            .send()
+            .await
+            .map_err(|e| GatewayError::Unavailable(e.to_string()))?;
+        Self::parse(resp).await
+    }
+
+    async fn parse<T: DeserializeOwned>(resp: Response) -> Result<T, GatewayError> {
+        let status = resp.status();
+        // 429/418 — превышен лимит запросов (418 — IP забанен на время)
+        if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS || status.as_u16() == 418 {
+            return Err(GatewayError::Unavailable(format!("Binance HTTP {status}")));
+        }
+        if status.is_client_error() {
+            return Err(match resp.json::<BinanceError>().await {
+                Ok(e) => GatewayError::Rejected(format!("Binance error {}: {}", e.code, e.msg)),
+                Err(_) => GatewayError::Rejected(format!("Binance HTTP {status}")),
+            });
+        }
+        resp.json::<T>().await.map_err(|e| GatewayError::InvalidResponse(e.to_string()))
+    }
+
+    /// Пары и фильтры из `GET /api/v3/exchangeInfo` (публичный запрос)
+    pub async fn exchange_info(&self) -> Result<Vec<Market>, GatewayError> {
+        let resp = self
+            .client
+            .get(self.url("/api/v3/exchangeInfo")?)
+            .send()
+            .await
+            .map_err(|e| GatewayError::Unavailable(e.to_string()))?;
+        let info: ExchangeInfo = Self::parse(resp).await?;
+
+        Ok(info
+            .symbols
+            .into_iter()
+            .map(|s| Market {
+                symbol: format!("{}/{}", s.base_asset, s.quote_asset),
+                market_type: Some(MarketType::Spot),
+                active: s.status == "TRADING",
+                tick_size: filter_value(&s.filters, "PRICE_FILTER", "tickSize"),
+                step_size: filter_value(&s.filters, "LOT_SIZE", "stepSize"),
+                min_qty: filter_value(&s.filters, "LOT_SIZE", "minQty"),
+                min_notional: filter_value(&s.filters, "NOTIONAL", "minNotional")
+                    .or_else(|| filter_value(&s.filters, "MIN_NOTIONAL", "minNotional")),
+                exchange_symbol: s.symbol,
+                base: s.base_asset,
+                quote: s.quote_asset,
+            })
+            .collect())
+    }
+}
+
+#[rocket::async_trait]
+impl ExchangeGateway for BinanceSpot {
+    async fn balance(&self, credentials: &ExchangeCredentials<'_>) -> Result<Balance, GatewayError> {
+        let params = [("omitZeroBalances", "true".to_string())];
+        let account: AccountInfo = self.signed(Method::GET, "/api/v3/account", credentials, &params).await?;
+
+        let mut assets: Vec<AssetBalance> = account
+            .balances
+            .into_iter()
+            .map(|b| {
+                let (free, used) = (number(&b.free), number(&b.locked));
+                AssetBalance { asset: b.asset, free, used, total: free.zip(used).map(|(f, u)| f + u) }
+            })
+            .collect();
+        assets.sort_by(|a, b| a.asset.cmp(&b.asset));
+
+        Ok(Balance { assets })
+    }
+
+    async fn trade(&self, credentials: &ExchangeCredentials<'_>, trade: &TradeRequest) -> Result<Order, GatewayError> {
+        let side = match trade.side {
+            OrderSide::Buy => "BUY",
+            OrderSide::Sell => "SELL",
+        };
+        if trade.market_type != MarketType::Spot {
+            return Err(GatewayError::Rejected(format!(
+                "Native Binance connector trades spot only, not {}",
+                trade.market_type.as_str()
+            )));
+        }
+        if trade.reduce_only {
+            return Err(GatewayError::Rejected("reduceOnly is not supported on Binance spot".to_string()));
+        }
+
+        let mut params = vec![
+            ("symbol", exchange_symbol(&trade.symbol)),
+            ("side", side.to_string()),
+            ("quantity", trade.amount.to_string()),
+            ("newOrderRespType", "FULL".to_string()),
+        ];
+        let time_in_force = trade.time_in_force.unwrap_or(TimeInForce::Gtc).as_str().to_string();
+        let price = || trade.price.ok_or_else(|| GatewayError::Rejected("Price is required".to_string()));
+        match trade.kind() {
+            OrderKind::Market => params.push(("type", "MARKET".to_string())),
+            // LIMIT_MAKER — post-only у Binance spot, timeInForce у него нет
+            OrderKind::Limit if trade.post_only => {
+                params.extend([("type", "LIMIT_MAKER".to_string()), ("price", price()?.to_string())])
+            }
+            OrderKind::Limit => params.extend([
+                ("type", "LIMIT".to_string()),
+                ("timeInForce", time_in_force),
+                ("price", price()?.to_string()),
+            ]),
+            OrderKind::StopLimit => {
+                let stop_price = trade
+                    .stop_price
+                    .ok_or_else(|| GatewayError::Rejected("stopPrice is required".to_string()))?;
+                params.extend([
+                    ("type", "STOP_LOSS_LIMIT".to_string()),
+                    ("timeInForce", time_in_force),
+                    ("price", price()?.to_string()),
+                    ("stopPrice", stop_price.to_string()),
+                ]);
+            }
+        }
+        if let Some(client_order_id) = &trade.client_order_id {
+            params.push(("newClientOrderId", client_order_id.clone()));
+        }
+
+        let order: BinanceOrder = self.signed(Method::POST, "/api/v3/order", credentials, &params).await?;
+        order.into_order(&trade.symbol)
+    }
+
+    async fn order_status(
+        &self,
+        credentials: &ExchangeCredentials<'_>,
+        symbol: &str,
+        order_id: &str,
+    ) -> Result<Order, GatewayError> {
+        let params = [("symbol", exchange_symbol(symbol)), ("orderId", order_id.to_string())];
+        let order: BinanceOrder = self.signed(Method::GET, "/api/v3/order", credentials, &params).await?;
+        order.into_order(symbol)
+    }
+
+    async fn markets(&self, exchange: &str) -> Result<Vec<Market>, GatewayError> {
+        if exchange != "binance" {
+            return Err(GatewayError::Rejected(format!("Native connector is not available for {exchange}")));
+        }
+        self.exchange_info().await
+    }
+
+    async fn configure_position(
+        &self,
+        _credentials: &ExchangeCredentials<'_>,
+        settings: &PositionSettings,
+    ) -> Result<(), GatewayError> {
+        Err(GatewayError::Rejected(format!(
+            "Native Binance connector trades spot only, not {}",
+            settings.market_type.as_str()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+    use uuid::Uuid;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    const SECRET: &str = "test-secret";
+
+    fn connector(server: &MockServer) -> BinanceSpot {
+        BinanceSpot::new(&server.uri(), Duration::from_secs(5), 5000).unwrap()
+    }
+
+    fn credentials() -> ExchangeCredentials<'static> {
+        ExchangeCredentials {
+            account_uid: Uuid::nil(),
+            exchange: "binance",
+            connector: "native",
+            api_key: "test-key",
+            secret: SECRET,
+        }
+    }
+
+    fn trade(order_type: Option<OrderKind>, price: Option<f64>) -> TradeRequest {
+        TradeRequest {
+            symbol: "BTC/USDT".to_string(),
+            side: OrderSide::Buy,
+            amount: 0.01,
+            price,
+            order_type,
+            stop_price: None,
+            time_in_force: None,
+            post_only: false,
+            reduce_only: false,
+            client_order_id: None,
+            market_type: MarketType::Spot,
+            margin_mode: None,
+        }
+    }
+
+    fn order_response(order_type: &str, status: &str) -> Value {
+        json!({
+            "symbol": "BTCUSDT",
+            "orderId": 28,
+            "clientOrderId": "mw1",
+            "transactTime": 1507725176595i64,
+            "price": "0.00000000",
+            "origQty": "0.01000000",
+            "executedQty": "0.01000000",
+            "cummulativeQuoteQty": "600.00000000",
+            "status": status,
+            "type": order_type,
+            "side": "BUY",
+            "fills": [
+                { "price": "60000", "qty": "0.006", "commission": "0.3", "commissionAsset": "USDT" },
+                { "price": "60000", "qty": "0.004", "commission": "0.2", "commissionAsset": "USDT" }
+            ]
+        })
+    }
+
+    /// Параметры единственного запроса, полученного мок-сервером
+    async fn sent_params(server: &MockServer) -> HashMap<String, String> {
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        requests[0].url.query_pairs().into_owned().collect()
+    }
+
+    async fn place(order: &TradeRequest, binance_type: &str) -> HashMap<String, String> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v3/order"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(order_response(binance_type, "NEW")))
+            .mount(&server)
+            .await;
+
+        connector(&server).trade(&credentials(), order).await.unwrap();
+        sent_params(&server).await
+    }
+
+    #[tokio::test]
+    async fn signs_the_query_with_the_account_secret() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v3/account"))
+            .and(header("X-MBX-APIKEY", "test-key"))
+            .and(query_param("omitZeroBalances", "true"))
+            .and(query_param("recvWindow", "5000"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "balances": [] })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        connector(&server).balance(&credentials()).await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let query = requests[0].url.query().unwrap();
+        let (payload, signature) = query.rsplit_once("&signature=").unwrap();
+        assert!(payload.contains("timestamp="));
+        let mut mac = HmacSha256::new_from_slice(SECRET.as_bytes()).unwrap();
+        mac.update(payload.as_bytes());
+        assert_eq!(signature, hex::encode(mac.finalize().into_bytes()));
+    }
+
+    #[tokio::test]
+    async fn parses_balances_with_locked_funds() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v3/account"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "makerCommission": 10,
+                "balances": [
+                    { "asset": "USDT", "free": "100.5", "locked": "0.00000000" },
+                    { "asset": "BTC", "free": "0.50000000", "locked": "0.25000000" }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        let balance = connector(&server).balance(&credentials()).await.unwrap();
+
+        let assets: Vec<_> = balance.assets.iter().map(|a| (a.asset.as_str(), a.free, a.used, a.total)).collect();
+        assert_eq!(
+            assets,
+            vec![("BTC", Some(0.5), Some(0.25), Some(0.75)), ("USDT", Some(100.5), Some(0.0), Some(100.5))]
+        );
+    }
+
+    #[tokio::test]
+    async fn market_order_params_and_fill_parsing() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v3/order"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(order_response("MARKET", "FILLED")))
+            .mount(&server)
+            .await;
+        let mut order = trade(None, None);
+        order.client_order_id = Some("mw1".to_string());
+
+        let placed = connector(&server).trade(&credentials(), &order).await.unwrap();
+
+        let params = sent_params(&server).await;
+        assert_eq!(params["symbol"], "BTCUSDT");
+        assert_eq!(params["side"], "BUY");
+        assert_eq!(params["type"], "MARKET");
+        assert_eq!(params["quantity"], "0.01");
+        assert_eq!(params["newOrderRespType"], "FULL");
+        assert_eq!(params["newClientOrderId"], "mw1");
+        assert!(!params.contains_key("price") && !params.contains_key("timeInForce"));
+
+        assert_eq!(placed.id, "28");
+        assert_eq!(placed.status.as_deref(), Some("closed"));
+        assert_eq!(placed.average, Some(60000.0));
+        assert_eq!(placed.price, Some(60000.0));
+        assert_eq!(placed.remaining, Some(0.0));
+        let fee = placed.fee.unwrap();
+        assert!((fee.cost.unwrap() - 0.5).abs() < 1e-9);
+        assert_eq!(fee.currency.as_deref(), Some("USDT"));
+    }
+
+    #[tokio::test]
+    async fn limit_order_params() {
+        let mut order = trade(None, Some(59000.0));
+        order.time_in_force = Some(TimeInForce::Ioc);
+
+        let params = place(&order, "LIMIT").await;
+
+        assert_eq!(params["type"], "LIMIT");
+        assert_eq!(params["price"], "59000");
+        assert_eq!(params["timeInForce"], "IOC");
+    }
+
+    #[tokio::test]
+    async fn post_only_limit_is_limit_maker_without_time_in_force() {
+        let mut order = trade(Some(OrderKind::Limit), Some(59000.0));
+        order.post_only = true;
+
+        let params = place(&order, "LIMIT_MAKER").await;
+
+        assert_eq!(params["type"], "LIMIT_MAKER");
+        assert_eq!(params["price"], "59000");
+        assert!(!params.contains_key("timeInForce"));
+    }
+
+    #[tokio::test]
+    async fn stop_limit_is_stop_loss_limit_with_stop_price() {
+        let mut order = trade(Some(OrderKind::StopLimit), Some(61100.0));
+        order.stop_price = Some(61000.0);
+
+        let params = place(&order, "STOP_LOSS_LIMIT").await;
+
+        assert_eq!(params["type"], "STOP_LOSS_LIMIT");
+        assert_eq!(params["price"], "61100");
+        assert_eq!(params["stopPrice"], "61000");
+        assert_eq!(params["timeInForce"], "GTC");
+    }
+
+    #[tokio::test]
+    async fn rejects_unsupported_orders_without_a_request() {
+        let server = MockServer::start().await;
+        let binance = connector(&server);
+
+        let mut perpetual = trade(None, None);
+        perpetual.market_type = MarketType::Perpetual;
+        let mut reduce_only = trade(None, None);
+        reduce_only.reduce_only = true;
+        let stop_without_trigger = trade(Some(OrderKind::StopLimit), Some(61000.0));
+
+        for order in [perpetual, reduce_only, stop_without_trigger] {
+            assert!(matches!(binance.trade(&credentials(), &order).await, Err(GatewayError::Rejected(_))));
+        }
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn maps_http_errors() {
+        let cases = [
+            (ResponseTemplate::new(400).set_body_json(json!({ "code": -2010, "msg": "Insufficient balance" })), "rejected"),
+            (ResponseTemplate::new(401).set_body_string("Unauthorized"), "rejected"),
+            (ResponseTemplate::new(429), "unavailable"),
+            (ResponseTemplate::new(418), "unavailable"),
+            (ResponseTemplate::new(503), "unavailable"),
+            (ResponseTemplate::new(200).set_body_string("not json"), "invalid"),
+        ];
+
+        for (response, expected) in cases {
+            let server = MockServer::start().await;
+            Mock::given(method("POST")).and(path("/api/v3/order")).respond_with(response).mount(&server).await;
+
+            let result = connector(&server).trade(&credentials(), &trade(None, None)).await;
+            let kind = match &result {
+                Err(GatewayError::Rejected(_)) => "rejected",
+                Err(GatewayError::Unavailable(_)) => "unavailable",
+                Err(GatewayError::InvalidResponse(_)) => "invalid",
+                _ => "other",
+            };
+            assert_eq!(kind, expected, "{result:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn rejection_keeps_the_binance_error_code() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v3/order"))
+            .respond_with(
+                ResponseTemplate::new(400).set_body_json(json!({ "code": -1013, "msg": "Filter failure: LOT_SIZE" })),
+            )
+            .mount(&server)
+            .await;
+
+        let err = connector(&server).trade(&credentials(), &trade(None, None)).await.unwrap_err();
+
+        assert!(matches!(err, GatewayError::Rejected(msg) if msg == "Binance error -1013: Filter failure: LOT_SIZE"));
+    }
+
+    #[tokio::test]
+    async fn extracts_exchange_info_filters() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v3/exchangeInfo"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "symbols": [
+                    {
+                        "symbol": "BTCUSDT", "status": "TRADING", "baseAsset": "BTC", "quoteAsset": "USDT",
+                        "filters": [
+                            { "filterType": "PRICE_FILTER", "minPrice": "0.01", "maxPrice": "1000000", "tickSize": "0.01" },
+                            { "filterType": "LOT_SIZE", "minQty": "0.00001", "maxQty": "9000", "stepSize": "0.00001" },
+                            { "filterType": "NOTIONAL", "minNotional": "5.00000000", "applyMinToMarket": true }
+                        ]
+                    },
+                    {
+                        "symbol": "OLDBTC", "status": "BREAK", "baseAsset": "OLD", "quoteAsset": "BTC",
+                        "filters": [ { "filterType": "MIN_NOTIONAL", "minNotional": "0.0001" } ]
+                    }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        let markets = connector(&server).markets("binance").await.unwrap();
+
+        let btc = &markets[0];
+        assert_eq!((btc.symbol.as_str(), btc.exchange_symbol.as_str()), ("BTC/USDT", "BTCUSDT"));
+        assert!(btc.active);
+        assert_eq!(btc.tick_size, Some(0.01));
+        assert_eq!(btc.step_size, Some(0.00001));
+        assert_eq!(btc.min_qty, Some(0.00001));
+        assert_eq!(btc.min_notional, Some(5.0));
+
+        let old = &markets[1];
+        assert!(!old.active);
+        assert_eq!(old.min_notional, Some(0.0001));
+        assert_eq!(old.tick_size, None);
+    }
+}
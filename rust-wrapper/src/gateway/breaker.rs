@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::Semaphore;
+
+use super::{
+    connector_route, Balance, ExchangeCredentials, ExchangeGateway, GatewayError, Market, Order, PositionSettings,
+    TradeRequest, NATIVE_CONNECTOR,
+};
+use crate::config::Config;
+use crate::types::BreakerStatus;
+
+enum BreakerState {
+    Closed,
+    /// Запросы отклоняются сразу до `until`
+    Open { until: Instant },
+    /// Пропущен один пробный запрос; остальные отклоняются, пока он не завершится
+    /// (или не истечёт `open_for` — если пробный запрос так и не вернулся)
+    HalfOpen { probe_started: Instant },
+}
+
+/// Биржа и коннектор, через который к ней идут запросы: падение trading-gateway
+/// не должно закрывать путь аккаунтам со встроенным коннектором
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct BreakerKey {
+    connector: &'static str,
+    exchange: String,
+}
+
+impl BreakerKey {
+    fn new(exchange: &str, connector: &str) -> Self {
+        BreakerKey { connector: connector_route(exchange, connector), exchange: exchange.to_string() }
+    }
+}
+
+impl From<&ExchangeCredentials<'_>> for BreakerKey {
+    fn from(credentials: &ExchangeCredentials<'_>) -> Self {
+        BreakerKey::new(credentials.exchange, credentials.connector)
+    }
+}
+
+impl fmt::Display for BreakerKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.exchange, self.connector)
+    }
+}
+
+struct ExchangeBreaker {
+    state: BreakerState,
+    consecutive_failures: u32,
+    total_failures: u64,
+    fast_failures: u64,
+    last_error: Option<String>,
+    permits: Arc<Semaphore>,
+}
+
+/// Предохранители и лимиты параллельных запросов по биржам (отдельно для каждого коннектора)
+pub struct CircuitBreakers {
+    failure_threshold: u32,
+    open_for: Duration,
+    max_concurrency: usize,
+    queue_timeout: Duration,
+    exchanges: Mutex<HashMap<BreakerKey, ExchangeBreaker>>,
+}
+
+impl CircuitBreakers {
+    pub fn from_config(config: &Config) -> Self {
+        CircuitBreakers {
+            failure_threshold: config.breaker_failure_threshold.max(1),
+            open_for: Duration::from_secs(config.breaker_open_secs),
+            max_concurrency: config.gateway_max_concurrency.max(1),
+            queue_timeout: Duration::from_millis(config.gateway_queue_timeout_ms),
+            exchanges: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Пускает запрос к бирже или сразу отказывает. Возвращает семафор биржи.
+    fn admit(&self, key: &BreakerKey) -> Result<Arc<Semaphore>, GatewayError> {
+        let mut exchanges = self.exchanges.lock().unwrap();
+        let breaker = exchanges.entry(key.clone()).or_insert_with(|| ExchangeBreaker {
+            state: BreakerState::Closed,
+            consecutive_failures: 0,
+            total_failures: 0,
+            fast_failures: 0,
+            last_error: None,
+            permits: Arc::new(Semaphore::new(self.max_concurrency)),
+        });
+
+        let now = Instant::now();
+        let retry_at = match breaker.state {
+            BreakerState::Closed => None,
+            BreakerState::Open { until } if now < until => Some(until),
+            BreakerState::HalfOpen { probe_started } if now < probe_started + self.open_for => {
+                Some(probe_started + self.open_for)
+            }
+            // Пора (или снова пора) пропустить пробный запрос
+            _ => {
+                breaker.state = BreakerState::HalfOpen { probe_started: now };
+                None
+            }
+        };
+
+        match retry_at {
+            Some(retry_at) => {
+                breaker.fast_failures += 1;
+                Err(GatewayError::CircuitOpen {
+                    exchange: key.to_string(),
+                    retry_in: retry_at.saturating_duration_since(now),
+                })
+            }
+            None => Ok(breaker.permits.clone()),
+        }
+    }
+
+    /// Отказ биржи (`Rejected`) — тоже ответ: предохранитель срабатывает только на недоступность
+    fn record<T>(&self, key: &BreakerKey, result: &Result<T, GatewayError>) {
+        let mut exchanges = self.exchanges.lock().unwrap();
+        let Some(breaker) = exchanges.get_mut(key) else {
+            return;
+        };
+
+        match result {
+            Err(e @ (GatewayError::Unavailable(_) | GatewayError::InvalidResponse(_))) => {
+                breaker.consecutive_failures += 1;
+                breaker.total_failures += 1;
+                breaker.last_error = Some(e.to_string());
+
+                let probe_failed = matches!(breaker.state, BreakerState::HalfOpen { .. });
+                if probe_failed || breaker.consecutive_failures >= self.failure_threshold {
+                    if !matches!(breaker.state, BreakerState::Open { .. }) {
+                        eprintln!("⚠️ Circuit for {key} opened after {} failure(s): {e}", breaker.consecutive_failures);
+                    }
+                    breaker.state = BreakerState::Open { until: Instant::now() + self.open_for };
+                }
+            }
+            _ => {
+                if !matches!(breaker.state, BreakerState::Closed) {
+                    println!("✅ Circuit for {key} closed");
+                }
+                breaker.state = BreakerState::Closed;
+                breaker.consecutive_failures = 0;
+            }
+        }
+    }
+
+    /// Выполняет запрос под предохранителем и лимитом параллельности биржи
+    async fn call<T, F>(&self, key: BreakerKey, request: F) -> Result<T, GatewayError>
+    where
+        F: Future<Output = Result<T, GatewayError>>,
+    {
+        let permits = self.admit(&key)?;
+        let _permit = match tokio::time::timeout(self.queue_timeout, permits.acquire_owned()).await {
+            Ok(Ok(permit)) => permit,
+            _ => {
+                if let Some(breaker) = self.exchanges.lock().unwrap().get_mut(&key) {
+                    breaker.fast_failures += 1;
+                }
+                return Err(GatewayError::Overloaded(key.to_string()));
+            }
+        };
+
+        let result = request.await;
+        self.record(&key, &result);
+        result
+    }
+
+    /// Состояние предохранителей для диагностики
+    pub fn snapshot(&self) -> Vec<BreakerStatus> {
+        let exchanges = self.exchanges.lock().unwrap();
+        let now = Instant::now();
+
+        let mut statuses: Vec<BreakerStatus> = exchanges
+            .iter()
+            .map(|(key, breaker)| {
+                let (state, retry_at) = match breaker.state {
+                    BreakerState::Closed => ("closed", None),
+                    BreakerState::Open { until } => ("open", Some(until)),
+                    BreakerState::HalfOpen { probe_started } => ("half_open", Some(probe_started + self.open_for)),
+                };
+                BreakerStatus {
+                    exchange: key.exchange.clone(),
+                    connector: key.connector.to_string(),
+                    state: state.to_string(),
+                    retry_in_secs: retry_at.map(|at| at.saturating_duration_since(now).as_secs()),
+                    consecutive_failures: breaker.consecutive_failures,
+                    total_failures: breaker.total_failures,
+                    fast_failures: breaker.fast_failures,
+                    last_error: breaker.last_error.clone(),
+                    in_flight: self.max_concurrency - breaker.permits.available_permits(),
+                    max_concurrency: self.max_concurrency,
+                }
+            })
+            .collect();
+        statuses.sort_by(|a, b| (&a.exchange, &a.connector).cmp(&(&b.exchange, &b.connector)));
+        statuses
+    }
+}
+
+/// Шлюз, который ходит во внутренний только через предохранители биржи
+pub struct GuardedGateway<G> {
+    pub inner: G,
+    pub breakers: Arc<CircuitBreakers>,
+}
+
+#[rocket::async_trait]
+impl<G: ExchangeGateway> ExchangeGateway for GuardedGateway<G> {
+    async fn balance(&self, credentials: &ExchangeCredentials<'_>) -> Result<Balance, GatewayError> {
+        self.breakers.call(BreakerKey::from(credentials), self.inner.balance(credentials)).await
+    }
+
+    async fn trade(&self, credentials: &ExchangeCredentials<'_>, trade: &TradeRequest) -> Result<Order, GatewayError> {
+        self.breakers.call(BreakerKey::from(credentials), self.inner.trade(credentials, trade)).await
+    }
+
+    async fn order_status(
+        &self,
+        credentials: &ExchangeCredentials<'_>,
+        symbol: &str,
+        order_id: &str,
+    ) -> Result<Order, GatewayError> {
+        self.breakers
+            .call(BreakerKey::from(credentials), self.inner.order_status(credentials, symbol, order_id))
+            .await
+    }
+
+    async fn markets(&self, exchange: &str) -> Result<Vec<Market>, GatewayError> {
+        // Пары публичные: у Binance спот — из встроенного коннектора, фьючерсы — из trading-gateway (см. `RoutingGateway`)
+        self.breakers.call(BreakerKey::new(exchange, NATIVE_CONNECTOR), self.inner.markets(exchange)).await
+    }
+
+    async fn configure_position(
+        &self,
+        credentials: &ExchangeCredentials<'_>,
+        settings: &PositionSettings,
+    ) -> Result<(), GatewayError> {
+        self.breakers
+            .call(BreakerKey::from(credentials), self.inner.configure_position(credentials, settings))
+            .await
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    AssetBalance, Balance, ExchangeCredentials, ExchangeGateway, GatewayError, Market, Order, PositionSettings,
+    TradeRequest,
+};
+use crate::config::Config;
+
+/// Баланс ccxt: `free`/`used`/`total` — словари «актив → количество», значения бывают `null`
+#[derive(Deserialize, Default)]
+struct CcxtBalance {
+    #[serde(default)]
+    free: HashMap<String, Option<f64>>,
+    #[serde(default)]
+    used: HashMap<String, Option<f64>>,
+    #[serde(default)]
+    total: HashMap<String, Option<f64>>,
+}
+
+impl From<CcxtBalance> for Balance {
+    fn from(raw: CcxtBalance) -> Self {
+        let mut assets: BTreeMap<String, AssetBalance> = BTreeMap::new();
+        let columns = [(raw.free, 0), (raw.used, 1), (raw.total, 2)];
+        for (column, index) in columns {
+            for (asset, amount) in column {
+                let entry = assets.entry(asset.clone()).or_insert_with(|| AssetBalance {
+                    asset,
+                    free: None,
+                    used: None,
+                    total: None,
+                });
+                match index {
+                    0 => entry.free = amount,
+                    1 => entry.used = amount,
+                    _ => entry.total = amount,
+                }
+            }
+        }
+        Balance { assets: assets.into_values().collect() }
+    }
+}
+
+/// Ответ trading-gateway: `{ status: "ok", <payload> }` или `{ status: "error", message }`
+#[derive(Deserialize)]
+struct Envelope {
+    status: String,
+    message: Option<String>,
+    balance: Option<CcxtBalance>,
+    order: Option<Order>,
+    markets: Option<Vec<Market>>,
+}
+
+impl Envelope {
+    fn into_result(self) -> Result<Self, GatewayError> {
+        if self.status == "ok" {
+            Ok(self)
+        } else {
+            Err(GatewayError::Rejected(self.message.unwrap_or_else(|| "Unknown error".to_string())))
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct TradeBody<'a> {
+    #[serde(flatten)]
+    credentials: &'a ExchangeCredentials<'a>,
+    #[serde(flatten)]
+    trade: &'a TradeRequest,
+}
+
+#[derive(Serialize)]
+struct PositionSettingsBody<'a> {
+    #[serde(flatten)]
+    credentials: &'a ExchangeCredentials<'a>,
+    #[serde(flatten)]
+    settings: &'a PositionSettings,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OrderStatusBody<'a> {
+    #[serde(flatten)]
+    credentials: &'a ExchangeCredentials<'a>,
+    symbol: &'a str,
+    order_id: &'a str,
+}
+
+/// Клиент HTTP trading-gateway (Node.js + ccxt).
+///
+/// Один `reqwest::Client` на всё приложение — соединения переиспользуются.
+/// Сетевые ошибки и 5xx повторяются с экспоненциальной паузой; ордер повторяется,
+/// только если соединение не было установлено, чтобы не выставить его дважды.
+pub struct HttpGateway {
+    client: Client,
+    base_url: String,
+    retries: u32,
+    retry_backoff: Duration,
+}
+
+impl HttpGateway {
+    pub fn new(base_url: &str, timeout: Duration, retries: u32) -> Result<Self, reqwest::Error> {
+        let client = Client::builder()
+            .timeout(timeout)
+            .connect_timeout(timeout.min(Duration::from_secs(5)))
+            .pool_idle_timeout(Duration::from_secs(90))
+            .build()?;
+
+        Ok(HttpGateway {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            retries,
+            retry_backoff: Duration::from_millis(200),
+        })
+    }
+
+    pub fn from_config(config: &Config) -> Result<Self, reqwest::Error> {
+        HttpGateway::new(
+            &config.gateway_url,
+            Duration::from_secs(config.gateway_timeout_secs),
+            config.gateway_retries,
+        )
+    }
+
+    async fn post<B: Serialize + ?Sized, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+        idempotent: bool,
+    ) -> Result<T, GatewayError> {
+        let url = format!("{}{}", self.base_url, path);
+        let mut attempt = 0;
+
+        loop {
+            let retries_left = attempt < self.retries;
+            let error = match self.client.post(&url).json(body).send().await {
+                Ok(resp) if resp.status().is_server_error() => {
+                    let error = format!("HTTP {}", resp.status());
+                    if !(idempotent && retries_left) {
+                        return Err(GatewayError::Unavailable(error));
+                    }
+                    error
+                }
+                // 4xx и «error» в теле — ответ шлюза, разбираем как есть
+                Ok(resp) => {
+                    let status = resp.status();
+                    return resp.json::<T>().await.map_err(|e| {
+                        GatewayError::InvalidResponse(format!("HTTP {status}: {e}"))
+                    });
+                }
+                Err(e) if retries_left && (idempotent || e.is_connect()) => e.to_string(),
+                Err(e) => return Err(GatewayError::Unavailable(e.to_string())),
+            };
+
+            let pause = self.retry_backoff * 2u32.pow(attempt);
+            eprintln!("⚠️ Gateway {path} failed ({error}), retrying in {pause:?}");
+            tokio::time::sleep(pause).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl ExchangeGateway for HttpGateway {
+    async fn balance(&self, credentials: &ExchangeCredentials<'_>) -> Result<Balance, GatewayError> {
+        let envelope: Envelope = self.post("/get_balance", credentials, true).await?;
+        let balance = envelope.into_result()?.balance.unwrap_or_default();
+        Ok(balance.into())
+    }
+
+    async fn trade(&self, credentials: &ExchangeCredentials<'_>, trade: &TradeRequest) -> Result<Order, GatewayError> {
+        let body = TradeBody { credentials, trade };
+        let envelope: Envelope = self.post("/trade", &body, false).await?;
+        envelope
+            .into_result()?
+            .order
+            .ok_or_else(|| GatewayError::InvalidResponse("missing order".to_string()))
+    }
+
+    async fn order_status(
+        &self,
+        credentials: &ExchangeCredentials<'_>,
+        symbol: &str,
+        order_id: &str,
+    ) -> Result<Order, GatewayError> {
+        let body = OrderStatusBody { credentials, symbol, order_id };
+        let envelope: Envelope = self.post("/order_status", &body, true).await?;
+        envelope
+            .into_result()?
+            .order
+            .ok_or_else(|| GatewayError::InvalidResponse("missing order".to_string()))
+    }
+
+    async fn markets(&self, exchange: &str) -> Result<Vec<Market>, GatewayError> {
+        let body = serde_json::json!({ "exchange": exchange });
+        let envelope: Envelope = self.post("/markets", &body, true).await?;
+        Ok(envelope.into_result()?.markets.unwrap_or_default())
+    }
+
+    /// Повторная установка тех же значений безопасна — запрос идемпотентный
+    async fn configure_position(
+        &self,
+        credentials: &ExchangeCredentials<'_>,
+        settings: &PositionSettings,
+    ) -> Result<(), GatewayError> {
+        let body = PositionSettingsBody { credentials, settings };
+        let envelope: Envelope = self.post("/position_settings", &body, true).await?;
+        envelope.into_result().map(|_| ())
+    }
+}
@@ -0,0 +1,659 @@
+mod binance;
+mod breaker;
+mod http;
+mod paper;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub use binance::BinanceSpot;
+pub use breaker::{CircuitBreakers, GuardedGateway};
+pub use http::HttpGateway;
+pub use paper::PaperExchange;
+
+/// Шлюз к биржам, через который ходят маршруты (балансы, ордера)
+#[rocket::async_trait]
+pub trait ExchangeGateway: Send + Sync {
+    /// Баланс аккаунта на бирже
+    async fn balance(&self, credentials: &ExchangeCredentials<'_>) -> Result<Balance, GatewayError>;
+
+    /// Ордер: рыночный, лимитный или стоп-лимитный (см. [`TradeRequest`])
+    async fn trade(&self, credentials: &ExchangeCredentials<'_>, trade: &TradeRequest) -> Result<Order, GatewayError>;
+
+    /// Состояние ордера по id биржи
+    async fn order_status(
+        &self,
+        credentials: &ExchangeCredentials<'_>,
+        symbol: &str,
+        order_id: &str,
+    ) -> Result<Order, GatewayError>;
+
+    /// Торгуемые пары биржи с ограничениями на цену и объём
+    async fn markets(&self, exchange: &str) -> Result<Vec<Market>, GatewayError>;
+
+    /// Плечо и режим маржи по символу — перед ордерами на фьючерсах и марже
+    async fn configure_position(
+        &self,
+        credentials: &ExchangeCredentials<'_>,
+        settings: &PositionSettings,
+    ) -> Result<(), GatewayError>;
+}
+
+/// `BTC/USDT`, `BTC/USDT:USDT` и `BTCUSDT` — один и тот же символ
+pub fn symbol_key(symbol: &str) -> String {
+    let symbol = symbol.split(':').next().unwrap_or(symbol);
+    symbol.replace(['/', '-', '_'], "").to_uppercase()
+}
+
+/// Шлюз в виде Rocket state: `&State<SharedGateway>`
+pub type SharedGateway = Arc<dyn ExchangeGateway>;
+
+/// Ключи аккаунта (секрет уже расшифрован)
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeCredentials<'a> {
+    /// Шлюзу не передаётся; нужен бумажной бирже, чтобы различать счета
+    #[serde(skip)]
+    pub account_uid: Uuid,
+    pub exchange: &'a str,
+    /// `gateway` или `native` (`exchange_accounts.connector`); шлюзу не передаётся
+    #[serde(skip)]
+    pub connector: &'a str,
+    pub api_key: &'a str,
+    pub secret: &'a str,
+}
+
+#[derive(Debug)]
+pub enum GatewayError {
+    /// Шлюз недоступен: сетевая ошибка, таймаут или 5xx после всех повторов
+    Unavailable(String),
+    /// Биржа (или шлюз) отклонили запрос — повторять бессмысленно
+    Rejected(String),
+    /// Ответ не удалось разобрать
+    InvalidResponse(String),
+    /// Предохранитель биржи разомкнут — запрос не отправлялся. `exchange` — биржа с коннектором (`binance (native)`)
+    CircuitOpen { exchange: String, retry_in: Duration },
+    /// Все слоты параллельных запросов к бирже (через этот коннектор) заняты дольше допустимого
+    Overloaded(String),
+}
+
+impl fmt::Display for GatewayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GatewayError::Unavailable(e) => write!(f, "Exchange gateway unavailable: {e}"),
+            GatewayError::Rejected(e) => write!(f, "Exchange rejected request: {e}"),
+            GatewayError::InvalidResponse(e) => write!(f, "Invalid exchange gateway response: {e}"),
+            GatewayError::CircuitOpen { exchange, retry_in } => write!(
+                f,
+                "Exchange {exchange} is temporarily unavailable (circuit open), retry in {}s",
+                retry_in.as_secs().max(1)
+            ),
+            GatewayError::Overloaded(exchange) => write!(f, "Too many concurrent requests to {exchange}, try again later"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderKind {
+    Market,
+    Limit,
+    /// Лимитный ордер, который выставляется, когда цена доходит до `stopPrice`
+    StopLimit,
+}
+
+impl OrderKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OrderKind::Market => "market",
+            OrderKind::Limit => "limit",
+            OrderKind::StopLimit => "stop_limit",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        [OrderKind::Market, OrderKind::Limit, OrderKind::StopLimit]
+            .into_iter()
+            .find(|kind| kind.as_str() == raw)
+    }
+}
+
+/// Срок действия лимитного ордера
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum TimeInForce {
+    /// До отмены
+    Gtc,
+    /// Исполнить сразу, остаток отменить
+    Ioc,
+    /// Исполнить сразу целиком или отменить
+    Fok,
+}
+
+impl TimeInForce {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TimeInForce::Gtc => "GTC",
+            TimeInForce::Ioc => "IOC",
+            TimeInForce::Fok => "FOK",
+        }
+    }
+}
+
+/// Рынок, на котором торгует стратегия
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketType {
+    #[default]
+    Spot,
+    /// Бессрочные фьючерсы с маржой в USDT
+    Perpetual,
+    Margin,
+}
+
+impl MarketType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MarketType::Spot => "spot",
+            MarketType::Perpetual => "perpetual",
+            MarketType::Margin => "margin",
+        }
+    }
+
+    /// Из строки: `spot`, `perpetual` (`futures`, `swap`) или `margin`
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.to_lowercase().as_str() {
+            "spot" => Some(MarketType::Spot),
+            "perpetual" | "futures" | "swap" => Some(MarketType::Perpetual),
+            "margin" => Some(MarketType::Margin),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MarginMode {
+    #[default]
+    Isolated,
+    Cross,
+}
+
+/// Плечо и режим маржи по символу
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionSettings {
+    pub symbol: String,
+    pub market_type: MarketType,
+    pub leverage: u32,
+    pub margin_mode: MarginMode,
+}
+
+/// Аккаунт, ключ символа и рынок
+type PositionKey = (Uuid, String, MarketType);
+
+/// Уже применённые плечо и режим маржи по (аккаунт, символ, рынок) — чтобы не выставлять их перед каждым ордером.
+/// Живёт в памяти процесса: после перезапуска настройки выставляются заново, запрос к бирже идемпотентен.
+#[derive(Default)]
+pub struct PositionSettingsCache {
+    applied: Mutex<HashMap<PositionKey, (u32, MarginMode)>>,
+}
+
+impl PositionSettingsCache {
+    /// Выставляет настройки через шлюз, если для аккаунта и символа они ещё не применялись или изменились
+    pub async fn ensure(
+        &self,
+        gateway: &dyn ExchangeGateway,
+        credentials: &ExchangeCredentials<'_>,
+        settings: &PositionSettings,
+    ) -> Result<(), GatewayError> {
+        let key = (credentials.account_uid, symbol_key(&settings.symbol), settings.market_type);
+        let wanted = (settings.leverage, settings.margin_mode);
+        if self.applied.lock().unwrap().get(&key) == Some(&wanted) {
+            return Ok(());
+        }
+
+        gateway.configure_position(credentials, settings).await?;
+        self.applied.lock().unwrap().insert(key, wanted);
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeRequest {
+    pub symbol: String,
+    pub side: OrderSide,
+    pub amount: f64,
+    /// Цена лимитного (и стоп-лимитного) ордера
+    #[serde(default)]
+    pub price: Option<f64>,
+    /// Тип ордера; если не указан — лимитный при наличии цены, иначе рыночный
+    #[serde(default, rename = "type")]
+    pub order_type: Option<OrderKind>,
+    /// Цена срабатывания стоп-лимитного ордера
+    #[serde(default)]
+    pub stop_price: Option<f64>,
+    /// Для лимитных ордеров; по умолчанию — как решит биржа (обычно GTC)
+    #[serde(default)]
+    pub time_in_force: Option<TimeInForce>,
+    /// Только мейкер: ордер отклоняется, если исполнился бы сразу
+    #[serde(default)]
+    pub post_only: bool,
+    /// Только уменьшение позиции (деривативы)
+    #[serde(default)]
+    pub reduce_only: bool,
+    /// Свой id ордера — повтор с тем же id биржа отклонит
+    #[serde(default)]
+    pub client_order_id: Option<String>,
+    #[serde(default)]
+    pub market_type: MarketType,
+    /// Режим маржи для фьючерсов и маржи; для спота не используется
+    #[serde(default)]
+    pub margin_mode: Option<MarginMode>,
+}
+
+impl TradeRequest {
+    pub fn kind(&self) -> OrderKind {
+        self.order_type
+            .unwrap_or(if self.price.is_some() { OrderKind::Limit } else { OrderKind::Market })
+    }
+}
+
+/// Какие рынки, типы ордеров и флаги принимает биржа
+#[derive(Serialize, JsonSchema, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeCapabilities {
+    pub exchange: String,
+    pub market_types: Vec<MarketType>,
+    pub order_types: Vec<OrderKind>,
+    pub time_in_force: Vec<TimeInForce>,
+    pub post_only: bool,
+    /// Только для бессрочных фьючерсов
+    pub reduce_only: bool,
+    pub client_order_id: bool,
+}
+
+/// Биржи ccxt со спотом, маржой и USDT-фьючерсами в одном классе
+const UNIFIED_DERIVATIVES_EXCHANGES: [&str; 5] = ["binance", "bybit", "okx", "bitget", "gate"];
+/// Биржи ccxt только с фьючерсами
+const FUTURES_ONLY_EXCHANGES: [&str; 2] = ["binanceusdm", "kucoinfutures"];
+
+/// Возможности биржи для аккаунта с данным коннектором. Для бирж через trading-gateway — то,
+/// что ccxt поддерживает единообразно; встроенный коннектор Binance настраивает только спот;
+/// остальные биржи считаются спотовыми.
+pub fn capabilities(exchange: &str, connector: &str) -> ExchangeCapabilities {
+    let all_order_types = vec![OrderKind::Market, OrderKind::Limit, OrderKind::StopLimit];
+    let all_tif = vec![TimeInForce::Gtc, TimeInForce::Ioc, TimeInForce::Fok];
+    let native = connector_route(exchange, connector) == NATIVE_CONNECTOR;
+    let (market_types, order_types, time_in_force, post_only) = match exchange {
+        // Симулятор исполняет ордер сразу или отклоняет — GTC и post-only ему не имитировать
+        PAPER_EXCHANGE => (
+            vec![MarketType::Spot],
+            vec![OrderKind::Market, OrderKind::Limit],
+            vec![TimeInForce::Ioc, TimeInForce::Fok],
+            false,
+        ),
+        exchange if UNIFIED_DERIVATIVES_EXCHANGES.contains(&exchange) && !native => (
+            vec![MarketType::Spot, MarketType::Perpetual, MarketType::Margin],
+            all_order_types,
+            all_tif,
+            true,
+        ),
+        exchange if FUTURES_ONLY_EXCHANGES.contains(&exchange) => (vec![MarketType::Perpetual], all_order_types, all_tif, true),
+        _ => (vec![MarketType::Spot], all_order_types, all_tif, true),
+    };
+
+    ExchangeCapabilities {
+        exchange: exchange.to_string(),
+        reduce_only: market_types.contains(&MarketType::Perpetual),
+        market_types,
+        order_types,
+        time_in_force,
+        post_only,
+        client_order_id: true,
+    }
+}
+
+/// Проверяет, что ордер собран правильно и биржа примет его тип и флаги
+pub fn validate_order(capabilities: &ExchangeCapabilities, trade: &TradeRequest) -> Result<(), String> {
+    let kind = trade.kind();
+    let exchange = &capabilities.exchange;
+    let positive = |value: Option<f64>| value.is_none_or(|v| v.is_finite() && v > 0.0);
+
+    if !capabilities.market_types.contains(&trade.market_type) {
+        return Err(format!("{exchange} does not support {} trading", trade.market_type.as_str()));
+    }
+    if !capabilities.order_types.contains(&kind) {
+        return Err(format!("{exchange} does not support {} orders", kind.as_str()));
+    }
+    if !positive(trade.price) || !positive(trade.stop_price) {
+        return Err("price and stopPrice must be positive".to_string());
+    }
+    match kind {
+        OrderKind::Market if trade.price.is_some() => return Err("Market orders take no price".to_string()),
+        OrderKind::Limit | OrderKind::StopLimit if trade.price.is_none() => {
+            return Err(format!("{} orders require a price", kind.as_str()))
+        }
+        OrderKind::StopLimit if trade.stop_price.is_none() => return Err("stop_limit orders require stopPrice".to_string()),
+        OrderKind::Market | OrderKind::Limit if trade.stop_price.is_some() => {
+            return Err("stopPrice is only used with stop_limit orders".to_string())
+        }
+        _ => {}
+    }
+
+    if let Some(tif) = trade.time_in_force {
+        if kind == OrderKind::Market {
+            return Err("timeInForce is not used with market orders".to_string());
+        }
+        if !capabilities.time_in_force.contains(&tif) {
+            return Err(format!("{exchange} does not support timeInForce {}", tif.as_str()));
+        }
+    }
+    if trade.post_only {
+        if !capabilities.post_only {
+            return Err(format!("{exchange} does not support postOnly"));
+        }
+        if kind != OrderKind::Limit || matches!(trade.time_in_force, Some(TimeInForce::Ioc | TimeInForce::Fok)) {
+            return Err("postOnly requires a limit order without IOC/FOK".to_string());
+        }
+    }
+    if trade.reduce_only && !capabilities.reduce_only {
+        return Err(format!("{exchange} does not support reduceOnly"));
+    }
+    if trade.reduce_only && trade.market_type != MarketType::Perpetual {
+        return Err("reduceOnly is only used with perpetual futures".to_string());
+    }
+    if let Some(id) = &trade.client_order_id {
+        if !capabilities.client_order_id {
+            return Err(format!("{exchange} does not support clientOrderId"));
+        }
+        let valid = !id.is_empty() && id.len() <= 36 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err("clientOrderId must be 1-36 characters of letters, digits, '-' and '_'".to_string());
+        }
+    }
+    Ok(())
+}
+
+#[derive(Serialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetBalance {
+    pub asset: String,
+    pub free: Option<f64>,
+    pub used: Option<f64>,
+    pub total: Option<f64>,
+}
+
+#[derive(Serialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Balance {
+    /// Активы в алфавитном порядке
+    pub assets: Vec<AssetBalance>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderFee {
+    pub cost: Option<f64>,
+    pub currency: Option<String>,
+}
+
+/// Торгуемая пара и её фильтры
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Market {
+    /// Единый формат `BTC/USDT`
+    pub symbol: String,
+    /// Как символ пишет сама биржа (`BTCUSDT`)
+    pub exchange_symbol: String,
+    pub base: String,
+    pub quote: String,
+    /// `spot` или `perpetual` (линейный бессрочный контракт); `null` — рынки, которыми сервис
+    /// не торгует (срочные и инверсные фьючерсы, опционы)
+    #[serde(default)]
+    pub market_type: Option<MarketType>,
+    /// Торги открыты
+    pub active: bool,
+    /// Шаг цены
+    pub tick_size: Option<f64>,
+    /// Шаг количества
+    pub step_size: Option<f64>,
+    pub min_qty: Option<f64>,
+    /// Минимальная сумма ордера в валюте котировки
+    pub min_notional: Option<f64>,
+}
+
+/// Ордер в формате ccxt (лишние поля биржи отбрасываются)
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Order {
+    pub id: String,
+    pub client_order_id: Option<String>,
+    pub symbol: String,
+    pub side: OrderSide,
+    #[serde(rename = "type")]
+    pub order_type: Option<String>,
+    pub status: Option<String>,
+    pub price: Option<f64>,
+    pub average: Option<f64>,
+    pub amount: Option<f64>,
+    pub filled: Option<f64>,
+    pub remaining: Option<f64>,
+    pub cost: Option<f64>,
+    pub fee: Option<OrderFee>,
+    /// Время ордера на бирже, мс
+    pub timestamp: Option<i64>,
+}
+
+/// Название биржи у бумажных аккаунтов (`exchange_accounts.exchange`)
+pub const PAPER_EXCHANGE: &str = "paper";
+
+/// Коннектор по умолчанию — trading-gateway
+pub const GATEWAY_CONNECTOR: &str = "gateway";
+/// Встроенный коннектор (сейчас только Binance spot)
+pub const NATIVE_CONNECTOR: &str = "native";
+
+/// Проверяет, что для биржи аккаунта есть выбранный коннектор
+pub fn validate_connector(exchange: &str, connector: &str) -> Result<(), String> {
+    match connector {
+        GATEWAY_CONNECTOR => Ok(()),
+        NATIVE_CONNECTOR if exchange == "binance" => Ok(()),
+        NATIVE_CONNECTOR => Err(format!("Native connector is not available for {exchange}")),
+        other => Err(format!("Unknown connector: {other} (expected gateway or native)")),
+    }
+}
+
+/// Через что на самом деле идёт запрос аккаунта: `paper` — симулятор, `native` — встроенный коннектор,
+/// `gateway` — trading-gateway (в том числе для бирж, у которых встроенного коннектора нет)
+pub fn connector_route(exchange: &str, connector: &str) -> &'static str {
+    if exchange == PAPER_EXCHANGE {
+        PAPER_EXCHANGE
+    } else if connector == NATIVE_CONNECTOR && exchange == "binance" {
+        NATIVE_CONNECTOR
+    } else {
+        GATEWAY_CONNECTOR
+    }
+}
+
+/// Выбирает реализацию по аккаунту (см. [`connector_route`])
+pub struct RoutingGateway {
+    pub live: HttpGateway,
+    pub binance: BinanceSpot,
+    pub paper: Arc<PaperExchange>,
+}
+
+impl RoutingGateway {
+    fn pick(&self, exchange: &str, connector: &str) -> &dyn ExchangeGateway {
+        match connector_route(exchange, connector) {
+            PAPER_EXCHANGE => self.paper.as_ref(),
+            NATIVE_CONNECTOR => &self.binance,
+            _ => &self.live,
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl ExchangeGateway for RoutingGateway {
+    async fn balance(&self, credentials: &ExchangeCredentials<'_>) -> Result<Balance, GatewayError> {
+        self.pick(credentials.exchange, credentials.connector).balance(credentials).await
+    }
+
+    async fn trade(&self, credentials: &ExchangeCredentials<'_>, trade: &TradeRequest) -> Result<Order, GatewayError> {
+        self.pick(credentials.exchange, credentials.connector).trade(credentials, trade).await
+    }
+
+    async fn order_status(
+        &self,
+        credentials: &ExchangeCredentials<'_>,
+        symbol: &str,
+        order_id: &str,
+    ) -> Result<Order, GatewayError> {
+        self.pick(credentials.exchange, credentials.connector)
+            .order_status(credentials, symbol, order_id)
+            .await
+    }
+
+    /// Пары — публичные данные, аккаунт не нужен. Спот Binance отдаёт встроенный коннектор,
+    /// фьючерсы к нему добавляются из trading-gateway: без них лимиты фьючерсных ордеров не проверялись бы.
+    /// Если шлюз недоступен, возвращается только спот.
+    async fn markets(&self, exchange: &str) -> Result<Vec<Market>, GatewayError> {
+        if connector_route(exchange, NATIVE_CONNECTOR) != NATIVE_CONNECTOR {
+            return self.pick(exchange, GATEWAY_CONNECTOR).markets(exchange).await;
+        }
+
+        let mut markets = self.binance.markets(exchange).await?;
+        match self.live.markets(exchange).await {
+            Ok(derivatives) => markets.extend(
+                derivatives.into_iter().filter(|market| market.market_type.is_some_and(|kind| kind != MarketType::Spot)),
+            ),
+            Err(e) => eprintln!("⚠️ Failed to load {exchange} derivatives from trading-gateway: {e}"),
+        }
+        Ok(markets)
+    }
+
+    async fn configure_position(
+        &self,
+        credentials: &ExchangeCredentials<'_>,
+        settings: &PositionSettings,
+    ) -> Result<(), GatewayError> {
+        self.pick(credentials.exchange, credentials.connector)
+            .configure_position(credentials, settings)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    fn gateway_market(symbol: &str, market_type: Option<&str>) -> serde_json::Value {
+        json!({
+            "symbol": symbol, "exchangeSymbol": symbol_key(symbol), "base": "BTC", "quote": "USDT",
+            "marketType": market_type, "active": true,
+            "tickSize": 0.1, "stepSize": 0.001, "minQty": 0.001, "minNotional": 100.0
+        })
+    }
+
+    fn routing(server: &MockServer) -> RoutingGateway {
+        RoutingGateway {
+            live: HttpGateway::new(&server.uri(), Duration::from_secs(5), 0).unwrap(),
+            binance: BinanceSpot::new(&server.uri(), Duration::from_secs(5), 5000).unwrap(),
+            paper: Arc::new(PaperExchange::fixed(100.0)),
+        }
+    }
+
+    #[tokio::test]
+    async fn binance_markets_combine_native_spot_and_gateway_derivatives() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v3/exchangeInfo"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "symbols": [{
+                    "symbol": "BTCUSDT", "status": "TRADING", "baseAsset": "BTC", "quoteAsset": "USDT",
+                    "filters": [{ "filterType": "LOT_SIZE", "minQty": "0.00001", "maxQty": "9000", "stepSize": "0.00001" }]
+                }]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/markets"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "status": "ok",
+                "markets": [
+                    gateway_market("BTC/USDT", Some("spot")),
+                    gateway_market("BTC/USDT:USDT", Some("perpetual")),
+                    gateway_market("BTC/USDT:USDT-260327", None)
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        let markets = routing(&server).markets("binance").await.unwrap();
+
+        // Спот — из встроенного коннектора (его фильтры), из шлюза — только бессрочный контракт
+        assert_eq!(markets.len(), 2);
+        assert_eq!(markets[0].market_type, Some(MarketType::Spot));
+        assert_eq!(markets[0].step_size, Some(0.00001));
+        assert_eq!(markets[1].symbol, "BTC/USDT:USDT");
+        assert_eq!(markets[1].market_type, Some(MarketType::Perpetual));
+    }
+
+    #[tokio::test]
+    async fn binance_spot_survives_gateway_outage() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v3/exchangeInfo"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "symbols": [{ "symbol": "BTCUSDT", "status": "TRADING", "baseAsset": "BTC", "quoteAsset": "USDT", "filters": [] }]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST")).and(path("/markets")).respond_with(ResponseTemplate::new(503)).mount(&server).await;
+
+        let markets = routing(&server).markets("binance").await.unwrap();
+        assert_eq!(markets.len(), 1);
+        assert_eq!(markets[0].symbol, "BTC/USDT");
+    }
+
+    #[test]
+    fn native_binance_accounts_trade_spot_only() {
+        let trade = TradeRequest {
+            symbol: "BTC/USDT:USDT".to_string(),
+            side: OrderSide::Buy,
+            amount: 0.01,
+            price: None,
+            order_type: None,
+            stop_price: None,
+            time_in_force: None,
+            post_only: false,
+            reduce_only: false,
+            client_order_id: None,
+            market_type: MarketType::Perpetual,
+            margin_mode: None,
+        };
+
+        assert!(validate_order(&capabilities("binance", GATEWAY_CONNECTOR), &trade).is_ok());
+        let err = validate_order(&capabilities("binance", NATIVE_CONNECTOR), &trade).unwrap_err();
+        assert_eq!(err, "binance does not support perpetual trading");
+        let spot = TradeRequest { symbol: "BTC/USDT".to_string(), market_type: MarketType::Spot, ..trade };
+        assert!(validate_order(&capabilities("binance", NATIVE_CONNECTOR), &spot).is_ok());
+    }
+}
@@ -0,0 +1,529 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::{
+    symbol_key, AssetBalance, Balance, ExchangeCredentials, ExchangeGateway, GatewayError, Market, MarketType, Order,
+    OrderFee, OrderKind, OrderSide, PositionSettings, TradeRequest,
+};
+use crate::config::Config;
+
+/// Котируемые валюты, по которым тикер без разделителя (`BTCUSDT`) делится на base/quote
+const QUOTE_ASSETS: [&str; 9] = ["USDT", "USDC", "FDUSD", "BUSD", "USD", "EUR", "BTC", "ETH", "BNB"];
+
+/// Откуда бумажная биржа берёт цену исполнения
+enum PriceSource {
+    /// Одна цена для всех символов
+    Fixed(f64),
+    /// Цены из CSV по очереди: каждый ордер по символу берёт следующую, последняя держится
+    Replay(HashMap<String, Vec<f64>>),
+    /// Цена последнего сигнала по символу
+    LastSignal,
+}
+
+#[derive(Default)]
+struct PaperState {
+    balances: HashMap<Uuid, HashMap<String, f64>>,
+    last_prices: HashMap<String, f64>,
+    replay_positions: HashMap<String, usize>,
+    /// Исполненные ордера по id — для `order_status`
+    orders: HashMap<String, (Uuid, Order)>,
+}
+
+/// Симулятор биржи для бумажной торговли (аккаунты с `exchange = "paper"`).
+///
+/// Балансы виртуальные и живут в памяти процесса: при первом обращении счёт получает
+/// стартовый баланс, после перезапуска всё начинается заново. Ордера исполняются целиком по цене
+/// источника со слиппеджем против стороны ордера (лимитные — только если цена достижима сразу);
+/// комиссия берётся в валюте котировки.
+pub struct PaperExchange {
+    source: PriceSource,
+    fee_pct: f64,
+    slippage_pct: f64,
+    initial_balance: HashMap<String, f64>,
+    state: Mutex<PaperState>,
+}
+
+fn split_symbol(symbol: &str) -> Option<(String, String)> {
+    let symbol = symbol.split(':').next().unwrap_or(symbol).to_uppercase();
+    if let Some((base, quote)) = symbol.split_once(['/', '-', '_']) {
+        return Some((base.to_string(), quote.to_string()));
+    }
+    QUOTE_ASSETS
+        .iter()
+        .find(|quote| symbol.len() > quote.len() && symbol.ends_with(*quote))
+        .map(|quote| (symbol[..symbol.len() - quote.len()].to_string(), quote.to_string()))
+}
+
+fn parse_source(raw: &str) -> Result<PriceSource, String> {
+    if raw == "last_signal" {
+        return Ok(PriceSource::LastSignal);
+    }
+    match raw.split_once(':') {
+        Some(("fixed", price)) => match price.parse::<f64>() {
+            Ok(price) if price > 0.0 => Ok(PriceSource::Fixed(price)),
+            _ => Err(format!("Invalid fixed price: {price}")),
+        },
+        Some(("csv", path)) => load_replay(path).map(PriceSource::Replay),
+        _ => Err(format!("Unknown price source: {raw} (expected fixed:<price>, csv:<path> or last_signal)")),
+    }
+}
+
+/// CSV со строками `symbol,price` или `timestamp,symbol,price`; заголовок необязателен
+fn load_replay(path: &str) -> Result<HashMap<String, Vec<f64>>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Cannot read {path}: {e}"))?;
+    let mut prices: HashMap<String, Vec<f64>> = HashMap::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() < 2 || line.trim().is_empty() {
+            continue;
+        }
+        let (symbol, price) = (fields[fields.len() - 2], fields[fields.len() - 1]);
+        match price.parse::<f64>() {
+            Ok(price) if price > 0.0 => prices.entry(symbol_key(symbol)).or_default().push(price),
+            _ if index == 0 => continue,
+            _ => return Err(format!("{path}:{}: invalid price {price}", index + 1)),
+        }
+    }
+
+    if prices.is_empty() {
+        return Err(format!("{path}: no prices"));
+    }
+    Ok(prices)
+}
+
+/// `USDT:10000,BTC:0.5`
+fn parse_balances(raw: &str) -> Result<HashMap<String, f64>, String> {
+    raw.split(',')
+        .filter(|part| !part.trim().is_empty())
+        .map(|part| {
+            let (asset, amount) = part.split_once(':').ok_or_else(|| format!("Invalid balance entry: {part}"))?;
+            let amount = amount.trim().parse::<f64>().map_err(|_| format!("Invalid balance entry: {part}"))?;
+            Ok((asset.trim().to_uppercase(), amount))
+        })
+        .collect()
+}
+
+impl PaperExchange {
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        Ok(PaperExchange {
+            source: parse_source(&config.paper_price_source)?,
+            fee_pct: config.paper_fee_pct,
+            slippage_pct: config.paper_slippage_pct,
+            initial_balance: parse_balances(&config.paper_initial_balance)?,
+            state: Mutex::new(PaperState::default()),
+        })
+    }
+
+    /// Симулятор с одной ценой для всех символов, без комиссий и проскальзывания
+    #[cfg(test)]
+    pub(crate) fn fixed(price: f64) -> Self {
+        PaperExchange {
+            source: PriceSource::Fixed(price),
+            fee_pct: 0.0,
+            slippage_pct: 0.0,
+            initial_balance: parse_balances("USDT:10000").unwrap(),
+            state: Mutex::new(PaperState::default()),
+        }
+    }
+
+    /// Запоминает цену из сигнала — источник для `last_signal`
+    pub fn record_signal_price(&self, symbol: &str, price: f64) {
+        if price > 0.0 {
+            let mut state = self.state.lock().unwrap();
+            state.last_prices.insert(symbol_key(symbol), price);
+        }
+    }
+
+    fn market_price(&self, state: &mut PaperState, symbol: &str) -> Result<f64, GatewayError> {
+        let key = symbol_key(symbol);
+        match &self.source {
+            PriceSource::Fixed(price) => Ok(*price),
+            PriceSource::Replay(series) => {
+                let prices = series
+                    .get(&key)
+                    .ok_or_else(|| GatewayError::Rejected(format!("No replay prices for {symbol}")))?;
+                let position = state.replay_positions.entry(key).or_insert(0);
+                let price = prices[(*position).min(prices.len() - 1)];
+                *position += 1;
+                Ok(price)
+            }
+            PriceSource::LastSignal => state
+                .last_prices
+                .get(&key)
+                .copied()
+                .ok_or_else(|| GatewayError::Rejected(format!("No signal price for {symbol} yet"))),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl ExchangeGateway for PaperExchange {
+    async fn balance(&self, credentials: &ExchangeCredentials<'_>) -> Result<Balance, GatewayError> {
+        let mut state = self.state.lock().unwrap();
+        let balances = state
+            .balances
+            .entry(credentials.account_uid)
+            .or_insert_with(|| self.initial_balance.clone());
+
+        let mut assets: Vec<AssetBalance> = balances
+            .iter()
+            .map(|(asset, amount)| AssetBalance {
+                asset: asset.clone(),
+                free: Some(*amount),
+                used: Some(0.0),
+                total: Some(*amount),
+            })
+            .collect();
+        assets.sort_by(|a, b| a.asset.cmp(&b.asset));
+
+        Ok(Balance { assets })
+    }
+
+    async fn trade(&self, credentials: &ExchangeCredentials<'_>, trade: &TradeRequest) -> Result<Order, GatewayError> {
+        let (base, quote) = split_symbol(&trade.symbol)
+            .ok_or_else(|| GatewayError::Rejected(format!("Cannot parse symbol {}", trade.symbol)))?;
+        if !(trade.amount.is_finite() && trade.amount > 0.0) {
+            return Err(GatewayError::Rejected("Amount must be positive".to_string()));
+        }
+        if trade.market_type != MarketType::Spot {
+            return Err(GatewayError::Rejected("Paper exchange trades spot only".to_string()));
+        }
+        if trade.kind() == OrderKind::StopLimit || trade.post_only || trade.reduce_only {
+            return Err(GatewayError::Rejected("Paper exchange supports market and limit orders only".to_string()));
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let market_price = self.market_price(&mut state, &trade.symbol)?;
+        let slippage = market_price * self.slippage_pct / 100.0;
+        let mut price = match trade.side {
+            OrderSide::Buy => market_price + slippage,
+            OrderSide::Sell => market_price - slippage,
+        };
+        // Лимитный ордер исполняется сразу и не хуже лимита или отклоняется — книги заявок у симулятора нет
+        if let (OrderKind::Limit, Some(limit)) = (trade.kind(), trade.price) {
+            let (reachable, capped) = match trade.side {
+                OrderSide::Buy => (market_price <= limit, price.min(limit)),
+                OrderSide::Sell => (market_price >= limit, price.max(limit)),
+            };
+            if !reachable {
+                return Err(GatewayError::Rejected(format!(
+                    "Limit price {limit} not reachable at market price {market_price}"
+                )));
+            }
+            price = capped;
+        }
+        let cost = trade.amount * price;
+        let fee = cost * self.fee_pct / 100.0;
+
+        let balances = state
+            .balances
+            .entry(credentials.account_uid)
+            .or_insert_with(|| self.initial_balance.clone());
+        let base_balance = balances.get(&base).copied().unwrap_or(0.0);
+        let quote_balance = balances.get(&quote).copied().unwrap_or(0.0);
+
+        match trade.side {
+            OrderSide::Buy if quote_balance < cost + fee => {
+                return Err(GatewayError::Rejected(format!("Insufficient {quote} balance: {quote_balance} < {}", cost + fee)));
+            }
+            OrderSide::Sell if base_balance < trade.amount => {
+                return Err(GatewayError::Rejected(format!("Insufficient {base} balance: {base_balance} < {}", trade.amount)));
+            }
+            OrderSide::Buy => {
+                balances.insert(base, base_balance + trade.amount);
+                balances.insert(quote.clone(), quote_balance - cost - fee);
+            }
+            OrderSide::Sell => {
+                balances.insert(base, base_balance - trade.amount);
+                balances.insert(quote.clone(), quote_balance + cost - fee);
+            }
+        }
+
+        let order = Order {
+            id: format!("paper-{}", Uuid::new_v4()),
+            client_order_id: trade.client_order_id.clone(),
+            symbol: trade.symbol.clone(),
+            side: trade.side,
+            order_type: Some(trade.kind().as_str().to_string()),
+            status: Some("closed".to_string()),
+            price: Some(price),
+            average: Some(price),
+            amount: Some(trade.amount),
+            filled: Some(trade.amount),
+            remaining: Some(0.0),
+            cost: Some(cost),
+            fee: Some(OrderFee { cost: Some(fee), currency: Some(quote) }),
+            timestamp: Some(Utc::now().timestamp_millis()),
+        };
+        state.orders.insert(order.id.clone(), (credentials.account_uid, order.clone()));
+
+        Ok(order)
+    }
+
+    async fn order_status(
+        &self,
+        credentials: &ExchangeCredentials<'_>,
+        _symbol: &str,
+        order_id: &str,
+    ) -> Result<Order, GatewayError> {
+        let state = self.state.lock().unwrap();
+        match state.orders.get(order_id) {
+            Some((account_uid, order)) if *account_uid == credentials.account_uid => Ok(order.clone()),
+            _ => Err(GatewayError::Rejected(format!("Order {order_id} not found"))),
+        }
+    }
+
+    /// Ограничений на пары у симулятора нет
+    async fn markets(&self, _exchange: &str) -> Result<Vec<Market>, GatewayError> {
+        Ok(Vec::new())
+    }
+
+    async fn configure_position(
+        &self,
+        _credentials: &ExchangeCredentials<'_>,
+        _settings: &PositionSettings,
+    ) -> Result<(), GatewayError> {
+        Err(GatewayError::Rejected("Paper exchange trades spot only".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fills::FillEvent;
+    use crate::positions::PositionState;
+
+    fn exchange(source: PriceSource, fee_pct: f64, slippage_pct: f64) -> PaperExchange {
+        PaperExchange {
+            source,
+            fee_pct,
+            slippage_pct,
+            initial_balance: parse_balances("USDT:10000").unwrap(),
+            state: Mutex::new(PaperState::default()),
+        }
+    }
+
+    fn credentials(account_uid: Uuid) -> ExchangeCredentials<'static> {
+        ExchangeCredentials { account_uid, exchange: "paper", connector: "gateway", api_key: "", secret: "" }
+    }
+
+    fn trade(side: OrderSide, amount: f64, price: Option<f64>) -> TradeRequest {
+        TradeRequest {
+            symbol: "BTC/USDT".to_string(),
+            side,
+            amount,
+            price,
+            order_type: None,
+            stop_price: None,
+            time_in_force: None,
+            post_only: false,
+            reduce_only: false,
+            client_order_id: None,
+            market_type: MarketType::Spot,
+            margin_mode: None,
+        }
+    }
+
+    async fn asset(paper: &PaperExchange, account_uid: Uuid, asset: &str) -> f64 {
+        let balance = paper.balance(&credentials(account_uid)).await.unwrap();
+        balance.assets.iter().find(|a| a.asset == asset).and_then(|a| a.total).unwrap_or(0.0)
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[tokio::test]
+    async fn fixed_price_buy_applies_slippage_and_fee() {
+        let paper = exchange(PriceSource::Fixed(100.0), 0.1, 1.0);
+        let account = Uuid::new_v4();
+
+        let order = paper.trade(&credentials(account), &trade(OrderSide::Buy, 2.0, None)).await.unwrap();
+
+        assert!(close(order.average.unwrap(), 101.0));
+        assert!(close(order.cost.unwrap(), 202.0));
+        assert!(close(order.fee.as_ref().unwrap().cost.unwrap(), 0.202));
+        assert_eq!(order.fee.unwrap().currency.as_deref(), Some("USDT"));
+        assert!(close(asset(&paper, account, "BTC").await, 2.0));
+        assert!(close(asset(&paper, account, "USDT").await, 10000.0 - 202.0 - 0.202));
+    }
+
+    #[tokio::test]
+    async fn sell_slips_down_and_pays_fee_from_proceeds() {
+        let paper = exchange(PriceSource::Fixed(100.0), 0.1, 1.0);
+        let account = Uuid::new_v4();
+        paper.trade(&credentials(account), &trade(OrderSide::Buy, 1.0, None)).await.unwrap();
+        let usdt = asset(&paper, account, "USDT").await;
+
+        let order = paper.trade(&credentials(account), &trade(OrderSide::Sell, 1.0, None)).await.unwrap();
+
+        assert!(close(order.average.unwrap(), 99.0));
+        assert!(close(asset(&paper, account, "BTC").await, 0.0));
+        assert!(close(asset(&paper, account, "USDT").await, usdt + 99.0 - 0.099));
+    }
+
+    #[tokio::test]
+    async fn replay_takes_next_price_per_order_and_holds_the_last() {
+        let series = HashMap::from([(symbol_key("BTC/USDT"), vec![100.0, 110.0])]);
+        let paper = exchange(PriceSource::Replay(series), 0.0, 0.0);
+        let account = Uuid::new_v4();
+
+        let mut prices = Vec::new();
+        for _ in 0..3 {
+            let order = paper.trade(&credentials(account), &trade(OrderSide::Buy, 0.1, None)).await.unwrap();
+            prices.push(order.average.unwrap());
+        }
+        assert_eq!(prices, vec![100.0, 110.0, 110.0]);
+
+        let mut other = trade(OrderSide::Buy, 0.1, None);
+        other.symbol = "ETH/USDT".to_string();
+        assert!(matches!(paper.trade(&credentials(account), &other).await, Err(GatewayError::Rejected(_))));
+    }
+
+    #[test]
+    fn replay_csv_accepts_header_and_timestamps() {
+        let path = std::env::temp_dir().join(format!("paper-replay-{}.csv", Uuid::new_v4()));
+        fs::write(&path, "timestamp,symbol,price\n1,BTCUSDT,100\n2,BTC/USDT,101.5\n3,ETH/USDT,5\n").unwrap();
+
+        let prices = load_replay(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(prices[&symbol_key("BTC/USDT")], vec![100.0, 101.5]);
+        assert_eq!(prices[&symbol_key("ETH/USDT")], vec![5.0]);
+    }
+
+    #[tokio::test]
+    async fn last_signal_price_is_required() {
+        let paper = exchange(PriceSource::LastSignal, 0.0, 0.0);
+        let account = Uuid::new_v4();
+        let order = trade(OrderSide::Buy, 0.1, None);
+
+        assert!(matches!(paper.trade(&credentials(account), &order).await, Err(GatewayError::Rejected(_))));
+
+        paper.record_signal_price("BTCUSDT", 250.0);
+        let filled = paper.trade(&credentials(account), &order).await.unwrap();
+        assert_eq!(filled.average, Some(250.0));
+    }
+
+    #[tokio::test]
+    async fn insufficient_balance_is_rejected_without_changes() {
+        let paper = exchange(PriceSource::Fixed(100.0), 0.1, 0.0);
+        let account = Uuid::new_v4();
+
+        // 100 × 100 плюс комиссия больше 10000 USDT
+        let buy = paper.trade(&credentials(account), &trade(OrderSide::Buy, 100.0, None)).await;
+        assert!(matches!(buy, Err(GatewayError::Rejected(e)) if e.contains("Insufficient USDT")));
+        let sell = paper.trade(&credentials(account), &trade(OrderSide::Sell, 0.1, None)).await;
+        assert!(matches!(sell, Err(GatewayError::Rejected(e)) if e.contains("Insufficient BTC")));
+
+        assert!(close(asset(&paper, account, "USDT").await, 10000.0));
+        assert!(close(asset(&paper, account, "BTC").await, 0.0));
+    }
+
+    #[tokio::test]
+    async fn limit_orders_fill_at_most_at_the_limit_or_are_rejected() {
+        let paper = exchange(PriceSource::Fixed(100.0), 0.0, 1.0);
+        let account = Uuid::new_v4();
+
+        // Достижимо: рынок 100 ≤ 100.5, слиппедж до 101 обрезается лимитом
+        let order = paper.trade(&credentials(account), &trade(OrderSide::Buy, 1.0, Some(100.5))).await.unwrap();
+        assert_eq!(order.order_type.as_deref(), Some("limit"));
+        assert!(close(order.average.unwrap(), 100.5));
+
+        // Недостижимо: покупка ниже рынка и продажа выше рынка
+        let buy = paper.trade(&credentials(account), &trade(OrderSide::Buy, 1.0, Some(99.0))).await;
+        assert!(matches!(buy, Err(GatewayError::Rejected(e)) if e.contains("not reachable")));
+        let sell = paper.trade(&credentials(account), &trade(OrderSide::Sell, 1.0, Some(101.0))).await;
+        assert!(matches!(sell, Err(GatewayError::Rejected(e)) if e.contains("not reachable")));
+
+        // Продажа с лимитом ниже рынка исполняется не хуже лимита
+        let order = paper.trade(&credentials(account), &trade(OrderSide::Sell, 1.0, Some(99.5))).await.unwrap();
+        assert!(close(order.average.unwrap(), 99.5));
+    }
+
+    #[tokio::test]
+    async fn rejects_orders_the_simulator_cannot_fill() {
+        let paper = exchange(PriceSource::Fixed(100.0), 0.0, 0.0);
+        let account = Uuid::new_v4();
+
+        let mut perpetual = trade(OrderSide::Buy, 1.0, None);
+        perpetual.market_type = MarketType::Perpetual;
+        let mut post_only = trade(OrderSide::Buy, 1.0, Some(100.0));
+        post_only.post_only = true;
+
+        for order in [perpetual, post_only] {
+            assert!(matches!(paper.trade(&credentials(account), &order).await, Err(GatewayError::Rejected(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn order_status_is_visible_to_the_owning_account_only() {
+        let paper = exchange(PriceSource::Fixed(100.0), 0.0, 0.0);
+        let account = Uuid::new_v4();
+        let order = paper.trade(&credentials(account), &trade(OrderSide::Buy, 1.0, None)).await.unwrap();
+
+        let status = paper.order_status(&credentials(account), "BTC/USDT", &order.id).await.unwrap();
+        assert_eq!(status.filled, Some(1.0));
+        assert!(paper.order_status(&credentials(Uuid::new_v4()), "BTC/USDT", &order.id).await.is_err());
+    }
+
+    /// Бумажный ордер → сообщение в `order-fills` → позиция в журнале
+    #[tokio::test]
+    async fn paper_orders_flow_into_fill_messages_and_the_ledger() {
+        let paper = exchange(PriceSource::Fixed(100.0), 0.1, 0.0);
+        let account = Uuid::new_v4();
+        let strategy = Uuid::new_v4();
+        let mut position = PositionState::default();
+
+        for (side, amount) in [(OrderSide::Buy, 2.0), (OrderSide::Sell, 0.5)] {
+            let request = trade(side, amount, None);
+            let order = paper.trade(&credentials(account), &request).await.unwrap();
+
+            let fill = FillEvent::from_order(&order, &request, account, Some(strategy), None);
+            let payload = serde_json::to_vec(&fill).unwrap();
+            let received: FillEvent = serde_json::from_slice(&payload).unwrap();
+
+            assert_eq!(received.account_uid, account);
+            assert_eq!(received.strategy_uid, Some(strategy));
+            assert_eq!(received.fill_id, order.id);
+            assert_eq!(received.market_type, MarketType::Spot);
+            position.apply(&received.side, received.quantity, received.price, received.fee);
+        }
+
+        assert!(close(position.quantity, 1.5));
+        assert_eq!(position.avg_entry_price, Some(100.0));
+        assert_eq!(position.fill_count, 2);
+        // Комиссии 0.2 + 0.05 USDT, закрытие по цене входа прибыли не даёт
+        assert!(close(position.fees, 0.25));
+        assert!(close(position.realized_pnl, -0.25));
+        assert!(close(asset(&paper, account, "BTC").await, position.quantity));
+    }
+
+    #[test]
+    fn fill_without_market_type_is_spot() {
+        let mut message = serde_json::json!({
+            "accountUid": Uuid::new_v4(),
+            "strategyUid": null,
+            "subscriptionUid": null,
+            "orderId": "1",
+            "fillId": "1",
+            "symbol": "BTC/USDT",
+            "side": "buy",
+            "quantity": 1.0,
+            "price": 100.0,
+            "feeCurrency": null,
+            "filledAt": "2026-01-01T00:00:00Z"
+        });
+        let fill: FillEvent = serde_json::from_value(message.clone()).unwrap();
+        assert_eq!(fill.market_type, MarketType::Spot);
+
+        message["marketType"] = "perpetual".into();
+        let fill: FillEvent = serde_json::from_value(message).unwrap();
+        assert_eq!(fill.market_type, MarketType::Perpetual);
+    }
+}
@@ -2,15 +2,26 @@ use async_nats::Client;
 use dotenv::dotenv;
 use nats_client::connect_nats;
 use sqlx::PgPool;
+use stats::StatsCache;
 use tokio::sync::Mutex;
 use std::env;
 use std::sync::Arc;
 use rocket::tokio::signal;
 use rocket::tokio::sync::broadcast;
 
+mod audit;
 mod config;
 mod crypto;
+mod fills;
+mod gateway;
+mod market_data;
+mod markets;
 mod nats_client;
+mod positions;
+mod reconciliation;
+mod stats;
+mod telegram;
+mod transfer;
 mod types;
 mod web;
 
@@ -43,7 +54,20 @@ async fn main() {
         }
     };
 
-    let rocket_task = spawn_rocket_server(port, pool, nats_client, shutdown_tx.subscribe());
+    let stats_cache = Arc::new(StatsCache::default());
+
+    // Журнал позиций появился позже истории исполнений: при первом запуске он строится из неё
+    match positions::backfill(&pool).await {
+        Ok(Some(summary)) => println!("✅ Built {} position(s) from {} fill(s)", summary.positions, summary.fills),
+        Ok(None) => {}
+        Err(e) => eprintln!("⚠️ Failed to build positions from fills: {:?}", e),
+    }
+
+    // Исполнения ордеров приходят от исполнителя через NATS
+    let fills_client = nats_client.lock().await.clone();
+    fills::spawn_fill_consumer(fills_client, pool.clone(), stats_cache.clone());
+
+    let rocket_task = spawn_rocket_server(port, pool, nats_client, stats_cache, shutdown_tx.subscribe());
 
     signal::ctrl_c().await.expect("failed to listen for Ctrl+C");
     println!("Ctrl+C received! Initiating shutdown...");
@@ -61,11 +85,12 @@ fn spawn_rocket_server(
     port: u16,
     pool: PgPool,
     nats: Arc<Mutex<Client>>,
+    stats_cache: Arc<StatsCache>,
     mut shutdown: broadcast::Receiver<()>,
 ) -> tokio::task::JoinHandle<()> {
     tokio::spawn(async move {
         println!("Starting Rocket server on port {}", port);
-        let rocket = web::server::rocket(port, pool, nats).await;
+        let rocket = web::server::rocket(port, pool, nats, stats_cache).await;
 
         tokio::select! {
             result = rocket.launch() => {
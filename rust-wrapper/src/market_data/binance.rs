@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+
+use super::{MarketDataFeed, QuoteBook};
+
+/// Если за это время не пришло ни одного сообщения, соединение считается зависшим
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Котировки Binance spot из публичных потоков `<symbol>@bookTicker` и `<symbol>@aggTrade`
+pub struct BinanceWsFeed {
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct StreamMessage {
+    stream: String,
+    data: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct BookTicker {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "b")]
+    bid: String,
+    #[serde(rename = "a")]
+    ask: String,
+}
+
+#[derive(Deserialize)]
+struct AggTrade {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "p")]
+    price: String,
+}
+
+impl BinanceWsFeed {
+    /// `base_url` — адрес потоков (`wss://stream.binance.com:9443`), символы в любом написании
+    pub fn new(base_url: &str, symbols: &[String]) -> Self {
+        let streams: Vec<String> = symbols
+            .iter()
+            .map(|symbol| symbol.split(':').next().unwrap_or(symbol).replace(['/', '-', '_'], "").to_lowercase())
+            .flat_map(|symbol| [format!("{symbol}@bookTicker"), format!("{symbol}@aggTrade")])
+            .collect();
+
+        BinanceWsFeed { url: format!("{}/stream?streams={}", base_url.trim_end_matches('/'), streams.join("/")) }
+    }
+
+    fn apply(&self, book: &QuoteBook, text: &str) -> Result<(), String> {
+        let message: StreamMessage = serde_json::from_str(text).map_err(|e| format!("Invalid message: {e}"))?;
+
+        if message.stream.ends_with("@bookTicker") {
+            let ticker: BookTicker = serde_json::from_value(message.data).map_err(|e| format!("Invalid bookTicker: {e}"))?;
+            book.update_book(self.name(), self.exchange(), &ticker.symbol, ticker.bid.parse().ok(), ticker.ask.parse().ok());
+        } else if message.stream.ends_with("@aggTrade") {
+            let trade: AggTrade = serde_json::from_value(message.data).map_err(|e| format!("Invalid aggTrade: {e}"))?;
+            if let Ok(price) = trade.price.parse() {
+                book.update_last(self.name(), self.exchange(), &trade.symbol, price);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[rocket::async_trait]
+impl MarketDataFeed for BinanceWsFeed {
+    fn name(&self) -> &str {
+        "binance"
+    }
+
+    fn exchange(&self) -> &str {
+        "binance"
+    }
+
+    async fn run(&self, book: &QuoteBook) -> Result<(), String> {
+        let (mut socket, _) = connect_async(self.url.as_str()).await.map_err(|e| format!("Connect failed: {e}"))?;
+        println!("✅ Connected to market data feed {}", self.name());
+
+        loop {
+            let message = match tokio::time::timeout(READ_TIMEOUT, socket.next()).await {
+                Err(_) => return Err(format!("No data for {}s", READ_TIMEOUT.as_secs())),
+                Ok(None) => return Err("Connection closed".to_string()),
+                Ok(Some(Err(e))) => return Err(e.to_string()),
+                Ok(Some(Ok(message))) => message,
+            };
+
+            match message {
+                Message::Text(text) => {
+                    if let Err(e) = self.apply(book, &text) {
+                        eprintln!("⚠️ Market data feed {}: {e}", self.name());
+                    }
+                }
+                Message::Ping(payload) => socket.send(Message::Pong(payload)).await.map_err(|e| e.to_string())?,
+                // Binance закрывает соединение раз в сутки — это обычный повод переподключиться
+                Message::Close(frame) => return Err(format!("Closed by server: {frame:?}")),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gateway::OrderSide;
+
+    #[test]
+    fn builds_combined_stream_url() {
+        let feed = BinanceWsFeed::new("wss://stream.binance.com:9443/", &["BTC/USDT".to_string(), "eth-usdt".to_string()]);
+        assert_eq!(
+            feed.url,
+            "wss://stream.binance.com:9443/stream?streams=btcusdt@bookTicker/btcusdt@aggTrade/ethusdt@bookTicker/ethusdt@aggTrade"
+        );
+    }
+
+    #[test]
+    fn applies_book_ticker_and_agg_trade() {
+        let feed = BinanceWsFeed::new("wss://example", &["BTCUSDT".to_string()]);
+        let book = QuoteBook::default();
+
+        feed.apply(
+            &book,
+            r#"{"stream":"btcusdt@bookTicker","data":{"u":1,"s":"BTCUSDT","b":"59990.10","B":"1.5","a":"60010.20","A":"2"}}"#,
+        )
+        .unwrap();
+        feed.apply(
+            &book,
+            r#"{"stream":"btcusdt@aggTrade","data":{"e":"aggTrade","s":"BTCUSDT","p":"60000.00","q":"0.01"}}"#,
+        )
+        .unwrap();
+
+        let quote = book.get("binance", "BTC/USDT").unwrap();
+        assert_eq!(quote.source, "binance");
+        assert_eq!(quote.bid, Some(59990.1));
+        assert_eq!(quote.ask, Some(60010.2));
+        assert_eq!(quote.last, Some(60000.0));
+        assert_eq!(quote.execution_price(OrderSide::Buy), Some(60010.2));
+    }
+
+    #[test]
+    fn rejects_malformed_messages() {
+        let feed = BinanceWsFeed::new("wss://example", &["BTCUSDT".to_string()]);
+        let book = QuoteBook::default();
+
+        assert!(feed.apply(&book, "not json").unwrap_err().contains("Invalid message"));
+        assert!(feed
+            .apply(&book, r#"{"stream":"btcusdt@bookTicker","data":{"s":"BTCUSDT"}}"#)
+            .unwrap_err()
+            .contains("Invalid bookTicker"));
+        // Нечисловая цена и незнакомые потоки пропускаются без ошибки
+        feed.apply(&book, r#"{"stream":"btcusdt@aggTrade","data":{"s":"BTCUSDT","p":"n/a"}}"#).unwrap();
+        feed.apply(&book, r#"{"stream":"btcusdt@depth","data":{}}"#).unwrap();
+        assert!(book.all().is_empty());
+    }
+}
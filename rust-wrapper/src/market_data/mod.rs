@@ -0,0 +1,217 @@
+mod binance;
+mod replay;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::gateway::{symbol_key, OrderSide};
+
+pub use binance::BinanceWsFeed;
+pub use replay::ReplayFeed;
+
+/// Пауза перед первым переподключением; дальше удваивается до `MAX_BACKOFF`
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Соединение, прожившее дольше этого, считается здоровым — задержка переподключения сбрасывается
+const HEALTHY_CONNECTION: Duration = Duration::from_secs(30);
+
+/// Текущая котировка символа
+#[derive(Serialize, JsonSchema, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Quote {
+    /// Символ в том виде, в каком его прислал источник
+    pub symbol: String,
+    /// Биржа, спотовый рынок которой котируется
+    pub exchange: String,
+    /// Цена последней сделки
+    pub last: Option<f64>,
+    pub bid: Option<f64>,
+    pub ask: Option<f64>,
+    /// Источник котировки (`binance`, `replay`)
+    pub source: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Quote {
+    /// Цена для оценок: последняя сделка, иначе середина спреда
+    pub fn price(&self) -> Option<f64> {
+        self.last.or_else(|| self.bid.zip(self.ask).map(|(bid, ask)| (bid + ask) / 2.0))
+    }
+
+    /// Цена, по которой сейчас исполнится рыночный ордер: ask для покупки, bid для продажи
+    pub fn execution_price(&self, side: OrderSide) -> Option<f64> {
+        match side {
+            OrderSide::Buy => self.ask,
+            OrderSide::Sell => self.bid,
+        }
+        .or(self.last)
+    }
+}
+
+/// Последние спотовые котировки по биржам и символам; `BTC/USDT` и `BTCUSDT` — один ключ.
+///
+/// Котировки фьючерсов и других бирж источники не присылают: их цена может заметно отличаться,
+/// поэтому для таких ордеров котировки нет.
+#[derive(Default)]
+pub struct QuoteBook {
+    quotes: Mutex<HashMap<(String, String), Quote>>,
+}
+
+impl QuoteBook {
+    fn entry<'a>(
+        quotes: &'a mut HashMap<(String, String), Quote>,
+        source: &str,
+        exchange: &str,
+        symbol: &str,
+    ) -> &'a mut Quote {
+        let quote = quotes.entry((exchange.to_string(), symbol_key(symbol))).or_insert_with(|| Quote {
+            symbol: symbol.to_string(),
+            exchange: exchange.to_string(),
+            last: None,
+            bid: None,
+            ask: None,
+            source: source.to_string(),
+            updated_at: Utc::now(),
+        });
+        quote.source = source.to_string();
+        quote.updated_at = Utc::now();
+        quote
+    }
+
+    pub fn update_last(&self, source: &str, exchange: &str, symbol: &str, price: f64) {
+        let mut quotes = self.quotes.lock().unwrap();
+        Self::entry(&mut quotes, source, exchange, symbol).last = Some(price);
+    }
+
+    pub fn update_book(&self, source: &str, exchange: &str, symbol: &str, bid: Option<f64>, ask: Option<f64>) {
+        let mut quotes = self.quotes.lock().unwrap();
+        let quote = Self::entry(&mut quotes, source, exchange, symbol);
+        quote.bid = bid.or(quote.bid);
+        quote.ask = ask.or(quote.ask);
+    }
+
+    pub fn get(&self, exchange: &str, symbol: &str) -> Option<Quote> {
+        self.quotes.lock().unwrap().get(&(exchange.to_string(), symbol_key(symbol))).cloned()
+    }
+
+    /// Котировка, если она обновлялась не раньше чем `max_age` назад
+    pub fn fresh(&self, exchange: &str, symbol: &str, max_age: Duration) -> Option<Quote> {
+        self.get(exchange, symbol).filter(|quote| {
+            (Utc::now() - quote.updated_at).to_std().is_ok_and(|age| age <= max_age)
+        })
+    }
+
+    /// Все котировки в алфавитном порядке бирж и символов
+    pub fn all(&self) -> Vec<Quote> {
+        let mut quotes: Vec<Quote> = self.quotes.lock().unwrap().values().cloned().collect();
+        quotes.sort_by(|a, b| (&a.exchange, &a.symbol).cmp(&(&b.exchange, &b.symbol)));
+        quotes
+    }
+}
+
+/// Источник рыночных данных. `run` держит одно соединение и пишет котировки в `book`,
+/// пока источник не закроется: `Ok` — данные закончились, `Err` — обрыв, нужно переподключиться.
+#[rocket::async_trait]
+pub trait MarketDataFeed: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Биржа, спотовый рынок которой транслирует источник
+    fn exchange(&self) -> &str;
+
+    async fn run(&self, book: &QuoteBook) -> Result<(), String>;
+}
+
+/// Запускает источник в фоне и переподключает его при обрывах (с растущей паузой)
+pub fn spawn_feed(feed: Box<dyn MarketDataFeed>, book: Arc<QuoteBook>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut backoff = INITIAL_BACKOFF;
+        loop {
+            let started = Instant::now();
+            match feed.run(&book).await {
+                Ok(()) => {
+                    println!("✅ Market data feed {} finished", feed.name());
+                    return;
+                }
+                Err(e) => {
+                    if started.elapsed() >= HEALTHY_CONNECTION {
+                        backoff = INITIAL_BACKOFF;
+                    }
+                    eprintln!("⚠️ Market data feed {} disconnected: {e}; reconnecting in {}s", feed.name(), backoff.as_secs());
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+            }
+        }
+    })
+}
+
+/// Источник из настроек: `MARKET_DATA_FEED` = `none`, `binance` или `replay:<path>`
+pub fn feed_from_config(config: &Config) -> Result<Option<Box<dyn MarketDataFeed>>, String> {
+    let symbols: Vec<String> = config
+        .market_data_symbols
+        .split(',')
+        .map(str::trim)
+        .filter(|symbol| !symbol.is_empty())
+        .map(String::from)
+        .collect();
+
+    match config.market_data_feed.as_str() {
+        "" | "none" => Ok(None),
+        "binance" if symbols.is_empty() => Err("MARKET_DATA_SYMBOLS is required for the binance feed".to_string()),
+        "binance" => Ok(Some(Box::new(BinanceWsFeed::new(&config.binance_ws_url, &symbols)))),
+        other => match other.split_once(':') {
+            Some(("replay", path)) => Ok(Some(Box::new(ReplayFeed::load(
+                path,
+                Duration::from_millis(config.market_data_replay_interval_ms),
+            )?))),
+            _ => Err(format!("Unknown market data feed: {other} (expected none, binance or replay:<path>)")),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn execution_price_takes_the_opposite_side_of_the_book() {
+        let book = QuoteBook::default();
+        book.update_book("replay", "binance", "BTCUSDT", Some(59990.0), Some(60010.0));
+        let quote = book.get("binance", "BTC/USDT").unwrap();
+        assert_eq!(quote.execution_price(OrderSide::Buy), Some(60010.0));
+        assert_eq!(quote.execution_price(OrderSide::Sell), Some(59990.0));
+        assert_eq!(quote.price(), Some(60000.0));
+
+        // Без стакана — цена последней сделки; стакан обновляется по одной стороне
+        book.update_last("replay", "binance", "ETH/USDT", 3000.0);
+        let quote = book.get("binance", "ETHUSDT").unwrap();
+        assert_eq!(quote.execution_price(OrderSide::Buy), Some(3000.0));
+        book.update_book("replay", "binance", "ETHUSDT", Some(2999.0), None);
+        let quote = book.get("binance", "ETHUSDT").unwrap();
+        assert_eq!(quote.execution_price(OrderSide::Sell), Some(2999.0));
+        assert_eq!(quote.execution_price(OrderSide::Buy), Some(3000.0));
+        assert_eq!(quote.price(), Some(3000.0));
+    }
+
+    #[test]
+    fn quotes_are_kept_per_exchange_and_expire() {
+        let book = QuoteBook::default();
+        book.update_last("binance", "binance", "BTCUSDT", 60000.0);
+        assert!(book.get("bybit", "BTCUSDT").is_none());
+        assert!(book.fresh("binance", "BTC-USDT", Duration::from_secs(10)).is_some());
+
+        book.quotes.lock().unwrap().values_mut().for_each(|quote| {
+            quote.updated_at = Utc::now() - chrono::Duration::seconds(30);
+        });
+        assert!(book.fresh("binance", "BTCUSDT", Duration::from_secs(10)).is_none());
+        assert!(book.fresh("binance", "BTCUSDT", Duration::from_secs(60)).is_some());
+        assert_eq!(book.all().len(), 1);
+    }
+}
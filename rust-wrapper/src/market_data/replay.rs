@@ -0,0 +1,83 @@
+use std::fs;
+use std::time::Duration;
+
+use super::{MarketDataFeed, QuoteBook};
+
+struct ReplayRow {
+    symbol: String,
+    last: Option<f64>,
+    bid: Option<f64>,
+    ask: Option<f64>,
+}
+
+/// Локальный источник для тестов: проигрывает котировки из CSV по одной строке раз в `interval`
+/// вместо потока Binance spot
+pub struct ReplayFeed {
+    rows: Vec<ReplayRow>,
+    interval: Duration,
+}
+
+fn optional_price(raw: &str) -> Result<Option<f64>, ()> {
+    match raw {
+        "" => Ok(None),
+        raw => raw.parse::<f64>().ok().filter(|price| *price > 0.0).map(Some).ok_or(()),
+    }
+}
+
+impl ReplayFeed {
+    /// CSV со строками `symbol,last,bid,ask` или `timestamp,symbol,last,bid,ask`;
+    /// пустое поле — значение не меняется, заголовок необязателен
+    pub fn load(path: &str, interval: Duration) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("Cannot read {path}: {e}"))?;
+        let mut rows = Vec::new();
+
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let fields = match fields.len() {
+                4 => &fields[..],
+                5 => &fields[1..],
+                _ => return Err(format!("{path}:{}: expected symbol,last,bid,ask", index + 1)),
+            };
+
+            match (optional_price(fields[1]), optional_price(fields[2]), optional_price(fields[3])) {
+                (Ok(last), Ok(bid), Ok(ask)) => rows.push(ReplayRow { symbol: fields[0].to_string(), last, bid, ask }),
+                _ if index == 0 => continue,
+                _ => return Err(format!("{path}:{}: invalid price", index + 1)),
+            }
+        }
+
+        if rows.is_empty() {
+            return Err(format!("{path}: no quotes"));
+        }
+        Ok(ReplayFeed { rows, interval })
+    }
+}
+
+#[rocket::async_trait]
+impl MarketDataFeed for ReplayFeed {
+    fn name(&self) -> &str {
+        "replay"
+    }
+
+    fn exchange(&self) -> &str {
+        "binance"
+    }
+
+    async fn run(&self, book: &QuoteBook) -> Result<(), String> {
+        for (index, row) in self.rows.iter().enumerate() {
+            if index > 0 {
+                tokio::time::sleep(self.interval).await;
+            }
+            if let Some(last) = row.last {
+                book.update_last(self.name(), self.exchange(), &row.symbol, last);
+            }
+            if row.bid.is_some() || row.ask.is_some() {
+                book.update_book(self.name(), self.exchange(), &row.symbol, row.bid, row.ask);
+            }
+        }
+        Ok(())
+    }
+}
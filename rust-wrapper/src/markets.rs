@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use sqlx::PgPool;
+
+use crate::gateway::{symbol_key, GatewayError, Market, MarketType, OrderSide, SharedGateway, TradeRequest, PAPER_EXCHANGE};
+
+/// Через сколько повторять загрузку пар биржи, если прошлая не удалась
+const RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Допуск при сравнении с шагом (погрешность f64: 0.3 / 0.1 = 2.9999999999999996)
+const EPSILON: f64 = 1e-9;
+
+struct ExchangeMarkets {
+    fetched_at: Instant,
+    /// `None` — загрузить не удалось или биржа не сообщает ограничений
+    markets: Option<HashMap<String, Market>>,
+}
+
+/// Кэш торгуемых пар и их фильтров (шаг цены и количества, минимальная сумма, статус) по биржам.
+///
+/// Обновляется фоновой задачей раз в `MARKETS_REFRESH_SECS`; биржа, которой ещё нет в кэше,
+/// загружается при первом обращении. Пока ограничения биржи неизвестны, ордера проходят как есть.
+pub struct MarketCache {
+    gateway: SharedGateway,
+    exchanges: Mutex<HashMap<String, ExchangeMarkets>>,
+}
+
+/// Ключ пары в кэше: маржинальная торговля идёт по спотовым парам, у фьючерсов — свои
+fn market_key(symbol: &str, market_type: MarketType) -> String {
+    match market_type {
+        MarketType::Perpetual => format!("{}:PERP", symbol_key(symbol)),
+        MarketType::Spot | MarketType::Margin => symbol_key(symbol),
+    }
+}
+
+/// Количество знаков после запятой у шага (`0.001` → 3)
+fn step_decimals(step: f64) -> i32 {
+    let mut decimals = 0;
+    let mut scaled = step;
+    while decimals < 12 && (scaled - scaled.round()).abs() > EPSILON * scaled.max(1.0) {
+        scaled *= 10.0;
+        decimals += 1;
+    }
+    decimals
+}
+
+/// Кратное шагу значение: вниз или вверх, без хвостов вида `0.30000000000000004`
+fn round_to_step(value: f64, step: f64, up: bool) -> f64 {
+    let steps = value / step;
+    let steps = if up { (steps - EPSILON).ceil() } else { (steps + EPSILON).floor() };
+    let factor = 10f64.powi(step_decimals(step));
+    (steps * step * factor).round() / factor
+}
+
+/// Приводит ордер к фильтрам пары или объясняет, почему биржа его не примет.
+///
+/// Количество округляется вниз до шага; цены лимитного и стоп-лимитного ордера — до шага цены
+/// в выгодную для исполнителя сторону (покупка вниз, продажа вверх). Минимальная сумма проверяется
+/// по цене ордера, а для рыночного — по `reference_price` (цене сигнала или котировке), если она есть.
+pub fn prepare_order(market: &Market, trade: &TradeRequest, reference_price: Option<f64>) -> Result<TradeRequest, String> {
+    if !market.active {
+        return Err(format!("Market {} is not trading", market.symbol));
+    }
+
+    let amount = match market.step_size.filter(|step| *step > 0.0) {
+        Some(step) => round_to_step(trade.amount, step, false),
+        None => trade.amount,
+    };
+    if amount <= 0.0 {
+        return Err(format!("Order amount is below the lot step of {}", market.symbol));
+    }
+    if let Some(min_qty) = market.min_qty {
+        if amount < min_qty {
+            return Err(format!("Order amount {amount} is below minimum {min_qty} for {}", market.symbol));
+        }
+    }
+
+    let round_price = |price: Option<f64>| match (price, market.tick_size.filter(|tick| *tick > 0.0)) {
+        (Some(price), Some(tick)) => Some(round_to_step(price, tick, trade.side == OrderSide::Sell)),
+        (price, _) => price,
+    };
+    let (price, stop_price) = (round_price(trade.price), round_price(trade.stop_price));
+    if price.is_some_and(|price| price <= 0.0) || stop_price.is_some_and(|price| price <= 0.0) {
+        return Err(format!("Order price is below the tick size of {}", market.symbol));
+    }
+
+    if let (Some(min_notional), Some(reference)) = (market.min_notional, price.or(reference_price)) {
+        let notional = amount * reference;
+        if notional < min_notional {
+            return Err(format!(
+                "Order value {notional} is below minimum notional {min_notional} for {}",
+                market.symbol
+            ));
+        }
+    }
+
+    Ok(TradeRequest { amount, price, stop_price, ..trade.clone() })
+}
+
+impl MarketCache {
+    pub fn new(gateway: SharedGateway) -> Self {
+        MarketCache { gateway, exchanges: Mutex::new(HashMap::new()) }
+    }
+
+    /// Загружает пары биржи через шлюз и заменяет ими кэш
+    pub async fn refresh(&self, exchange: &str) -> Result<Vec<Market>, GatewayError> {
+        let result = self.gateway.markets(exchange).await;
+
+        let markets = result.as_ref().ok().and_then(|markets| {
+            // Срочные и инверсные контракты (`BTC/USD:BTC`, `BTC/USDT:USDT-250328`) пропускаются:
+            // иначе они перезапишут фильтры бессрочного контракта с тем же ключом
+            let by_symbol: HashMap<String, Market> = markets
+                .iter()
+                .filter_map(|market| Some((market_key(&market.symbol, market.market_type?), market.clone())))
+                .collect();
+            (!by_symbol.is_empty()).then_some(by_symbol)
+        });
+
+        let mut exchanges = self.exchanges.lock().unwrap();
+        match (&result, exchanges.get(exchange)) {
+            // Неудачное обновление не затирает уже загруженные пары
+            (Err(_), Some(ExchangeMarkets { markets: Some(_), .. })) => {}
+            _ => {
+                exchanges.insert(exchange.to_string(), ExchangeMarkets { fetched_at: Instant::now(), markets });
+            }
+        }
+        result
+    }
+
+    /// Пары биржи: из кэша или, если их там нет, из шлюза
+    pub async fn markets(&self, exchange: &str) -> Result<Vec<Market>, GatewayError> {
+        let cached = {
+            let exchanges = self.exchanges.lock().unwrap();
+            exchanges
+                .get(exchange)
+                .and_then(|entry| entry.markets.as_ref())
+                .map(|markets| markets.values().cloned().collect::<Vec<_>>())
+        };
+
+        let mut markets = match cached {
+            Some(markets) => markets,
+            None => self.refresh(exchange).await?,
+        };
+        markets.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        Ok(markets)
+    }
+
+    /// Пара по символу в любом написании. `Ok(None)` — ограничения биржи неизвестны.
+    async fn market(&self, exchange: &str, symbol: &str, market_type: MarketType) -> Result<Option<Market>, String> {
+        let needs_load = {
+            let exchanges = self.exchanges.lock().unwrap();
+            match exchanges.get(exchange) {
+                None => true,
+                Some(entry) => entry.markets.is_none() && entry.fetched_at.elapsed() >= RETRY_AFTER,
+            }
+        };
+        if needs_load {
+            if let Err(e) = self.refresh(exchange).await {
+                eprintln!("⚠️ Failed to load markets for {exchange}: {e}");
+            }
+        }
+
+        let exchanges = self.exchanges.lock().unwrap();
+        let Some(markets) = exchanges.get(exchange).and_then(|entry| entry.markets.as_ref()) else {
+            return Ok(None);
+        };
+        match markets.get(&market_key(symbol, market_type)) {
+            Some(market) => Ok(Some(market.clone())),
+            // Пары фьючерсов отдаёт не каждый источник (встроенный коннектор Binance — только спот)
+            None if market_type == MarketType::Perpetual => Ok(None),
+            None => Err(format!("Symbol {symbol} is not listed on {exchange}")),
+        }
+    }
+
+    /// Приводит ордер к ограничениям пары на бирже (см. [`prepare_order`])
+    pub async fn prepare(
+        &self,
+        exchange: &str,
+        trade: &TradeRequest,
+        reference_price: Option<f64>,
+    ) -> Result<TradeRequest, String> {
+        match self.market(exchange, &trade.symbol, trade.market_type).await? {
+            Some(market) => prepare_order(&market, trade, reference_price),
+            None => Ok(trade.clone()),
+        }
+    }
+}
+
+/// Раз в `interval` обновляет пары всех бирж, на которых есть аккаунты (кроме бумажной)
+pub fn spawn_market_refresher(pool: PgPool, cache: Arc<MarketCache>, interval: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+
+            let exchanges = match sqlx::query_scalar!(
+                "SELECT DISTINCT exchange FROM exchange_accounts WHERE exchange <> $1",
+                PAPER_EXCHANGE
+            )
+            .fetch_all(&pool)
+            .await
+            {
+                Ok(exchanges) => exchanges,
+                Err(e) => {
+                    eprintln!("❌ Failed to list exchanges for market refresh: {:?}", e);
+                    continue;
+                }
+            };
+
+            for exchange in exchanges {
+                match cache.refresh(&exchange).await {
+                    Ok(markets) => println!("✅ Loaded {} market(s) for {exchange}", markets.len()),
+                    Err(e) => eprintln!("⚠️ Failed to refresh markets for {exchange}: {e}"),
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gateway::{Balance, ExchangeCredentials, ExchangeGateway, Order, OrderKind, PositionSettings};
+
+    fn market(symbol: &str, market_type: Option<MarketType>) -> Market {
+        Market {
+            symbol: symbol.to_string(),
+            exchange_symbol: symbol_key(symbol),
+            base: "BTC".to_string(),
+            quote: "USDT".to_string(),
+            market_type,
+            active: true,
+            tick_size: Some(0.01),
+            step_size: Some(0.001),
+            min_qty: Some(0.001),
+            min_notional: Some(5.0),
+        }
+    }
+
+    fn trade(side: OrderSide, amount: f64, price: Option<f64>) -> TradeRequest {
+        TradeRequest {
+            symbol: "BTC/USDT".to_string(),
+            side,
+            amount,
+            price,
+            order_type: price.map(|_| OrderKind::Limit),
+            stop_price: None,
+            time_in_force: None,
+            post_only: false,
+            reduce_only: false,
+            client_order_id: None,
+            market_type: MarketType::Spot,
+            margin_mode: None,
+        }
+    }
+
+    /// Шлюз, который отдаёт только заданный список пар
+    struct StaticMarkets(Vec<Market>);
+
+    #[rocket::async_trait]
+    impl ExchangeGateway for StaticMarkets {
+        async fn balance(&self, _: &ExchangeCredentials<'_>) -> Result<Balance, GatewayError> {
+            Err(GatewayError::Unavailable("not used".to_string()))
+        }
+
+        async fn trade(&self, _: &ExchangeCredentials<'_>, _: &TradeRequest) -> Result<Order, GatewayError> {
+            Err(GatewayError::Unavailable("not used".to_string()))
+        }
+
+        async fn order_status(&self, _: &ExchangeCredentials<'_>, _: &str, _: &str) -> Result<Order, GatewayError> {
+            Err(GatewayError::Unavailable("not used".to_string()))
+        }
+
+        async fn markets(&self, _exchange: &str) -> Result<Vec<Market>, GatewayError> {
+            Ok(self.0.clone())
+        }
+
+        async fn configure_position(&self, _: &ExchangeCredentials<'_>, _: &PositionSettings) -> Result<(), GatewayError> {
+            Err(GatewayError::Unavailable("not used".to_string()))
+        }
+    }
+
+    #[test]
+    fn rounds_to_step_without_float_tails() {
+        assert_eq!(round_to_step(0.3, 0.1, false), 0.3);
+        assert_eq!(round_to_step(0.3, 0.1, true), 0.3);
+        assert_eq!(round_to_step(0.123456, 0.001, false), 0.123);
+        assert_eq!(round_to_step(0.123456, 0.001, true), 0.124);
+        assert_eq!(round_to_step(60000.017, 0.01, false), 60000.01);
+        assert_eq!(round_to_step(1234.0, 10.0, false), 1230.0);
+        assert_eq!(step_decimals(0.001), 3);
+        assert_eq!(step_decimals(10.0), 0);
+    }
+
+    #[test]
+    fn rounds_amount_down_and_price_towards_the_taker() {
+        let market = market("BTC/USDT", Some(MarketType::Spot));
+
+        let buy = prepare_order(&market, &trade(OrderSide::Buy, 0.0129, Some(60000.019)), None).unwrap();
+        assert_eq!(buy.amount, 0.012);
+        assert_eq!(buy.price, Some(60000.01));
+
+        let sell = prepare_order(&market, &trade(OrderSide::Sell, 0.0129, Some(60000.011)), None).unwrap();
+        assert_eq!(sell.amount, 0.012);
+        assert_eq!(sell.price, Some(60000.02));
+    }
+
+    #[test]
+    fn rejects_orders_below_exchange_limits() {
+        let market = market("BTC/USDT", Some(MarketType::Spot));
+
+        let err = prepare_order(&market, &trade(OrderSide::Buy, 0.0009, None), None).unwrap_err();
+        assert!(err.contains("lot step"), "{err}");
+
+        let err = prepare_order(&market, &trade(OrderSide::Buy, 0.002, Some(2000.0)), None).unwrap_err();
+        assert!(err.contains("minimum notional"), "{err}");
+
+        // У рыночного ордера сумма считается по опорной цене, а без неё не проверяется
+        let err = prepare_order(&market, &trade(OrderSide::Buy, 0.002, None), Some(2000.0)).unwrap_err();
+        assert!(err.contains("minimum notional"), "{err}");
+        assert!(prepare_order(&market, &trade(OrderSide::Buy, 0.002, None), None).is_ok());
+
+        let closed = Market { active: false, ..market };
+        let err = prepare_order(&closed, &trade(OrderSide::Buy, 1.0, None), None).unwrap_err();
+        assert!(err.contains("not trading"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn keeps_only_spot_and_linear_perpetual_markets() {
+        let spot = market("BTC/USDT", Some(MarketType::Spot));
+        let perpetual = Market { min_qty: Some(0.01), ..market("BTC/USDT:USDT", Some(MarketType::Perpetual)) };
+        let dated = Market { min_qty: Some(1.0), ..market("BTC/USDT:USDT-250328", None) };
+        let cache = MarketCache::new(Arc::new(StaticMarkets(vec![spot, perpetual, dated])));
+
+        let mut perp = trade(OrderSide::Buy, 0.005, None);
+        perp.symbol = "BTCUSDT".to_string();
+        perp.market_type = MarketType::Perpetual;
+        let err = cache.prepare("binance", &perp, Some(60000.0)).await.unwrap_err();
+        assert!(err.contains("below minimum 0.01"), "{err}");
+
+        assert_eq!(cache.markets("binance").await.unwrap().len(), 2);
+        assert!(cache.prepare("binance", &trade(OrderSide::Buy, 0.005, None), Some(60000.0)).await.is_ok());
+    }
+}
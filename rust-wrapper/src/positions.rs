@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use serde_json::json;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::audit::{self, AuditActor};
+use crate::gateway::{symbol_key, MarketType};
+
+/// Остаток позиции меньше этого считается нулевым (погрешность f64)
+const EPSILON: f64 = 1e-9;
+
+/// Аккаунт, стратегия, рынок и ключ символа
+type PositionKey = (Uuid, Option<Uuid>, MarketType, String);
+
+/// Состояние позиции в журнале: знаковый объём (плюс — лонг), средняя цена входа и реализованный PnL
+#[derive(Debug, Clone, Default)]
+pub struct PositionState {
+    pub quantity: f64,
+    pub avg_entry_price: Option<f64>,
+    /// За вычетом комиссий, в валюте котировки
+    pub realized_pnl: f64,
+    pub fees: f64,
+    pub fill_count: i32,
+}
+
+impl PositionState {
+    /// Применяет исполнение. Как в статистике стратегий: цена входа — средняя, частичное закрытие
+    /// фиксирует PnL по этой цене, переворот открывает остаток по цене исполнения.
+    pub fn apply(&mut self, side: &str, quantity: f64, price: f64, fee: f64) {
+        let direction = if side == "buy" { 1.0 } else { -1.0 };
+        let held = self.quantity.abs();
+        let entry = self.avg_entry_price.unwrap_or(price);
+
+        if held < EPSILON {
+            self.quantity = direction * quantity;
+            self.avg_entry_price = Some(price);
+        } else if self.quantity.signum() == direction {
+            self.avg_entry_price = Some((entry * held + price * quantity) / (held + quantity));
+            self.quantity += direction * quantity;
+        } else {
+            let closed = held.min(quantity);
+            self.realized_pnl += closed * (price - entry) * self.quantity.signum();
+            self.quantity += direction * quantity;
+            if self.quantity.abs() < EPSILON {
+                self.quantity = 0.0;
+                self.avg_entry_price = None;
+            } else if quantity > held {
+                self.avg_entry_price = Some(price);
+            }
+        }
+
+        self.realized_pnl -= fee;
+        self.fees += fee;
+        self.fill_count += 1;
+    }
+}
+
+/// Исполнение в том виде, в каком оно лежит в `fills`
+pub struct LedgerFill<'a> {
+    pub account_uid: Uuid,
+    pub strategy_uid: Option<Uuid>,
+    pub symbol: &'a str,
+    pub market_type: MarketType,
+    pub side: &'a str,
+    pub quantity: f64,
+    pub price: f64,
+    pub fee: f64,
+    pub filled_at: NaiveDateTime,
+}
+
+/// Применяет исполнение к позиции в журнале. Вызывается в транзакции, в которой исполнение записано.
+pub async fn apply_fill(conn: &mut PgConnection, fill: &LedgerFill<'_>) -> Result<(), sqlx::Error> {
+    let key = symbol_key(fill.symbol);
+
+    // Сначала строка позиции (если её ещё нет), затем блокировка — одновременные исполнения
+    // по одной позиции применяются по очереди
+    sqlx::query!(
+        "INSERT INTO positions (id, account_id, strategy_id, symbol, symbol_key, market_type, last_fill_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         ON CONFLICT (account_id, symbol_key, market_type, strategy_id) DO NOTHING",
+        Uuid::new_v4(),
+        fill.account_uid,
+        fill.strategy_uid,
+        fill.symbol,
+        key,
+        fill.market_type.as_str(),
+        fill.filled_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let row = sqlx::query!(
+        "SELECT id, quantity, avg_entry_price, realized_pnl, fees, fill_count
+         FROM positions
+         WHERE account_id = $1 AND strategy_id IS NOT DISTINCT FROM $2 AND market_type = $3 AND symbol_key = $4
+         FOR UPDATE",
+        fill.account_uid,
+        fill.strategy_uid,
+        fill.market_type.as_str(),
+        key
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let mut state = PositionState {
+        quantity: row.quantity,
+        avg_entry_price: row.avg_entry_price,
+        realized_pnl: row.realized_pnl,
+        fees: row.fees,
+        fill_count: row.fill_count,
+    };
+    state.apply(fill.side, fill.quantity, fill.price, fill.fee);
+
+    sqlx::query!(
+        "UPDATE positions
+         SET symbol = $2, quantity = $3, avg_entry_price = $4, realized_pnl = $5, fees = $6, fill_count = $7,
+             last_fill_at = GREATEST(last_fill_at, $8), updated_at = now()
+         WHERE id = $1",
+        row.id,
+        fill.symbol,
+        state.quantity,
+        state.avg_entry_price,
+        state.realized_pnl,
+        state.fees,
+        state.fill_count,
+        fill.filled_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Сколько позиций пересчитано
+pub struct RebuildSummary {
+    pub fills: usize,
+    pub positions: usize,
+}
+
+/// Перестраивает журнал из истории исполнений (`fills`) — всего или одного аккаунта.
+///
+/// Исполнения применяются в порядке `filled_at`, поэтому результат может отличаться от журнала,
+/// в который они попадали в порядке поступления. Вызывается в транзакции: до её завершения новые исполнения ждут.
+pub async fn rebuild(conn: &mut PgConnection, account_uid: Option<Uuid>) -> Result<RebuildSummary, sqlx::Error> {
+    sqlx::query!("LOCK TABLE positions IN SHARE ROW EXCLUSIVE MODE").execute(&mut *conn).await?;
+
+    let fills = sqlx::query!(
+        "SELECT account_id, strategy_id, symbol, market_type, side, quantity, price, fee, filled_at
+         FROM fills
+         WHERE $1::uuid IS NULL OR account_id = $1
+         ORDER BY filled_at, recorded_at, id",
+        account_uid
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut positions: HashMap<PositionKey, (String, NaiveDateTime, PositionState)> = HashMap::new();
+    for fill in &fills {
+        let (symbol, last_fill_at, state) = positions
+            .entry((
+                fill.account_id,
+                fill.strategy_id,
+                MarketType::parse(&fill.market_type).unwrap_or_default(),
+                symbol_key(&fill.symbol),
+            ))
+            .or_insert_with(|| (fill.symbol.clone(), fill.filled_at, PositionState::default()));
+        state.apply(&fill.side, fill.quantity, fill.price, fill.fee);
+        *symbol = fill.symbol.clone();
+        *last_fill_at = fill.filled_at;
+    }
+
+    sqlx::query!("DELETE FROM positions WHERE $1::uuid IS NULL OR account_id = $1", account_uid)
+        .execute(&mut *conn)
+        .await?;
+    for ((account_id, strategy_id, market_type, key), (symbol, last_fill_at, state)) in &positions {
+        sqlx::query!(
+            "INSERT INTO positions
+                (id, account_id, strategy_id, symbol, symbol_key, market_type, quantity, avg_entry_price,
+                 realized_pnl, fees, fill_count, last_fill_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+            Uuid::new_v4(),
+            account_id,
+            *strategy_id,
+            symbol,
+            key,
+            market_type.as_str(),
+            state.quantity,
+            state.avg_entry_price,
+            state.realized_pnl,
+            state.fees,
+            state.fill_count,
+            last_fill_at
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(RebuildSummary { fills: fills.len(), positions: positions.len() })
+}
+
+/// При запуске строит журнал из уже записанных исполнений, если он пуст (первый запуск после
+/// появления журнала). `None` — журнал уже ведётся или исполнений нет.
+pub async fn backfill(pool: &PgPool) -> Result<Option<RebuildSummary>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query!("LOCK TABLE positions IN SHARE ROW EXCLUSIVE MODE").execute(&mut *tx).await?;
+
+    let needed = sqlx::query_scalar!(
+        "SELECT NOT EXISTS (SELECT 1 FROM positions) AND EXISTS (SELECT 1 FROM fills)"
+    )
+    .fetch_one(&mut *tx)
+    .await?
+    .unwrap_or(false);
+    if !needed {
+        return Ok(None);
+    }
+
+    let summary = rebuild(&mut tx, None).await?;
+    let actor = AuditActor::system("positions", Uuid::new_v4().to_string());
+    let after = json!({ "fills": summary.fills, "positions": summary.positions });
+    audit::record(&mut tx, &actor, "positions.rebuild", "exchange_account", None, None, Some(after)).await?;
+    tx.commit().await?;
+
+    Ok(Some(summary))
+}
+
+/// Текущий знаковый объём позиции стратегии на аккаунте и рынке; позиции нет — 0.
+/// Исполнения приходят асинхронно, поэтому только что отправленный ордер ещё может не учитываться.
+pub async fn position_quantity(
+    pool: &PgPool,
+    account_uid: Uuid,
+    strategy_uid: Uuid,
+    market_type: MarketType,
+    symbol: &str,
+) -> Result<f64, String> {
+    sqlx::query_scalar!(
+        "SELECT quantity FROM positions
+         WHERE account_id = $1 AND strategy_id = $2 AND market_type = $3 AND symbol_key = $4",
+        account_uid,
+        strategy_uid,
+        market_type.as_str(),
+        symbol_key(symbol)
+    )
+    .fetch_optional(pool)
+    .await
+    .map(|quantity| quantity.unwrap_or(0.0))
+    .map_err(|e| format!("Database error: {e}"))
+}
@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::audit::{self, decrypt_credentials, AuditActor, CredentialAccess, CredentialPurpose};
+use crate::config::Config;
+use crate::gateway::{ExchangeCredentials, MarketType, SharedGateway, PAPER_EXCHANGE};
+use crate::types::DiscrepancySeverity;
+
+/// Котируемые валюты для символов без разделителя (`BTCUSDT`)
+const QUOTE_ASSETS: [&str; 8] = ["USDT", "USDC", "FDUSD", "BUSD", "TUSD", "EUR", "BTC", "ETH"];
+
+/// Меньше этого количества актив считается нулевым
+const EPSILON: f64 = 1e-9;
+
+/// Базовый актив спотового символа: `BTC/USDT` и `BTCUSDT` → `BTC`
+fn base_asset(symbol: &str) -> Option<String> {
+    if let Some((base, _)) = symbol.split_once(['/', '-', '_']) {
+        return Some(base.to_uppercase());
+    }
+    let symbol = symbol.to_uppercase();
+    QUOTE_ASSETS
+        .iter()
+        .find_map(|quote| symbol.strip_suffix(quote).filter(|base| !base.is_empty()).map(String::from))
+}
+
+/// Расхождение по одному активу аккаунта
+struct AssetDrift {
+    asset: String,
+    expected: f64,
+    actual: f64,
+    difference_pct: f64,
+    severity: DiscrepancySeverity,
+}
+
+/// Сверка журнала позиций с балансами на биржах.
+///
+/// По каждому аккаунту, на котором есть включённая стратегия или активная подписка на включённую,
+/// ожидаемое количество базового актива — сумма открытых спотовых лонгов журнала по всем стратегиям
+/// аккаунта; оно сравнивается с `total` баланса. Котируемые валюты и позиции на фьючерсах и марже
+/// не сверяются: журнал их остаток не ведёт или биржа не показывает его в спотовом балансе.
+/// Бумажные аккаунты пропускаются — их балансы живут в памяти и обнуляются при перезапуске.
+pub struct Reconciler {
+    pool: PgPool,
+    gateway: SharedGateway,
+    salt_key: String,
+    tolerance_pct: f64,
+    critical_pct: f64,
+    auto_disable: bool,
+}
+
+/// Итог одного запуска
+pub struct ReconcileSummary {
+    pub run_uid: Uuid,
+    pub accounts: usize,
+    pub failed_accounts: usize,
+    pub discrepancies: usize,
+    pub disabled_accounts: usize,
+}
+
+impl Reconciler {
+    pub fn from_config(pool: PgPool, gateway: SharedGateway, config: &Config) -> Self {
+        Reconciler {
+            pool,
+            gateway,
+            salt_key: config.salt_key.clone(),
+            tolerance_pct: config.reconcile_tolerance_pct.max(0.0),
+            critical_pct: config.reconcile_critical_pct.max(config.reconcile_tolerance_pct),
+            auto_disable: config.reconcile_auto_disable,
+        }
+    }
+
+    /// Серьёзность расхождения или `None`, если оно в пределах допуска
+    fn classify(&self, expected: f64, actual: f64) -> Option<(f64, DiscrepancySeverity)> {
+        let difference_pct = (actual - expected) / expected * 100.0;
+        if difference_pct.abs() <= self.tolerance_pct {
+            return None;
+        }
+        let severity = if difference_pct > 0.0 {
+            DiscrepancySeverity::Info
+        } else if -difference_pct > self.critical_pct {
+            DiscrepancySeverity::Critical
+        } else {
+            DiscrepancySeverity::Warning
+        };
+        Some((difference_pct, severity))
+    }
+
+    /// Сверяет все аккаунты и записывает найденные расхождения
+    pub async fn run(&self) -> Result<ReconcileSummary, sqlx::Error> {
+        let run_uid = Uuid::new_v4();
+        let actor = AuditActor::system("reconciliation", run_uid.to_string());
+
+        let accounts = sqlx::query!(
+            "SELECT id, user_id, exchange, connector, api_key, encrypted_secret
+             FROM exchange_accounts
+             WHERE exchange <> $1
+               AND (EXISTS (SELECT 1 FROM strategies
+                            WHERE strategies.account_id = exchange_accounts.id AND strategies.enabled)
+                    OR EXISTS (SELECT 1 FROM strategy_subscriptions
+                               JOIN strategies ON strategies.id = strategy_subscriptions.strategy_id
+                               WHERE strategy_subscriptions.account_id = exchange_accounts.id
+                                 AND strategy_subscriptions.active AND strategies.enabled))
+             ORDER BY id",
+            PAPER_EXCHANGE
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut summary = ReconcileSummary {
+            run_uid,
+            accounts: accounts.len(),
+            failed_accounts: 0,
+            discrepancies: 0,
+            disabled_accounts: 0,
+        };
+
+        for account in accounts {
+            let positions = sqlx::query!(
+                "SELECT symbol, quantity FROM positions WHERE account_id = $1 AND market_type = $2 AND quantity <> 0",
+                account.id,
+                MarketType::Spot.as_str()
+            )
+            .fetch_all(&self.pool)
+            .await?;
+
+            let mut expected: HashMap<String, f64> = HashMap::new();
+            for position in positions {
+                if let Some(asset) = base_asset(&position.symbol) {
+                    *expected.entry(asset).or_default() += position.quantity;
+                }
+            }
+            expected.retain(|_, quantity| *quantity > EPSILON);
+            if expected.is_empty() {
+                continue;
+            }
+
+            let access = CredentialAccess {
+                user_uid: account.user_id,
+                account_uid: account.id,
+                purpose: CredentialPurpose::Reconciliation,
+                strategy_uid: None,
+                signal_id: None,
+            };
+            let secret =
+                match decrypt_credentials(&self.pool, &actor, access, &account.encrypted_secret, &self.salt_key).await {
+                    Ok(secret) => secret,
+                    Err(e) => {
+                        eprintln!("⚠️ Reconciliation skipped account {}: {e}", account.id);
+                        summary.failed_accounts += 1;
+                        continue;
+                    }
+                };
+            let credentials = ExchangeCredentials {
+                account_uid: account.id,
+                exchange: &account.exchange,
+                connector: &account.connector,
+                api_key: &account.api_key,
+                secret: &secret,
+            };
+            let balance = match self.gateway.balance(&credentials).await {
+                Ok(balance) => balance,
+                Err(e) => {
+                    eprintln!("⚠️ Reconciliation could not fetch balance of account {}: {e}", account.id);
+                    summary.failed_accounts += 1;
+                    continue;
+                }
+            };
+
+            let mut drifts: Vec<AssetDrift> = expected
+                .into_iter()
+                .filter_map(|(asset, expected)| {
+                    let actual = balance
+                        .assets
+                        .iter()
+                        .find(|balance| balance.asset.eq_ignore_ascii_case(&asset))
+                        .and_then(|balance| balance.total)
+                        .unwrap_or(0.0);
+                    self.classify(expected, actual).map(|(difference_pct, severity)| AssetDrift {
+                        asset,
+                        expected,
+                        actual,
+                        difference_pct,
+                        severity,
+                    })
+                })
+                .collect();
+            if drifts.is_empty() {
+                continue;
+            }
+            drifts.sort_by(|a, b| a.asset.cmp(&b.asset));
+
+            let critical = drifts.iter().any(|drift| drift.severity == DiscrepancySeverity::Critical);
+            let disable = critical && self.auto_disable;
+
+            let mut tx = self.pool.begin().await?;
+            for drift in &drifts {
+                sqlx::query!(
+                    "INSERT INTO reconciliation_discrepancies
+                        (id, run_id, account_id, asset, expected, actual, difference_pct, severity, auto_disabled)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+                    Uuid::new_v4(),
+                    run_uid,
+                    account.id,
+                    drift.asset,
+                    drift.expected,
+                    drift.actual,
+                    drift.difference_pct,
+                    drift.severity.as_str(),
+                    disable && drift.severity == DiscrepancySeverity::Critical
+                )
+                .execute(&mut *tx)
+                .await?;
+                eprintln!(
+                    "⚠️ Account {} {}: expected {} {}, exchange has {} ({:+.2}%, {})",
+                    account.id,
+                    account.exchange,
+                    drift.expected,
+                    drift.asset,
+                    drift.actual,
+                    drift.difference_pct,
+                    drift.severity.as_str()
+                );
+            }
+
+            if disable {
+                let strategies = sqlx::query_scalar!(
+                    "UPDATE strategies SET enabled = false, auto_disabled_at = now()
+                     WHERE account_id = $1 AND enabled
+                     RETURNING id",
+                    account.id
+                )
+                .fetch_all(&mut *tx)
+                .await?;
+                let subscriptions = sqlx::query_scalar!(
+                    "UPDATE strategy_subscriptions SET active = false
+                     WHERE account_id = $1 AND active
+                     RETURNING id",
+                    account.id
+                )
+                .fetch_all(&mut *tx)
+                .await?;
+
+                let after = json!({
+                    "runUid": run_uid,
+                    "disabledStrategies": strategies,
+                    "deactivatedSubscriptions": subscriptions,
+                    "assets": drifts
+                        .iter()
+                        .filter(|drift| drift.severity == DiscrepancySeverity::Critical)
+                        .map(|drift| json!({ "asset": drift.asset, "expected": drift.expected, "actual": drift.actual }))
+                        .collect::<Vec<_>>(),
+                });
+                audit::record(&mut tx, &actor, "reconciliation.auto_disable", "exchange_account", Some(account.id), None, Some(after))
+                    .await?;
+                eprintln!(
+                    "❌ Account {} drifted beyond {}%: disabled {} strategy(ies) and {} subscription(s)",
+                    account.id,
+                    self.critical_pct,
+                    strategies.len(),
+                    subscriptions.len()
+                );
+                summary.disabled_accounts += 1;
+            }
+            tx.commit().await?;
+
+            summary.discrepancies += drifts.len();
+        }
+
+        Ok(summary)
+    }
+}
+
+/// Раз в `interval` запускает сверку (первый запуск — через `interval` после старта)
+pub fn spawn_reconciliation(reconciler: Reconciler, interval: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            match reconciler.run().await {
+                Ok(summary) => println!(
+                    "✅ Reconciliation {}: {} account(s), {} discrepancy(ies), {} failed, {} disabled",
+                    summary.run_uid,
+                    summary.accounts,
+                    summary.discrepancies,
+                    summary.failed_accounts,
+                    summary.disabled_accounts
+                ),
+                Err(e) => eprintln!("❌ Reconciliation failed: {:?}", e),
+            }
+        }
+    })
+}
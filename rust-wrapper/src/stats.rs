@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{NaiveDateTime, Utc};
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+use crate::gateway::symbol_key;
+use crate::types::StrategyStats;
+
+/// Сколько живёт посчитанная статистика, если новых исполнений не было
+const STATS_TTL: Duration = Duration::from_secs(60);
+
+/// Остаток позиции меньше этого считается нулевым (погрешность f64)
+const EPSILON: f64 = 1e-9;
+
+type StatsKey = (Uuid, Option<NaiveDateTime>, Option<NaiveDateTime>);
+
+/// Кэш статистики стратегий. Запись сбрасывается по TTL или при новом исполнении стратегии.
+#[derive(Default)]
+pub struct StatsCache {
+    entries: Mutex<HashMap<StatsKey, (Instant, StrategyStats)>>,
+}
+
+impl StatsCache {
+    pub fn get(&self, key: &StatsKey) -> Option<StrategyStats> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(key)
+            .filter(|(at, _)| at.elapsed() < STATS_TTL)
+            .map(|(_, stats)| stats.clone())
+    }
+
+    pub fn put(&self, key: StatsKey, stats: StrategyStats) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (at, _)| at.elapsed() < STATS_TTL);
+        entries.insert(key, (Instant::now(), stats));
+    }
+
+    pub fn invalidate(&self, strategy_uid: Uuid) {
+        self.entries.lock().unwrap().retain(|(uid, _, _), _| *uid != strategy_uid);
+    }
+}
+
+/// Открытая позиция по символу и рынку: знаковый объём, средняя цена входа и накопленный PnL сделки
+struct OpenPosition {
+    quantity: f64,
+    avg_price: f64,
+    opened_at: NaiveDateTime,
+    pnl: f64,
+}
+
+/// Завершённая сделка: позиция открыта и полностью закрыта (или перевёрнута)
+struct RoundTrip {
+    pnl: f64,
+    opened_at: NaiveDateTime,
+    closed_at: NaiveDateTime,
+}
+
+/// Исполнение, как его видит статистика
+struct StatsFill {
+    symbol: String,
+    market_type: String,
+    side: String,
+    quantity: f64,
+    price: f64,
+    fee: f64,
+    filled_at: NaiveDateTime,
+}
+
+/// Считает статистику стратегии по исполнениям автора (без подписчиков, в том числе бывших).
+///
+/// Позиции ведутся по средней цене входа отдельно по каждому символу и рынку (как в журнале позиций);
+/// комиссия считается в валюте котировки. В диапазон `[from, to)` попадают сделки, закрытые в нём,
+/// но открытые могут быть и раньше `from`.
+pub async fn compute_stats(
+    conn: impl PgExecutor<'_>,
+    strategy_uid: Uuid,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+) -> Result<StrategyStats, sqlx::Error> {
+    let fills = sqlx::query_as!(
+        StatsFill,
+        "SELECT symbol, market_type, side, quantity, price, fee, filled_at
+         FROM fills
+         WHERE strategy_id = $1 AND is_author AND ($2::timestamp IS NULL OR filled_at < $2)
+         ORDER BY filled_at, id",
+        strategy_uid,
+        to
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(summarize(strategy_uid, fills, from, to))
+}
+
+fn summarize(
+    strategy_uid: Uuid,
+    fills: Vec<StatsFill>,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+) -> StrategyStats {
+    let mut positions: HashMap<(String, String), OpenPosition> = HashMap::new();
+    let mut round_trips = Vec::new();
+
+    for fill in fills {
+        let direction = if fill.side == "buy" { 1.0 } else { -1.0 };
+        let position = positions.entry((symbol_key(&fill.symbol), fill.market_type)).or_insert(OpenPosition {
+            quantity: 0.0,
+            avg_price: 0.0,
+            opened_at: fill.filled_at,
+            pnl: 0.0,
+        });
+
+        if position.quantity.abs() < EPSILON {
+            *position = OpenPosition {
+                quantity: direction * fill.quantity,
+                avg_price: fill.price,
+                opened_at: fill.filled_at,
+                pnl: -fill.fee,
+            };
+        } else if position.quantity.signum() == direction {
+            let held = position.quantity.abs();
+            position.avg_price = (position.avg_price * held + fill.price * fill.quantity) / (held + fill.quantity);
+            position.quantity += direction * fill.quantity;
+            position.pnl -= fill.fee;
+        } else {
+            let closed = position.quantity.abs().min(fill.quantity);
+            let closed_fee = fill.fee * closed / fill.quantity;
+            position.pnl += closed * (fill.price - position.avg_price) * position.quantity.signum() - closed_fee;
+            position.quantity += direction * closed;
+
+            if position.quantity.abs() < EPSILON {
+                round_trips.push(RoundTrip {
+                    pnl: position.pnl,
+                    opened_at: position.opened_at,
+                    closed_at: fill.filled_at,
+                });
+
+                // Остаток исполнения открывает позицию в обратную сторону
+                let remainder = fill.quantity - closed;
+                *position = OpenPosition {
+                    quantity: direction * remainder,
+                    avg_price: fill.price,
+                    opened_at: fill.filled_at,
+                    pnl: -(fill.fee - closed_fee),
+                };
+            }
+        }
+    }
+
+    round_trips.retain(|trip| from.is_none_or(|from| trip.closed_at >= from));
+    round_trips.sort_by_key(|trip| trip.closed_at);
+
+    let trades = round_trips.len() as i64;
+    let winning_trades = round_trips.iter().filter(|trip| trip.pnl > 0.0).count() as i64;
+
+    let mut realized_pnl = 0.0;
+    let mut peak = 0.0_f64;
+    let mut max_drawdown = 0.0_f64;
+    let mut holding_secs = 0.0;
+    for trip in &round_trips {
+        realized_pnl += trip.pnl;
+        peak = peak.max(realized_pnl);
+        max_drawdown = max_drawdown.max(peak - realized_pnl);
+        holding_secs += (trip.closed_at - trip.opened_at).num_milliseconds() as f64 / 1000.0;
+    }
+
+    StrategyStats {
+        strategy_uid,
+        from,
+        to,
+        realized_pnl,
+        trades,
+        winning_trades,
+        win_rate: (trades > 0).then(|| winning_trades as f64 / trades as f64),
+        avg_holding_secs: (trades > 0).then(|| holding_secs / trades as f64),
+        max_drawdown,
+        computed_at: Utc::now().naive_utc(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use sqlx::{Connection, PgConnection};
+
+    use super::*;
+
+    fn fill(symbol: &str, market_type: &str, side: &str, quantity: f64, price: f64, minute: u32) -> StatsFill {
+        StatsFill {
+            symbol: symbol.to_string(),
+            market_type: market_type.to_string(),
+            side: side.to_string(),
+            quantity,
+            price,
+            fee: 0.0,
+            filled_at: chrono::NaiveDate::from_ymd_opt(2026, 1, 1).unwrap().and_hms_opt(0, minute, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn spot_and_perpetual_fills_are_separate_positions() {
+        let fills = vec![
+            fill("BTC/USDT", "spot", "buy", 1.0, 100.0, 0),
+            fill("BTC/USDT:USDT", "perpetual", "sell", 1.0, 100.0, 1),
+            fill("BTCUSDT", "spot", "sell", 1.0, 110.0, 2),
+            fill("BTCUSDT", "perpetual", "buy", 1.0, 90.0, 3),
+        ];
+        let stats = summarize(Uuid::new_v4(), fills, None, None);
+
+        // Лонг на споте +10 и шорт на фьючерсах +10 — две сделки, а не одна нулевая
+        assert_eq!(stats.trades, 2);
+        assert_eq!(stats.winning_trades, 2);
+        assert!((stats.realized_pnl - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn partial_close_and_reversal() {
+        let fills = vec![
+            fill("ETH/USDT", "spot", "buy", 2.0, 100.0, 0),
+            fill("ETH/USDT", "spot", "sell", 1.0, 90.0, 1),
+            fill("ETH/USDT", "spot", "sell", 1.0, 120.0, 2),
+        ];
+        let stats = summarize(Uuid::new_v4(), fills, None, None);
+        assert_eq!(stats.trades, 1);
+        assert!((stats.realized_pnl - 10.0).abs() < 1e-9);
+        assert!((stats.max_drawdown - 0.0).abs() < 1e-9);
+    }
+
+    /// Удаление подписки обнуляет `fills.subscription_id`, но исполнения бывшего подписчика
+    /// не должны попасть в статистику автора. Нужна база из `DATABASE_URL`; всё откатывается.
+    #[tokio::test]
+    async fn deleting_subscription_keeps_author_stats() {
+        let mut conn = PgConnection::connect(&env::var("DATABASE_URL").expect("DATABASE_URL must be set"))
+            .await
+            .unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        let (author, follower) = (Uuid::new_v4(), Uuid::new_v4());
+        let (author_account, follower_account) = (Uuid::new_v4(), Uuid::new_v4());
+        let (strategy, subscription) = (Uuid::new_v4(), Uuid::new_v4());
+        for (user, account) in [(author, author_account), (follower, follower_account)] {
+            sqlx::query("INSERT INTO users (id, user_telegram_id) VALUES ($1, $2)")
+                .bind(user)
+                .bind(-(user.as_u128() as i64).abs())
+                .execute(&mut *tx)
+                .await
+                .unwrap();
+            sqlx::query("INSERT INTO exchange_accounts (id, user_id, exchange, api_key, encrypted_secret) VALUES ($1, $2, 'binance', '', '')")
+                .bind(account)
+                .bind(user)
+                .execute(&mut *tx)
+                .await
+                .unwrap();
+        }
+        sqlx::query("INSERT INTO strategies (id, user_id, account_id, strategy_name, webhook_token) VALUES ($1, $2, $3, 'test', $4)")
+            .bind(strategy)
+            .bind(author)
+            .bind(author_account)
+            .bind(strategy.to_string())
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO strategy_subscriptions (id, strategy_id, user_id, account_id) VALUES ($1, $2, $3, $4)")
+            .bind(subscription)
+            .bind(strategy)
+            .bind(follower)
+            .bind(follower_account)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        // Автор зарабатывает 10, подписчик на том же сигнале теряет 50
+        let fills: [(Uuid, Option<Uuid>, &str, f64, u32); 4] = [
+            (author_account, None, "buy", 100.0, 0),
+            (author_account, None, "sell", 110.0, 1),
+            (follower_account, Some(subscription), "buy", 100.0, 0),
+            (follower_account, Some(subscription), "sell", 50.0, 1),
+        ];
+        for (index, (account, subscription_uid, side, price, minute)) in fills.into_iter().enumerate() {
+            let fill = fill("BTC/USDT", "spot", side, 1.0, price, minute);
+            sqlx::query(
+                "INSERT INTO fills (id, account_id, strategy_id, subscription_id, is_author, order_id, exchange_fill_id,
+                                    symbol, market_type, side, quantity, price, filled_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $6, $7, $8, $9, $10, $11, $12)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(account)
+            .bind(strategy)
+            .bind(subscription_uid)
+            .bind(subscription_uid.is_none())
+            .bind(index.to_string())
+            .bind(fill.symbol)
+            .bind(fill.market_type)
+            .bind(fill.side)
+            .bind(fill.quantity)
+            .bind(fill.price)
+            .bind(fill.filled_at)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+
+        let before = compute_stats(&mut *tx, strategy, None, None).await.unwrap();
+        sqlx::query("DELETE FROM strategy_subscriptions WHERE id = $1").bind(subscription).execute(&mut *tx).await.unwrap();
+        let after = compute_stats(&mut *tx, strategy, None, None).await.unwrap();
+
+        assert_eq!(before.trades, 1);
+        assert!((before.realized_pnl - 10.0).abs() < 1e-9);
+        assert_eq!(after.trades, before.trades);
+        assert_eq!(after.realized_pnl, before.realized_pnl);
+        assert_eq!(after.max_drawdown, before.max_drawdown);
+    }
+}
@@ -0,0 +1,68 @@
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Пользователь из поля `user` в Telegram WebApp `initData`
+#[derive(Debug, Deserialize)]
+pub struct WebAppUser {
+    pub id: i64,
+}
+
+/// Проверка подписи Telegram WebApp `initData`
+///
+/// - `init_data` — строка `window.Telegram.WebApp.initData` как есть (url-encoded)
+/// - `bot_token` — токен бота, от имени которого открыт mini-app
+/// - `max_age_secs` — сколько секунд после `auth_date` данные считаются действительными
+///
+/// Алгоритм описан в документации Telegram: ключ = HMAC-SHA256("WebAppData", bot_token),
+/// подпись = HMAC-SHA256(ключ, отсортированные пары `key=value` через `\n` без `hash`).
+pub fn verify_init_data(init_data: &str, bot_token: &str, max_age_secs: u64) -> Result<WebAppUser, String> {
+    let mut hash = None;
+    let mut pairs: Vec<(String, String)> = Vec::new();
+
+    for (key, value) in url::form_urlencoded::parse(init_data.as_bytes()) {
+        if key == "hash" {
+            hash = Some(value.into_owned());
+        } else {
+            pairs.push((key.into_owned(), value.into_owned()));
+        }
+    }
+
+    let hash = hash.ok_or("initData has no hash")?;
+    let expected = hex::decode(&hash).map_err(|_| "initData hash is not hex".to_string())?;
+
+    pairs.sort_by(|a, b| a.0.cmp(&b.0));
+    let data_check_string = pairs
+        .iter()
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut secret = HmacSha256::new_from_slice(b"WebAppData").expect("HMAC accepts any key length");
+    secret.update(bot_token.as_bytes());
+    let secret_key = secret.finalize().into_bytes();
+
+    let mut mac = HmacSha256::new_from_slice(&secret_key).expect("HMAC accepts any key length");
+    mac.update(data_check_string.as_bytes());
+    // verify_slice сравнивает за постоянное время
+    mac.verify_slice(&expected).map_err(|_| "initData signature mismatch".to_string())?;
+
+    let field = |name: &str| pairs.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str());
+
+    let auth_date: u64 = field("auth_date")
+        .and_then(|v| v.parse().ok())
+        .ok_or("initData has no auth_date")?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    if now.saturating_sub(auth_date) > max_age_secs {
+        return Err("initData is expired".to_string());
+    }
+
+    let user = field("user").ok_or("initData has no user")?;
+    serde_json::from_str(user).map_err(|e| format!("initData user is invalid: {e}"))
+}
@@ -0,0 +1,312 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use crate::types::{ExportAccount, ExportBundle, ExportStrategy, ExportUser, StrategyConfig, TransferEntity};
+
+const DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+const USER_COLUMNS: [&str; 3] = ["user_uid", "user_telegram_id", "created_at"];
+const ACCOUNT_COLUMNS: [&str; 8] =
+    ["account_uid", "user_uid", "exchange", "label", "connector", "api_key", "encrypted_secret", "created_at"];
+const STRATEGY_COLUMNS: [&str; 9] = [
+    "strategy_uid",
+    "user_uid",
+    "account_uid",
+    "strategy_name",
+    "enabled",
+    "published",
+    "config",
+    "webhook_token",
+    "created_at",
+];
+
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn write_rows(header: &[&str], rows: impl Iterator<Item = Vec<String>>) -> String {
+    let mut out = header.join(",");
+    out.push_str("\r\n");
+    for row in rows {
+        out.push_str(&row.iter().map(|f| escape(f)).collect::<Vec<_>>().join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Разбирает CSV в строки полей
+fn parse_rows(input: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (in_quotes, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            (true, '"') => in_quotes = false,
+            (true, c) => field.push(c),
+            (false, '"') if field.is_empty() => in_quotes = true,
+            (false, ',') => row.push(std::mem::take(&mut field)),
+            // CR вне кавычек встречается только в составе CRLF
+            (false, '\r') => {}
+            (false, '\n') => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("Unterminated quoted field".to_string());
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+/// Строки CSV в виде `колонка -> значение` с проверкой заголовка
+fn parse_records<'a>(input: &str, columns: &[&'a str]) -> Result<Vec<Vec<(&'a str, String)>>, String> {
+    let mut rows = parse_rows(input)?.into_iter();
+    let header = rows.next().ok_or("CSV is empty")?;
+    if header.iter().map(String::as_str).ne(columns.iter().copied()) {
+        return Err(format!("CSV header must be: {}", columns.join(",")));
+    }
+
+    rows.enumerate()
+        .filter(|(_, row)| !(row.len() == 1 && row[0].is_empty()))
+        .map(|(i, row)| {
+            if row.len() != columns.len() {
+                return Err(format!("Row {}: expected {} fields, got {}", i + 2, columns.len(), row.len()));
+            }
+            Ok(columns.iter().copied().zip(row).collect())
+        })
+        .collect()
+}
+
+fn field<'r>(record: &'r [(&str, String)], name: &str) -> &'r str {
+    record.iter().find(|(k, _)| *k == name).map(|(_, v)| v.as_str()).unwrap_or_default()
+}
+
+fn parse_uuid(record: &[(&str, String)], name: &str) -> Result<Uuid, String> {
+    field(record, name).parse().map_err(|_| format!("Invalid {name}: {}", field(record, name)))
+}
+
+fn parse_time(record: &[(&str, String)], name: &str) -> Result<NaiveDateTime, String> {
+    NaiveDateTime::parse_from_str(field(record, name), DATETIME_FORMAT)
+        .map_err(|_| format!("Invalid {name}: {}", field(record, name)))
+}
+
+fn parse_bool(record: &[(&str, String)], name: &str) -> Result<bool, String> {
+    field(record, name).parse().map_err(|_| format!("Invalid {name}: {}", field(record, name)))
+}
+
+fn optional(value: &str) -> Option<String> {
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Выгрузка одной сущности в CSV с заголовком.
+///
+/// Кавычки и экранирование — по RFC 4180; пустое поле у необязательной колонки означает `null`.
+pub fn to_csv(bundle: &ExportBundle, entity: TransferEntity) -> String {
+    let time = |t: &NaiveDateTime| t.format(DATETIME_FORMAT).to_string();
+
+    match entity {
+        TransferEntity::Users => write_rows(
+            &USER_COLUMNS,
+            bundle
+                .users
+                .iter()
+                .map(|u| vec![u.user_uid.to_string(), u.user_telegram_id.to_string(), time(&u.created_at)]),
+        ),
+        TransferEntity::Accounts => write_rows(
+            &ACCOUNT_COLUMNS,
+            bundle.accounts.iter().map(|a| {
+                vec![
+                    a.account_uid.to_string(),
+                    a.user_uid.to_string(),
+                    a.exchange.clone(),
+                    a.label.clone().unwrap_or_default(),
+                    a.connector.clone(),
+                    a.api_key.clone(),
+                    a.encrypted_secret.clone(),
+                    time(&a.created_at),
+                ]
+            }),
+        ),
+        TransferEntity::Strategies => write_rows(
+            &STRATEGY_COLUMNS,
+            bundle.strategies.iter().map(|s| {
+                vec![
+                    s.strategy_uid.to_string(),
+                    s.user_uid.to_string(),
+                    s.account_uid.to_string(),
+                    s.strategy_name.clone(),
+                    s.enabled.to_string(),
+                    s.published.to_string(),
+                    serde_json::to_string(&s.config).unwrap_or_default(),
+                    s.webhook_token.clone(),
+                    time(&s.created_at),
+                ]
+            }),
+        ),
+    }
+}
+
+/// Загрузка CSV одной сущности в выгрузку (остальные списки пустые)
+pub fn from_csv(input: &str, entity: TransferEntity, secrets_wrapped: bool) -> Result<ExportBundle, String> {
+    let mut bundle = ExportBundle { secrets_wrapped, ..Default::default() };
+
+    match entity {
+        TransferEntity::Users => {
+            for record in parse_records(input, &USER_COLUMNS)? {
+                bundle.users.push(ExportUser {
+                    user_uid: parse_uuid(&record, "user_uid")?,
+                    user_telegram_id: field(&record, "user_telegram_id")
+                        .parse()
+                        .map_err(|_| format!("Invalid user_telegram_id: {}", field(&record, "user_telegram_id")))?,
+                    created_at: parse_time(&record, "created_at")?,
+                });
+            }
+        }
+        TransferEntity::Accounts => {
+            for record in parse_records(input, &ACCOUNT_COLUMNS)? {
+                bundle.accounts.push(ExportAccount {
+                    account_uid: parse_uuid(&record, "account_uid")?,
+                    user_uid: parse_uuid(&record, "user_uid")?,
+                    exchange: field(&record, "exchange").to_string(),
+                    label: optional(field(&record, "label")),
+                    connector: field(&record, "connector").to_string(),
+                    api_key: field(&record, "api_key").to_string(),
+                    encrypted_secret: field(&record, "encrypted_secret").to_string(),
+                    created_at: parse_time(&record, "created_at")?,
+                });
+            }
+        }
+        TransferEntity::Strategies => {
+            for record in parse_records(input, &STRATEGY_COLUMNS)? {
+                let config: StrategyConfig = serde_json::from_str(field(&record, "config"))
+                    .map_err(|e| format!("Invalid config: {e}"))?;
+                bundle.strategies.push(ExportStrategy {
+                    strategy_uid: parse_uuid(&record, "strategy_uid")?,
+                    user_uid: parse_uuid(&record, "user_uid")?,
+                    account_uid: parse_uuid(&record, "account_uid")?,
+                    strategy_name: field(&record, "strategy_name").to_string(),
+                    enabled: parse_bool(&record, "enabled")?,
+                    published: parse_bool(&record, "published")?,
+                    config,
+                    webhook_token: field(&record, "webhook_token").to_string(),
+                    created_at: parse_time(&record, "created_at")?,
+                });
+            }
+        }
+    }
+
+    Ok(bundle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(raw: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(raw, DATETIME_FORMAT).unwrap()
+    }
+
+    fn account(label: Option<&str>, api_key: &str) -> ExportAccount {
+        ExportAccount {
+            account_uid: Uuid::new_v4(),
+            user_uid: Uuid::new_v4(),
+            exchange: "binance".to_string(),
+            label: label.map(String::from),
+            connector: "native".to_string(),
+            api_key: api_key.to_string(),
+            encrypted_secret: "00ff".to_string(),
+            created_at: time("2024-03-01T10:20:30.123456"),
+        }
+    }
+
+    #[test]
+    fn accounts_round_trip_quotes_line_breaks_and_empty_label() {
+        let accounts = vec![
+            account(Some("main, \"hot\" wallet"), "key"),
+            account(Some("line one\r\nline two\nline three"), "k\"ey,"),
+            account(None, ""),
+        ];
+        let bundle = ExportBundle { accounts: accounts.clone(), ..Default::default() };
+
+        let csv = to_csv(&bundle, TransferEntity::Accounts);
+        let parsed = from_csv(&csv, TransferEntity::Accounts, false).unwrap();
+
+        assert_eq!(parsed.accounts.len(), accounts.len());
+        for (parsed, original) in parsed.accounts.iter().zip(&accounts) {
+            assert_eq!(parsed.account_uid, original.account_uid);
+            assert_eq!(parsed.user_uid, original.user_uid);
+            assert_eq!(parsed.label, original.label);
+            assert_eq!(parsed.api_key, original.api_key);
+            assert_eq!(parsed.connector, original.connector);
+            assert_eq!(parsed.encrypted_secret, original.encrypted_secret);
+            assert_eq!(parsed.created_at, original.created_at);
+        }
+    }
+
+    #[test]
+    fn strategies_round_trip_json_config() {
+        let config = StrategyConfig {
+            symbol_allowlist: vec!["BTC/USDT".to_string(), "NEAR/USDT".to_string()],
+            max_position: Some(0.5),
+            ..Default::default()
+        };
+        let strategy = ExportStrategy {
+            strategy_uid: Uuid::new_v4(),
+            user_uid: Uuid::new_v4(),
+            account_uid: Uuid::new_v4(),
+            strategy_name: "Trend, \"v2\"".to_string(),
+            enabled: true,
+            published: false,
+            config,
+            webhook_token: "abc".to_string(),
+            created_at: time("2024-03-01T00:00:00"),
+        };
+        let bundle = ExportBundle { strategies: vec![strategy.clone()], ..Default::default() };
+
+        let parsed = from_csv(&to_csv(&bundle, TransferEntity::Strategies), TransferEntity::Strategies, false).unwrap();
+
+        let parsed = &parsed.strategies[0];
+        assert_eq!(parsed.strategy_name, strategy.strategy_name);
+        assert!(parsed.enabled && !parsed.published);
+        assert_eq!(parsed.config.symbol_allowlist, strategy.config.symbol_allowlist);
+        assert_eq!(parsed.config.max_position, Some(0.5));
+        assert_eq!(parsed.created_at, strategy.created_at);
+    }
+
+    #[test]
+    fn parses_lf_only_input_and_trailing_empty_field() {
+        let rows = parse_rows("a,b,c\n1,\"x\ny\",\n").unwrap();
+        assert_eq!(rows, vec![vec!["a", "b", "c"], vec!["1", "x\ny", ""]]);
+    }
+
+    #[test]
+    fn rejects_unterminated_quote_and_wrong_header() {
+        assert!(parse_rows("a,\"b\r\n").is_err());
+        assert!(from_csv("user_uid,created_at\r\n", TransferEntity::Users, false).is_err());
+    }
+
+    #[test]
+    fn rejects_row_with_missing_fields() {
+        let csv = format!("{}\r\n{},1\r\n", USER_COLUMNS.join(","), Uuid::new_v4());
+        let err = from_csv(&csv, TransferEntity::Users, false).unwrap_err();
+        assert!(err.starts_with("Row 2"), "{err}");
+    }
+}
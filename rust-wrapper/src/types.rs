@@ -1,13 +1,22 @@
+use chrono::NaiveDateTime;
+use rocket::{FromForm, FromFormField};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::gateway::{MarginMode, MarketType, TimeInForce};
 
+
+/// **Запрос баланса**
+///
+/// Либо `accountUid`, либо `userTelegramId` (+ `exchange`, если у пользователя
+/// несколько аккаунтов).
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct BalanceRequest {
-    pub user_telegram_id: i64,
-    pub exchange: String,
+    pub account_uid: Option<Uuid>,
+    pub user_telegram_id: Option<i64>,
+    pub exchange: Option<String>,
 }
 
 /// **Запрос на регистрацию пользователя**
@@ -25,15 +34,14 @@ pub struct RegisterUserRequest {
 #[serde(rename_all = "camelCase")]
 pub struct RegisterUserResponse {
     pub user_uid: Uuid,
+    pub account_uid: Uuid,
 }
 
 /// **Запрос на обновление пользователя**
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateUserRequest {
-    pub api_key: String,
-    pub secret_key: Option<String>,
-    pub exchange: String,
+    pub user_telegram_id: i64,
 }
 
 /// **Структура пользователя**
@@ -42,17 +50,262 @@ pub struct UpdateUserRequest {
 pub struct User {
     pub id: Uuid,
     pub user_telegram_id: i64,
+    pub created_at: NaiveDateTime,
+    pub accounts: Vec<ExchangeAccount>,
+}
+
+/// **Запрос на добавление биржевого аккаунта**
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAccountRequest {
     pub exchange: String,
+    pub label: Option<String>,
+    pub api_key: String,
+    pub secret_key: String,
+    /// `gateway` (по умолчанию) или `native` — встроенный коннектор, если он есть для биржи.
+    /// Встроенный коннектор запрашивает балансы и состояние ордеров; ордера по сигналам в обоих
+    /// случаях выставляет внешний исполнитель
+    pub connector: Option<String>,
+}
+
+/// **Ответ на добавление биржевого аккаунта**
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAccountResponse {
+    pub account_uid: Uuid,
+}
+
+/// **Запрос на обновление биржевого аккаунта**
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateAccountRequest {
+    pub label: Option<String>,
+    pub api_key: String,
+    pub secret_key: Option<String>,
+    /// Не указан — остаётся прежним
+    pub connector: Option<String>,
+}
+
+/// **Структура биржевого аккаунта** (без ключей)
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeAccount {
+    pub account_uid: Uuid,
+    pub user_uid: Uuid,
+    pub exchange: String,
+    pub label: Option<String>,
+    pub connector: String,
+    pub created_at: NaiveDateTime,
 }
 
 /// **Запрос на создание стратегии**
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateStrategyRequest {
-    pub user_uid: Uuid,
+    /// Аккаунт биржи того же пользователя
+    pub account_uid: Uuid,
+    pub strategy_name: String,
+    /// Настройки; если не переданы — значения по умолчанию
+    #[serde(default)]
+    pub config: StrategyConfig,
+}
+
+/// **Запрос на изменение стратегии**
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateStrategyRequest {
+    /// Аккаунт биржи того же пользователя
+    pub account_uid: Uuid,
     pub strategy_name: String,
 }
 
+/// **Как считается объём ордера**
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SizingMode {
+    /// Объём из сигнала (`contracts`)
+    #[default]
+    Signal,
+    /// Всегда `sizingValue` (в режиме `target_position` — со знаком позиции из сигнала)
+    Fixed,
+    /// Объём из сигнала, умноженный на `sizingValue`
+    Multiplier,
+}
+
+/// **Что означает объём в сигнале**
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalMode {
+    /// `contracts` — объём ордера, направление из `signal`
+    #[default]
+    Delta,
+    /// `contracts` — желаемая позиция (`strategy.position_size`: плюс — лонг, минус — шорт, 0 — без позиции);
+    /// ордер — разница с текущей позицией по исполнениям
+    TargetPosition,
+}
+
+/// **Тип ордера, который выставляется по сигналу**
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StrategyOrderType {
+    /// Как в сигнале: `orderType` = `limit` или `stop_limit` — по цене сигнала, иначе рыночный
+    #[default]
+    Signal,
+    Market,
+    /// Лимитный по цене из сигнала; сигналы без цены отклоняются
+    Limit,
+    /// Стоп-лимитный: срабатывает по цене сигнала, лимит сдвинут на `stopLimitOffsetPct`
+    StopLimit,
+}
+
+/// **Что делать с рыночным сигналом, если живая цена ушла от цены сигнала дальше допустимого**
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviationAction {
+    /// Сигнал отклоняется
+    #[default]
+    Reject,
+    /// Вместо рыночного выставляется лимитный ордер по границе допустимого отклонения
+    Limit,
+}
+
+/// **Настройки стратегии**
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StrategyConfig {
+    pub signal_mode: SignalMode,
+    pub sizing_mode: SizingMode,
+    /// Объём для `fixed` или множитель для `multiplier`
+    pub sizing_value: Option<f64>,
+    /// Разрешённые тикеры (`NEAR/USDT`); пустой список — любые
+    pub symbol_allowlist: Vec<String>,
+    /// Максимальный объём одного ордера (в режиме `target_position` — позиции); больше — сигнал отклоняется
+    pub max_position: Option<f64>,
+    /// Плечо, 1–125
+    pub leverage: u32,
+    pub order_type: StrategyOrderType,
+    /// Допустимое проскальзывание в процентах
+    pub slippage_tolerance_pct: Option<f64>,
+    /// Максимальное отклонение живой цены от цены сигнала для рыночных ордеров, %; без него не проверяется.
+    /// Действует для спота и маржи: котировок бессрочных фьючерсов нет
+    pub max_price_deviation_pct: Option<f64>,
+    pub deviation_action: DeviationAction,
+    /// Насколько лимит стоп-лимитного ордера хуже цены срабатывания, % (покупка выше, продажа ниже)
+    pub stop_limit_offset_pct: f64,
+    /// Срок действия лимитных ордеров; без него — по умолчанию биржи
+    pub time_in_force: Option<TimeInForce>,
+    /// Лимитные ордера только мейкером
+    pub post_only: bool,
+    /// Ордера только уменьшают позицию (деривативы)
+    pub reduce_only: bool,
+    /// Минимальный интервал между сигналами, секунды
+    pub cooldown_secs: u64,
+    /// Рынок стратегии, по умолчанию спот (`orderType` сигнала на рынок не влияет)
+    pub market_type: Option<MarketType>,
+    /// Режим маржи для фьючерсов и маржи
+    pub margin_mode: MarginMode,
+}
+
+impl Default for StrategyConfig {
+    fn default() -> Self {
+        StrategyConfig {
+            signal_mode: SignalMode::Delta,
+            sizing_mode: SizingMode::Signal,
+            sizing_value: None,
+            symbol_allowlist: Vec::new(),
+            max_position: None,
+            leverage: 1,
+            order_type: StrategyOrderType::Signal,
+            slippage_tolerance_pct: None,
+            max_price_deviation_pct: None,
+            deviation_action: DeviationAction::Reject,
+            stop_limit_offset_pct: 0.0,
+            time_in_force: None,
+            post_only: false,
+            reduce_only: false,
+            cooldown_secs: 0,
+            market_type: None,
+            margin_mode: MarginMode::Isolated,
+        }
+    }
+}
+
+impl StrategyConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let positive = |v: Option<f64>| v.is_none_or(|v| v.is_finite() && v > 0.0);
+
+        match self.sizing_mode {
+            SizingMode::Signal if self.sizing_value.is_some() => {
+                return Err("sizingValue is only used with sizingMode fixed or multiplier".to_string())
+            }
+            SizingMode::Fixed | SizingMode::Multiplier if self.sizing_value.is_none() => {
+                return Err("sizingValue is required for this sizingMode".to_string())
+            }
+            _ => {}
+        }
+        if !positive(self.sizing_value) {
+            return Err("sizingValue must be positive".to_string());
+        }
+        if !positive(self.max_position) {
+            return Err("maxPosition must be positive".to_string());
+        }
+        if !(1..=125).contains(&self.leverage) {
+            return Err("leverage must be between 1 and 125".to_string());
+        }
+        if let Some(pct) = self.slippage_tolerance_pct {
+            if !(pct.is_finite() && (0.0..=100.0).contains(&pct)) {
+                return Err("slippageTolerancePct must be between 0 and 100".to_string());
+            }
+        }
+        if let Some(pct) = self.max_price_deviation_pct {
+            if !(pct.is_finite() && pct > 0.0 && pct < 100.0) {
+                return Err("maxPriceDeviationPct must be between 0 and 100".to_string());
+            }
+        }
+        if !(self.stop_limit_offset_pct.is_finite() && (0.0..100.0).contains(&self.stop_limit_offset_pct)) {
+            return Err("stopLimitOffsetPct must be between 0 and 100".to_string());
+        }
+        if self.post_only && matches!(self.order_type, StrategyOrderType::Market | StrategyOrderType::StopLimit) {
+            return Err("postOnly is only used with limit orders".to_string());
+        }
+        if self.post_only && matches!(self.time_in_force, Some(TimeInForce::Ioc | TimeInForce::Fok)) {
+            return Err("postOnly cannot be combined with IOC or FOK".to_string());
+        }
+        if self.reduce_only && self.market_type != Some(MarketType::Perpetual) {
+            return Err("reduceOnly requires marketType perpetual".to_string());
+        }
+        if self.leverage > 1 && self.market_type == Some(MarketType::Spot) {
+            return Err("leverage is not used with marketType spot".to_string());
+        }
+        if let Some(symbol) = self.symbol_allowlist.iter().find(|s| !s.contains('/')) {
+            return Err(format!("Symbol {symbol} must look like BASE/QUOTE"));
+        }
+        Ok(())
+    }
+
+    pub fn allows_symbol(&self, symbol: &str) -> bool {
+        self.symbol_allowlist.is_empty() || self.symbol_allowlist.iter().any(|s| s.eq_ignore_ascii_case(symbol))
+    }
+}
+
+/// **Настройки стратегии с номером версии**
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StrategyConfigVersion {
+    pub version: i32,
+    pub config: StrategyConfig,
+    pub created_at: NaiveDateTime,
+}
+
+/// **Запрос на изменение настроек стратегии**
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateStrategyConfigRequest {
+    pub config: StrategyConfig,
+    /// Если указана — запись пройдёт только при совпадении с текущей версией
+    pub expected_version: Option<i32>,
+}
+
 /// **Ответ на создание стратегии**
 #[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
@@ -61,6 +314,23 @@ pub struct CreateStrategyResponse {
     pub strategy_uid: Uuid,
 }
 
+/// **Запрос на ротацию вебхука**
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RotateWebhookRequest {
+    /// Сколько секунд ещё принимать старый URL (по умолчанию 0, максимум 7 дней)
+    pub grace_period_secs: Option<u32>,
+}
+
+/// **Ответ на ротацию вебхука**
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RotateWebhookResponse {
+    pub webhook: String,
+    /// До какого момента действует старый URL; `null` — отключён сразу
+    pub previous_valid_until: Option<NaiveDateTime>,
+}
+
 /// **Запрос на включение/выключение стратегий**
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
@@ -73,16 +343,218 @@ pub struct ToggleStrategiesRequest {
 #[serde(rename_all = "camelCase")]
 pub struct Strategy {
     pub strategy_uid: Uuid,
+    pub account_uid: Uuid,
     pub strategy_name: String,
     pub enabled: bool,
+    /// Стратегия видна в каталоге
+    pub published: bool,
+    pub created_at: NaiveDateTime,
+    pub config: StrategyConfig,
+    pub config_version: i32,
 }
 
 /// **Ответ на получение списка стратегий**
 #[derive(Debug, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct StrategiesResponse {
+    /// Свои стратегии (постранично)
     pub personal: Vec<Strategy>,
-    pub other: Vec<Strategy>,
+    pub page: PageInfo,
+}
+
+/// **Стратегия в каталоге**
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogueStrategy {
+    pub strategy_uid: Uuid,
+    pub strategy_name: String,
+    pub exchange: String,
+    pub config: StrategyConfig,
+    pub subscribers: i64,
+    pub created_at: NaiveDateTime,
+}
+
+/// **Параметры каталога стратегий**
+#[derive(Debug, Default, FromForm, JsonSchema)]
+pub struct CatalogueQuery {
+    /// Курсор из `page.nextCursor` предыдущего ответа
+    pub cursor: Option<String>,
+    /// Размер страницы (по умолчанию 50, максимум 200)
+    pub limit: Option<i64>,
+    pub order: Option<SortOrder>,
+    pub exchange: Option<String>,
+    /// Подстрока названия (без учёта регистра)
+    pub name: Option<String>,
+}
+
+/// **Запрос на подписку на стратегию**
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSubscriptionRequest {
+    pub strategy_uid: Uuid,
+    /// Аккаунт биржи подписчика, на котором исполняются сигналы
+    pub account_uid: Uuid,
+    /// Объём ордера автора умножается на это значение (по умолчанию 1)
+    pub sizing_multiplier: Option<f64>,
+}
+
+/// **Запрос на изменение подписки**
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSubscriptionRequest {
+    pub sizing_multiplier: Option<f64>,
+    pub active: Option<bool>,
+}
+
+/// **Параметры списка подписок** (по дате подписки)
+#[derive(Debug, Default, FromForm, JsonSchema)]
+pub struct SubscriptionsQuery {
+    /// Курсор из `page.nextCursor` предыдущего ответа
+    pub cursor: Option<String>,
+    /// Размер страницы (по умолчанию 50, максимум 200)
+    pub limit: Option<i64>,
+    pub order: Option<SortOrder>,
+    pub active: Option<bool>,
+}
+
+/// **Подписка на стратегию**
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Subscription {
+    pub subscription_uid: Uuid,
+    pub strategy_uid: Uuid,
+    pub strategy_name: String,
+    pub account_uid: Uuid,
+    pub sizing_multiplier: f64,
+    pub active: bool,
+    pub created_at: NaiveDateTime,
+}
+
+/// **Направление сортировки**
+#[derive(Debug, Clone, Copy, Default, FromFormField, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// **Поле сортировки пользователей**
+#[derive(Debug, Clone, Copy, Default, FromFormField, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UserSortField {
+    #[default]
+    #[field(value = "created_at")]
+    CreatedAt,
+    #[field(value = "telegram_id")]
+    TelegramId,
+}
+
+/// **Поле сортировки биржевых аккаунтов**
+#[derive(Debug, Clone, Copy, Default, FromFormField, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountSortField {
+    #[default]
+    #[field(value = "created_at")]
+    CreatedAt,
+    #[field(value = "exchange")]
+    Exchange,
+    #[field(value = "label")]
+    Label,
+}
+
+/// **Поле сортировки стратегий**
+#[derive(Debug, Clone, Copy, Default, FromFormField, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StrategySortField {
+    #[default]
+    #[field(value = "created_at")]
+    CreatedAt,
+    #[field(value = "name")]
+    Name,
+}
+
+/// **Параметры списка пользователей**
+#[derive(Debug, Default, FromForm, JsonSchema)]
+pub struct UsersQuery {
+    /// Курсор из `page.nextCursor` предыдущего ответа
+    pub cursor: Option<String>,
+    /// Размер страницы (по умолчанию 50, максимум 200)
+    pub limit: Option<i64>,
+    pub sort: Option<UserSortField>,
+    pub order: Option<SortOrder>,
+    /// Только пользователи с аккаунтом на этой бирже
+    pub exchange: Option<String>,
+    /// Созданы не раньше (RFC 3339 или `YYYY-MM-DD`)
+    pub created_from: Option<String>,
+    /// Созданы строго раньше (RFC 3339 или `YYYY-MM-DD`)
+    pub created_to: Option<String>,
+}
+
+/// **Поиск ордера на бирже**
+#[derive(Debug, FromForm, JsonSchema)]
+pub struct OrderStatusQuery {
+    /// Id ордера на бирже
+    pub order_id: String,
+    /// Пара, например `BTC/USDT` (Binance ищет ордера только в рамках пары)
+    pub symbol: String,
+}
+
+/// **Параметры списка биржевых аккаунтов**
+#[derive(Debug, Default, FromForm, JsonSchema)]
+pub struct AccountsQuery {
+    /// Курсор из `page.nextCursor` предыдущего ответа
+    pub cursor: Option<String>,
+    /// Размер страницы (по умолчанию 50, максимум 200)
+    pub limit: Option<i64>,
+    pub sort: Option<AccountSortField>,
+    pub order: Option<SortOrder>,
+    pub exchange: Option<String>,
+    /// Поиск по подстроке в названии аккаунта (без учёта регистра)
+    pub name: Option<String>,
+    /// Созданы не раньше (RFC 3339 или `YYYY-MM-DD`)
+    pub created_from: Option<String>,
+    /// Созданы строго раньше (RFC 3339 или `YYYY-MM-DD`)
+    pub created_to: Option<String>,
+}
+
+/// **Параметры списка стратегий**
+#[derive(Debug, Default, FromForm, JsonSchema)]
+pub struct StrategiesQuery {
+    /// Курсор из `page.nextCursor` предыдущего ответа
+    pub cursor: Option<String>,
+    /// Размер страницы (по умолчанию 50, максимум 200)
+    pub limit: Option<i64>,
+    pub sort: Option<StrategySortField>,
+    pub order: Option<SortOrder>,
+    pub account_uid: Option<Uuid>,
+    /// Биржа аккаунта, к которому привязана стратегия
+    pub exchange: Option<String>,
+    pub enabled: Option<bool>,
+    /// Поиск по подстроке в названии стратегии (без учёта регистра)
+    pub name: Option<String>,
+    /// Созданы не раньше (RFC 3339 или `YYYY-MM-DD`)
+    pub created_from: Option<String>,
+    /// Созданы строго раньше (RFC 3339 или `YYYY-MM-DD`)
+    pub created_to: Option<String>,
+}
+
+/// **Метаданные страницы**
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PageInfo {
+    /// Передайте в `cursor`, чтобы получить следующую страницу; `null`, если это последняя
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+    pub limit: i64,
+}
+
+/// **Страница списка**
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: PageInfo,
 }
 
 /// **Запрос от TradingView**
@@ -90,12 +562,678 @@ pub struct StrategiesResponse {
 #[serde(rename_all = "camelCase")]
 pub struct TradingViewSignal {
     pub id: String,
-    pub signal: String,  // "buy" / "sell"
+    pub signal: String,  // "buy" / "sell" или "open_long" / "close_long" / "open_short" / "close_short"
     pub contracts: String, // Количество контрактов
     pub ticker: String, // "NEAR/USDT"
     pub order_price: String, // Цена ордера (или "market")
     pub deposit_pct_limit: String, // Лимит депозита в процентах
-    pub order_type: String, // "spot" или другое
+    pub order_type: String, // Тип ордера: "market" / "limit" / "stop_limit", прочие значения — рыночный
     pub title: String, // Доп. информация
     pub sl_percentage: String, // Стоп-лосс
+    #[serde(default)]
+    pub market_position: Option<String>, // Позиция после сигнала: "long" / "short" / "flat"
+    #[serde(default)]
+    pub prev_market_position: Option<String>, // Позиция до сигнала
+}
+
+/// **Что сигнал делает с позицией**
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionAction {
+    OpenLong,
+    CloseLong,
+    OpenShort,
+    CloseShort,
+}
+
+impl PositionAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PositionAction::OpenLong => "open_long",
+            PositionAction::CloseLong => "close_long",
+            PositionAction::OpenShort => "open_short",
+            PositionAction::CloseShort => "close_short",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        [
+            PositionAction::OpenLong,
+            PositionAction::CloseLong,
+            PositionAction::OpenShort,
+            PositionAction::CloseShort,
+        ]
+        .into_iter()
+        .find(|action| action.as_str() == raw)
+    }
+
+    pub fn is_short(self) -> bool {
+        matches!(self, PositionAction::OpenShort | PositionAction::CloseShort)
+    }
+
+    pub fn is_close(self) -> bool {
+        matches!(self, PositionAction::CloseLong | PositionAction::CloseShort)
+    }
+}
+
+/// **Право (scope) API-токена**
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub enum Scope {
+    #[serde(rename = "users:read")]
+    UsersRead,
+    #[serde(rename = "users:write")]
+    UsersWrite,
+    #[serde(rename = "users:delete")]
+    UsersDelete,
+    #[serde(rename = "strategies:read")]
+    StrategiesRead,
+    #[serde(rename = "strategies:write")]
+    StrategiesWrite,
+    #[serde(rename = "strategies:delete")]
+    StrategiesDelete,
+    #[serde(rename = "balance:read")]
+    BalanceRead,
+    #[serde(rename = "signals:replay")]
+    SignalsReplay,
+    #[serde(rename = "tokens:manage")]
+    TokensManage,
+    #[serde(rename = "audit:read")]
+    AuditRead,
+    #[serde(rename = "data:transfer")]
+    DataTransfer,
+    #[serde(rename = "positions:rebuild")]
+    PositionsRebuild,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 12] = [
+        Scope::UsersRead,
+        Scope::UsersWrite,
+        Scope::UsersDelete,
+        Scope::StrategiesRead,
+        Scope::StrategiesWrite,
+        Scope::StrategiesDelete,
+        Scope::BalanceRead,
+        Scope::SignalsReplay,
+        Scope::TokensManage,
+        Scope::AuditRead,
+        Scope::DataTransfer,
+        Scope::PositionsRebuild,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::UsersRead => "users:read",
+            Scope::UsersWrite => "users:write",
+            Scope::UsersDelete => "users:delete",
+            Scope::StrategiesRead => "strategies:read",
+            Scope::StrategiesWrite => "strategies:write",
+            Scope::StrategiesDelete => "strategies:delete",
+            Scope::BalanceRead => "balance:read",
+            Scope::SignalsReplay => "signals:replay",
+            Scope::TokensManage => "tokens:manage",
+            Scope::AuditRead => "audit:read",
+            Scope::DataTransfer => "data:transfer",
+            Scope::PositionsRebuild => "positions:rebuild",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Scope> {
+        Scope::ALL.into_iter().find(|scope| scope.as_str() == raw)
+    }
+}
+
+/// **Роль владельца API-токена**
+///
+/// | Роль       | Права                                                               |
+/// |------------|---------------------------------------------------------------------|
+/// | `admin`    | все                                                                 |
+/// | `operator` | `users:read`, `users:write`, `strategies:read`, `strategies:write`  |
+/// | `viewer`   | `users:read`, `strategies:read`                                     |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Admin,
+    Operator,
+    Viewer,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Operator => "operator",
+            Role::Viewer => "viewer",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Role> {
+        [Role::Admin, Role::Operator, Role::Viewer].into_iter().find(|role| role.as_str() == raw)
+    }
+
+    /// Матрица прав: что вообще разрешено роли
+    pub fn scopes(self) -> &'static [Scope] {
+        match self {
+            Role::Admin => &Scope::ALL,
+            Role::Operator => &[Scope::UsersRead, Scope::UsersWrite, Scope::StrategiesRead, Scope::StrategiesWrite],
+            Role::Viewer => &[Scope::UsersRead, Scope::StrategiesRead],
+        }
+    }
+}
+
+/// **Запрос на выпуск API-токена**
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueTokenRequest {
+    pub name: String,
+    pub role: Role,
+    /// Подмножество прав роли; если не указано — все права роли
+    #[serde(default)]
+    pub scopes: Vec<Scope>,
+    /// Срок действия (UTC); без него токен бессрочный
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+/// **Ответ на выпуск API-токена**
+///
+/// Токен показывается один раз, в базе хранится только его хэш.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueTokenResponse {
+    pub token_uid: Uuid,
+    pub token: String,
+}
+
+/// **Структура API-токена** (без самого токена)
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiToken {
+    pub token_uid: Uuid,
+    pub name: String,
+    pub role: Role,
+    pub scopes: Vec<Scope>,
+    pub created_at: NaiveDateTime,
+    pub expires_at: Option<NaiveDateTime>,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+/// **Права текущего вызывающего**
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CallerPermissions {
+    /// `token` или `telegram`
+    pub kind: String,
+    pub token_uid: Option<Uuid>,
+    pub token_name: Option<String>,
+    pub role: Option<Role>,
+    pub user_uid: Option<Uuid>,
+    /// Итоговые права (права роли ∩ scope'ы токена)
+    pub permissions: Vec<Scope>,
+    /// `true` для Telegram-пользователей: права действуют только на собственные данные
+    pub own_data_only: bool,
+}
+
+/// **Событие журнала аудита**
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEvent {
+    pub event_uid: Uuid,
+    pub occurred_at: NaiveDateTime,
+    /// `token`, `admin_token` или `telegram`
+    pub actor_kind: String,
+    pub actor_id: Option<String>,
+    pub actor_name: Option<String>,
+    /// Например `user.update`, `strategy.toggle`
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<Uuid>,
+    /// Изменившиеся поля до изменения (секреты скрыты)
+    pub before: Option<serde_json::Value>,
+    /// Изменившиеся поля после изменения (секреты скрыты)
+    pub after: Option<serde_json::Value>,
+    pub request_id: String,
+    pub ip: Option<String>,
+}
+
+/// **Параметры поиска по журналу аудита**
+#[derive(Debug, Default, FromForm, JsonSchema)]
+pub struct AuditQuery {
+    /// Курсор из `page.nextCursor` предыдущего ответа
+    pub cursor: Option<String>,
+    /// Размер страницы (по умолчанию 50, максимум 200)
+    pub limit: Option<i64>,
+    pub order: Option<SortOrder>,
+    pub actor_id: Option<String>,
+    pub action: Option<String>,
+    pub target_type: Option<String>,
+    pub target_id: Option<Uuid>,
+    pub request_id: Option<String>,
+    /// Не раньше (RFC 3339 или `YYYY-MM-DD`)
+    pub from: Option<String>,
+    /// Строго раньше (RFC 3339 или `YYYY-MM-DD`)
+    pub to: Option<String>,
+}
+
+/// **Обращение к ключам биржи** (расшифровка секрета)
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialAccessEvent {
+    pub access_uid: Uuid,
+    pub accessed_at: NaiveDateTime,
+    pub account_uid: Uuid,
+    /// `balance`, `signal`, `validation`, `transfer`, `order` или `reconciliation`
+    pub purpose: String,
+    pub success: bool,
+    /// `token`, `admin_token`, `telegram`, `webhook` или `system`
+    pub caller_kind: String,
+    pub caller_id: Option<String>,
+    pub caller_name: Option<String>,
+    pub strategy_uid: Option<Uuid>,
+    pub signal_id: Option<String>,
+    pub request_id: String,
+    pub ip: Option<String>,
+}
+
+/// **Параметры истории обращений к ключам**
+#[derive(Debug, Default, FromForm, JsonSchema)]
+pub struct CredentialAccessQuery {
+    /// Курсор из `page.nextCursor` предыдущего ответа
+    pub cursor: Option<String>,
+    /// Размер страницы (по умолчанию 50, максимум 200)
+    pub limit: Option<i64>,
+    pub account_uid: Option<Uuid>,
+    /// `balance`, `signal`, `validation`, `transfer`, `order` или `reconciliation`
+    pub purpose: Option<String>,
+    /// Не раньше (RFC 3339 или `YYYY-MM-DD`)
+    pub from: Option<String>,
+    /// Строго раньше (RFC 3339 или `YYYY-MM-DD`)
+    pub to: Option<String>,
+}
+
+/// **Результат проверки ключей биржи**
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateAccountResponse {
+    pub valid: bool,
+    pub message: Option<String>,
+}
+
+/// **Статистика стратегии**
+///
+/// Считается по исполнениям ордеров автора стратегии; PnL — в валюте котировки.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StrategyStats {
+    pub strategy_uid: Uuid,
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+    /// Реализованный PnL за вычетом комиссий
+    pub realized_pnl: f64,
+    /// Количество закрытых сделок (позиция открыта и закрыта)
+    pub trades: i64,
+    pub winning_trades: i64,
+    /// Доля прибыльных сделок, 0–1
+    pub win_rate: Option<f64>,
+    /// Среднее время удержания позиции, секунды
+    pub avg_holding_secs: Option<f64>,
+    /// Максимальная просадка кривой реализованного PnL
+    pub max_drawdown: f64,
+    pub computed_at: NaiveDateTime,
+}
+
+/// **Решение проверки отклонения цены по сигналу**
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SlippageDecision {
+    /// Не проверялось: лимит не задан, ордер лимитный, нет цены сигнала или живой котировки
+    /// (у бессрочных фьючерсов котировок нет, они всегда здесь)
+    Unchecked,
+    /// Отклонение в пределах лимита
+    Passed,
+    /// Сигнал отклонён
+    Rejected,
+    /// Рыночный ордер заменён лимитным по границе
+    Limited,
+}
+
+impl SlippageDecision {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SlippageDecision::Unchecked => "unchecked",
+            SlippageDecision::Passed => "passed",
+            SlippageDecision::Rejected => "rejected",
+            SlippageDecision::Limited => "limited",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        [
+            SlippageDecision::Unchecked,
+            SlippageDecision::Passed,
+            SlippageDecision::Rejected,
+            SlippageDecision::Limited,
+        ]
+        .into_iter()
+        .find(|decision| decision.as_str() == raw)
+    }
+}
+
+/// **Принятый сигнал стратегии и решение по проскальзыванию**
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalRecord {
+    pub signal_record_uid: Uuid,
+    /// `id` из сигнала TradingView
+    pub signal_id: String,
+    pub symbol: String,
+    pub side: String,
+    pub market_type: MarketType,
+    pub position_action: PositionAction,
+    pub signal_price: Option<f64>,
+    /// Котировка на момент сигнала (ask для покупки, bid для продажи)
+    pub live_price: Option<f64>,
+    /// Отклонение живой цены от цены сигнала, %
+    pub deviation_pct: Option<f64>,
+    pub slippage_decision: SlippageDecision,
+    /// Цена выставленного ордера; `null` — рыночный
+    pub order_price: Option<f64>,
+    pub received_at: NaiveDateTime,
+}
+
+/// **Параметры списка сигналов**
+#[derive(Debug, Default, FromForm, JsonSchema)]
+pub struct SignalsQuery {
+    /// Сколько последних сигналов вернуть (по умолчанию 50, максимум 200)
+    pub limit: Option<i64>,
+}
+
+/// **Позиция по исполнениям** (аккаунт, стратегия, символ)
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Position {
+    pub position_uid: Uuid,
+    pub account_uid: Uuid,
+    /// `null` — ордера вне стратегий
+    pub strategy_uid: Option<Uuid>,
+    /// Символ последнего исполнения
+    pub symbol: String,
+    /// Спот, фьючерсы и маржа по одному символу — разные позиции
+    pub market_type: MarketType,
+    /// Чистый объём: плюс — лонг, минус — шорт, 0 — позиция закрыта
+    pub quantity: f64,
+    /// Средняя цена входа открытой позиции
+    pub avg_entry_price: Option<f64>,
+    /// Реализованный PnL за вычетом комиссий, в валюте котировки
+    pub realized_pnl: f64,
+    pub fees: f64,
+    pub fill_count: i32,
+    pub last_fill_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// **Фильтры списка позиций** (по аккаунту, символу, типу рынка и стратегии)
+#[derive(Debug, Default, FromForm, JsonSchema)]
+pub struct PositionsQuery {
+    /// Курсор из `page.nextCursor` предыдущего ответа
+    pub cursor: Option<String>,
+    /// Размер страницы (по умолчанию 50, максимум 200)
+    pub limit: Option<i64>,
+    pub account_uid: Option<Uuid>,
+    pub strategy_uid: Option<Uuid>,
+    /// Символ в любом написании (`BTC/USDT`, `BTCUSDT`)
+    pub symbol: Option<String>,
+    /// Показывать и закрытые позиции (по умолчанию только открытые)
+    pub include_closed: Option<bool>,
+}
+
+/// **Запрос на перестроение журнала позиций**
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RebuildPositionsRequest {
+    /// Только этот аккаунт; если не указан — все
+    pub account_uid: Option<Uuid>,
+}
+
+/// **Итог перестроения журнала позиций**
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RebuildPositionsResponse {
+    /// Сколько исполнений применено
+    pub fills: usize,
+    /// Сколько позиций получилось
+    pub positions: usize,
+}
+
+/// **Серьёзность расхождения журнала позиций с биржей**
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema, FromFormField)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscrepancySeverity {
+    /// На бирже актива больше, чем в журнале (например, куплен вне сервиса)
+    Info,
+    /// Актива меньше, чем в журнале, в пределах `RECONCILE_CRITICAL_PCT`
+    Warning,
+    /// Актива меньше, чем в журнале, больше чем на `RECONCILE_CRITICAL_PCT`
+    Critical,
+}
+
+impl DiscrepancySeverity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiscrepancySeverity::Info => "info",
+            DiscrepancySeverity::Warning => "warning",
+            DiscrepancySeverity::Critical => "critical",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        [DiscrepancySeverity::Info, DiscrepancySeverity::Warning, DiscrepancySeverity::Critical]
+            .into_iter()
+            .find(|severity| severity.as_str() == raw)
+    }
+}
+
+/// **Расхождение журнала позиций с балансом на бирже**
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Discrepancy {
+    pub discrepancy_uid: Uuid,
+    /// Запуск сверки, в котором найдено расхождение
+    pub run_uid: Uuid,
+    pub account_uid: Uuid,
+    pub asset: String,
+    /// Сколько актива должно быть по журналу позиций
+    pub expected: f64,
+    /// Сколько актива на бирже (`total`)
+    pub actual: f64,
+    /// `(actual - expected) / expected`, %
+    pub difference_pct: f64,
+    pub severity: DiscrepancySeverity,
+    /// Стратегии и подписки аккаунта остановлены из-за этого расхождения
+    pub auto_disabled: bool,
+    pub detected_at: NaiveDateTime,
+}
+
+/// **Фильтры расхождений сверки**
+#[derive(Debug, Default, FromForm, JsonSchema)]
+pub struct DiscrepanciesQuery {
+    /// Курсор из `page.nextCursor` предыдущего ответа
+    pub cursor: Option<String>,
+    /// Размер страницы (по умолчанию 50, максимум 200)
+    pub limit: Option<i64>,
+    pub account_uid: Option<Uuid>,
+    pub severity: Option<DiscrepancySeverity>,
+}
+
+/// **Параметры статистики стратегии**
+#[derive(Debug, Default, FromForm, JsonSchema)]
+pub struct StatsQuery {
+    /// Сделки, закрытые не раньше (RFC 3339 или `YYYY-MM-DD`)
+    pub from: Option<String>,
+    /// Сделки, закрытые строго раньше (RFC 3339 или `YYYY-MM-DD`)
+    pub to: Option<String>,
+}
+
+/// **Сущность для импорта/экспорта**
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferEntity {
+    Users,
+    Accounts,
+    Strategies,
+}
+
+/// **Пользователь в выгрузке**
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportUser {
+    pub user_uid: Uuid,
+    pub user_telegram_id: i64,
+    pub created_at: NaiveDateTime,
+}
+
+/// **Биржевой аккаунт в выгрузке**
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportAccount {
+    pub account_uid: Uuid,
+    pub user_uid: Uuid,
+    pub exchange: String,
+    pub label: Option<String>,
+    #[serde(default = "default_connector")]
+    pub connector: String,
+    pub api_key: String,
+    /// Секрет, зашифрованный `SALT_KEY` источника или ключом выгрузки
+    pub encrypted_secret: String,
+    pub created_at: NaiveDateTime,
+}
+
+fn default_connector() -> String {
+    "gateway".to_string()
+}
+
+/// **Стратегия в выгрузке**
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportStrategy {
+    pub strategy_uid: Uuid,
+    pub user_uid: Uuid,
+    pub account_uid: Uuid,
+    pub strategy_name: String,
+    pub enabled: bool,
+    pub published: bool,
+    pub config: StrategyConfig,
+    /// Токен вебхука переносится, чтобы алерты TradingView продолжили работать
+    pub webhook_token: String,
+    pub created_at: NaiveDateTime,
+}
+
+/// **Выгрузка пользователей, аккаунтов и стратегий**
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportBundle {
+    /// `true` — секреты перешифрованы ключом выгрузки, иначе зашифрованы `SALT_KEY` источника
+    pub secrets_wrapped: bool,
+    #[serde(default)]
+    pub users: Vec<ExportUser>,
+    #[serde(default)]
+    pub accounts: Vec<ExportAccount>,
+    #[serde(default)]
+    pub strategies: Vec<ExportStrategy>,
+}
+
+/// **Запрос на выгрузку**
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportRequest {
+    /// Только эти пользователи; если не указано — все
+    pub user_uids: Option<Vec<Uuid>>,
+    /// Ключ, которым перешифровать секреты; без него они остаются под `SALT_KEY`
+    pub export_key: Option<String>,
+}
+
+/// **Запрос на выгрузку одной сущности в CSV**
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportCsvRequest {
+    pub entity: TransferEntity,
+    pub user_uids: Option<Vec<Uuid>>,
+    pub export_key: Option<String>,
+}
+
+/// **Запрос на загрузку выгрузки**
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportRequest {
+    pub bundle: ExportBundle,
+    /// Ключ выгрузки, если секреты перешифрованы (`secretsWrapped`)
+    pub import_key: Option<String>,
+    /// Только отчёт, без изменений
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// **Запрос на загрузку CSV одной сущности**
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportCsvRequest {
+    pub entity: TransferEntity,
+    /// CSV с заголовком, как в выгрузке
+    pub csv: String,
+    /// Ключ выгрузки, если секреты перешифрованы
+    pub import_key: Option<String>,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// **Что импорт сделал (или сделал бы) со строкой**
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportAction {
+    Created,
+    Updated,
+    Unchanged,
+    Failed,
+}
+
+/// **Результат импорта одной строки**
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportItem {
+    pub entity: TransferEntity,
+    pub uid: Uuid,
+    pub action: ImportAction,
+    pub error: Option<String>,
+}
+
+/// **Отчёт об импорте**
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub failed: usize,
+    pub items: Vec<ImportItem>,
+}
+
+/// **Состояние предохранителя биржи**
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakerStatus {
+    pub exchange: String,
+    /// Через что идут запросы: `gateway`, `native` или `paper`
+    pub connector: String,
+    /// `closed`, `open` или `half_open`
+    pub state: String,
+    /// Через сколько секунд будет пропущен пробный запрос (для `open`/`half_open`)
+    pub retry_in_secs: Option<u64>,
+    pub consecutive_failures: u32,
+    pub total_failures: u64,
+    /// Запросы, отклонённые без обращения к бирже
+    pub fast_failures: u64,
+    pub last_error: Option<String>,
+    pub in_flight: usize,
+    pub max_concurrency: usize,
 }
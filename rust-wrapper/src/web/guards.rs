@@ -1,54 +1,393 @@
+use std::marker::PhantomData;
+
 use rocket::http::Status;
 use rocket::request::{FromRequest, Outcome, Request};
+use rocket::serde::json::Json;
 use rocket::State;
 use rocket_okapi::gen::OpenApiGenerator;
 use rocket_okapi::okapi::openapi3::{Object, SecurityRequirement, SecurityScheme, SecuritySchemeData};
 use rocket_okapi::request::{OpenApiFromRequest, RequestHeaderInput};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::audit::AuditActor;
 use crate::config::Config;
+use crate::crypto::hash_api_token;
+use crate::telegram::verify_init_data;
+use crate::types::{Role, Scope};
+
+/// Право, которое требует роут. Реализуется маркерными типами из [`scopes`].
+pub trait RequiredScope: Send + Sync + 'static {
+    const SCOPE: Scope;
+}
+
+/// Маркерные типы для `TokenGuard<S>` / `Caller<S>`
+pub mod scopes {
+    use super::RequiredScope;
+    use crate::types::Scope;
+
+    macro_rules! scope_marker {
+        ($($name:ident => $scope:expr),* $(,)?) => {
+            $(
+                pub struct $name;
+
+                impl RequiredScope for $name {
+                    const SCOPE: Scope = $scope;
+                }
+            )*
+        };
+    }
+
+    scope_marker! {
+        UsersRead => Scope::UsersRead,
+        UsersWrite => Scope::UsersWrite,
+        UsersDelete => Scope::UsersDelete,
+        StrategiesRead => Scope::StrategiesRead,
+        StrategiesWrite => Scope::StrategiesWrite,
+        StrategiesDelete => Scope::StrategiesDelete,
+        BalanceRead => Scope::BalanceRead,
+        SignalsReplay => Scope::SignalsReplay,
+        TokensManage => Scope::TokensManage,
+        AuditRead => Scope::AuditRead,
+        DataTransfer => Scope::DataTransfer,
+        PositionsRebuild => Scope::PositionsRebuild,
+    }
+}
+
+/// Права Telegram-пользователя mini-app (только на собственные данные)
+pub const TELEGRAM_USER_SCOPES: [Scope; 7] = [
+    Scope::UsersRead,
+    Scope::UsersWrite,
+    Scope::UsersDelete,
+    Scope::StrategiesRead,
+    Scope::StrategiesWrite,
+    Scope::StrategiesDelete,
+    Scope::BalanceRead,
+];
+
+/// Проверенный API-токен
+#[derive(Clone)]
+pub struct TokenPrincipal {
+    /// `None` для бутстрап-токена из `ADMIN_TOKEN`
+    pub token_uid: Option<Uuid>,
+    pub name: String,
+    pub role: Role,
+    pub scopes: Vec<Scope>,
+}
+
+impl TokenPrincipal {
+    /// Итоговые права: scope'ы токена, разрешённые его роли
+    pub fn permissions(&self) -> Vec<Scope> {
+        self.scopes.iter().copied().filter(|s| self.role.scopes().contains(s)).collect()
+    }
+}
+
+/// Кто делает запрос, без привязки к конкретному праву
+#[derive(Clone)]
+pub enum Identity {
+    Token(TokenPrincipal),
+    TelegramUser { user_uid: Uuid },
+}
+
+impl Identity {
+    pub fn permissions(&self) -> Vec<Scope> {
+        match self {
+            Identity::Token(principal) => principal.permissions(),
+            Identity::TelegramUser { .. } => TELEGRAM_USER_SCOPES.to_vec(),
+        }
+    }
+
+    pub fn has(&self, scope: Scope) -> bool {
+        self.permissions().contains(&scope)
+    }
+}
+
+/// Определяет вызывающего по `Authorization`: API-токен или `tma <initData>`.
+///
+/// Результат кэшируется на время запроса, чтобы несколько guard'ов не ходили в базу повторно.
+async fn identify<'r>(request: &'r Request<'_>) -> &'r Result<Identity, (Status, String)> {
+    request
+        .local_cache_async(async {
+            let config = request.guard::<&State<Config>>().await.unwrap();
+            let pool = request.guard::<&State<PgPool>>().await.unwrap();
+
+            let header = request
+                .headers()
+                .get_one("Authorization")
+                .ok_or((Status::Unauthorized, "Missing Authorization header".to_string()))?;
+
+            if let Some(init_data) = header.strip_prefix("tma ") {
+                return identify_telegram_user(config, pool, init_data).await;
+            }
+
+            // Сравнение хэшей blake3 выполняется за постоянное время
+            if let Some(admin_token) = &config.admin_token {
+                if blake3::hash(header.as_bytes()) == blake3::hash(admin_token.as_bytes()) {
+                    return Ok(Identity::Token(TokenPrincipal {
+                        token_uid: None,
+                        name: "ADMIN_TOKEN".to_string(),
+                        role: Role::Admin,
+                        scopes: Scope::ALL.to_vec(),
+                    }));
+                }
+            }
+
+            let row = sqlx::query!(
+                "UPDATE api_tokens SET last_used_at = now()
+                 WHERE token_hash = $1
+                   AND revoked_at IS NULL
+                   AND (expires_at IS NULL OR expires_at > now())
+                 RETURNING id, name, role, scopes",
+                hash_api_token(header)
+            )
+            .fetch_optional(pool.inner())
+            .await
+            .map_err(|e| (Status::InternalServerError, format!("Database error: {e}")))?
+            .ok_or((Status::Unauthorized, "Invalid or expired token".to_string()))?;
 
-pub struct AdminGuard;
+            let role = Role::parse(&row.role)
+                .ok_or((Status::InternalServerError, format!("Unknown role {}", row.role)))?;
+
+            Ok(Identity::Token(TokenPrincipal {
+                token_uid: Some(row.id),
+                name: row.name,
+                role,
+                scopes: row.scopes.iter().filter_map(|s| Scope::parse(s)).collect(),
+            }))
+        })
+        .await
+}
+
+async fn identify_telegram_user(config: &Config, pool: &PgPool, init_data: &str) -> Result<Identity, (Status, String)> {
+    let bot_token = config
+        .telegram_bot_token
+        .as_ref()
+        .ok_or((Status::Unauthorized, "Telegram auth is disabled".to_string()))?;
+
+    let tg_user = verify_init_data(init_data, bot_token, config.telegram_init_data_max_age)
+        .map_err(|e| (Status::Unauthorized, e))?;
+
+    sqlx::query_scalar!("SELECT id FROM users WHERE user_telegram_id = $1", tg_user.id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| (Status::InternalServerError, format!("Database error: {e}")))?
+        .map(|user_uid| Identity::TelegramUser { user_uid })
+        .ok_or((Status::Forbidden, "Telegram user is not registered".to_string()))
+}
 
 #[rocket::async_trait]
-impl<'r> FromRequest<'r> for AdminGuard {
-    type Error = ();
+impl<'r> FromRequest<'r> for Identity {
+    type Error = String;
 
     async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        // Получаем конфиг из state
-        let config = request.guard::<&State<Config>>().await.unwrap();
-        // Проверяем наличие заголовка Authorization и сравниваем с токеном из конфига
-        match request.headers().get_one("Authorization") {
-            Some(token) if token == config.admin_token => Outcome::Success(AdminGuard),
-            _ => Outcome::Error((Status::Unauthorized, ())),
+        match identify(request).await {
+            Ok(identity) => Outcome::Success(identity.clone()),
+            Err(e) => Outcome::Error(e.clone()),
         }
     }
 }
 
+impl<'a> OpenApiFromRequest<'a> for Identity {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        Ok(security_input(
+            "ApiTokenOrTelegram",
+            "API token, or `tma <initData>` from a Telegram WebApp (access limited to the caller's own data).",
+            Vec::new(),
+        ))
+    }
+}
 
-impl<'a> OpenApiFromRequest<'a> for AdminGuard {
+/// API-токен (не Telegram-пользователь), роль и scope'ы которого дают право `S`
+pub struct TokenGuard<S: RequiredScope> {
+    _scope: PhantomData<S>,
+}
+
+#[rocket::async_trait]
+impl<'r, S: RequiredScope> FromRequest<'r> for TokenGuard<S> {
+    type Error = String;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match identify(request).await {
+            Ok(identity @ Identity::Token(_)) if identity.has(S::SCOPE) => {
+                Outcome::Success(TokenGuard { _scope: PhantomData })
+            }
+            Ok(_) => Outcome::Error((Status::Forbidden, format!("Missing permission {}", S::SCOPE.as_str()))),
+            Err(e) => Outcome::Error(e.clone()),
+        }
+    }
+}
+
+/// Схема безопасности в OpenAPI с указанием требуемого права
+fn security_input(scheme_name: &str, description: &str, scopes: Vec<String>) -> RequestHeaderInput {
+    let security_scheme = SecurityScheme {
+        description: Some(description.to_owned()),
+        data: SecuritySchemeData::ApiKey {
+            name: "Authorization".to_owned(),
+            location: "header".to_owned(),
+        },
+        extensions: Object::default(),
+    };
+
+    let mut security_req = SecurityRequirement::new();
+    // Ключ должен совпадать с названием схемы:
+    security_req.insert(scheme_name.to_string(), scopes);
+
+    RequestHeaderInput::Security(scheme_name.to_owned(), security_scheme, security_req)
+}
+
+impl<'a, S: RequiredScope> OpenApiFromRequest<'a> for TokenGuard<S> {
     fn from_request_input(
         _gen: &mut OpenApiGenerator,
         _name: String,
         _required: bool,
     ) -> rocket_okapi::Result<RequestHeaderInput> {
-        // 1. Описываем схему безопасности
-        let security_scheme = SecurityScheme {
-            description: Some("Use admin token in `Authorization` header.".to_owned()),
-            data: SecuritySchemeData::ApiKey {
-                name: "Authorization".to_owned(),
-                location: "header".to_owned(),
-            },
-            extensions: Object::default(),
-        };
+        Ok(security_input(
+            "ApiToken",
+            "API token in `Authorization` header. Each route lists the permission it requires; \
+             a token has it when both its role and its scopes allow it.",
+            vec![S::SCOPE.as_str().to_string()],
+        ))
+    }
+}
+
+/// Кто вызывает роут: API-токен с правом `S` или пользователь Telegram mini-app.
+///
+/// Пользователь передаёт `Authorization: tma <initData>` и получает доступ
+/// только к своим аккаунтам, стратегиям и балансам.
+pub enum Caller<S: RequiredScope> {
+    Token(PhantomData<S>),
+    TelegramUser { user_uid: Uuid },
+}
 
-        // 2. Требование безопасности (SecurityRequirement)
-        let mut security_req = SecurityRequirement::new();
-        // Ключ должен совпадать с названием схемы:
-        security_req.insert("AdminToken".to_string(), Vec::new());
+impl<S: RequiredScope> Caller<S> {
+    /// Ограничение выборки по владельцу: `None` — без ограничений (API-токен)
+    pub fn owner_filter(&self) -> Option<Uuid> {
+        match self {
+            Caller::Token(_) => None,
+            Caller::TelegramUser { user_uid } => Some(*user_uid),
+        }
+    }
+
+    /// Проверяет, что вызывающий может работать с данными пользователя `user_uid`.
+    ///
+    /// Чужой пользователь выглядит как несуществующий.
+    pub fn ensure_user(&self, user_uid: Uuid) -> Result<(), Json<String>> {
+        match self.owner_filter() {
+            Some(own) if own != user_uid => Err(Json("User not found".to_string())),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, S: RequiredScope> FromRequest<'r> for Caller<S> {
+    type Error = String;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match identify(request).await {
+            Ok(identity) if !identity.has(S::SCOPE) => {
+                Outcome::Error((Status::Forbidden, format!("Missing permission {}", S::SCOPE.as_str())))
+            }
+            Ok(Identity::Token(_)) => Outcome::Success(Caller::Token(PhantomData)),
+            Ok(Identity::TelegramUser { user_uid }) => Outcome::Success(Caller::TelegramUser { user_uid: *user_uid }),
+            Err(e) => Outcome::Error(e.clone()),
+        }
+    }
+}
 
-        Ok(RequestHeaderInput::Security(
-            "AdminToken".to_owned(),
-            security_scheme,
-            security_req,
+impl<'a, S: RequiredScope> OpenApiFromRequest<'a> for Caller<S> {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        Ok(security_input(
+            "ApiTokenOrTelegram",
+            "API token, or `tma <initData>` from a Telegram WebApp (access limited to the caller's own data).",
+            vec![S::SCOPE.as_str().to_string()],
         ))
     }
 }
+
+/// Id запроса (`X-Request-Id` или новый) и IP клиента — для журналов
+#[derive(Clone)]
+pub struct RequestMeta {
+    pub request_id: String,
+    pub ip: Option<String>,
+}
+
+fn request_meta<'r>(request: &'r Request<'_>) -> &'r RequestMeta {
+    request.local_cache(|| RequestMeta {
+        request_id: request
+            .headers()
+            .get_one("X-Request-Id")
+            .map(str::to_string)
+            .unwrap_or_else(|| Uuid::new_v4().to_string()),
+        ip: request.client_ip().map(|ip| ip.to_string()),
+    })
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RequestMeta {
+    type Error = String;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(request_meta(request).clone())
+    }
+}
+
+impl<'a> OpenApiFromRequest<'a> for RequestMeta {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        Ok(RequestHeaderInput::None)
+    }
+}
+
+/// Контекст для журнала аудита: кто вызывает, id запроса и IP
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuditActor {
+    type Error = String;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let identity = match identify(request).await {
+            Ok(identity) => identity,
+            Err(e) => return Outcome::Error(e.clone()),
+        };
+
+        let (kind, id, name) = match identity {
+            Identity::Token(TokenPrincipal { token_uid: None, name, .. }) => ("admin_token", None, Some(name.clone())),
+            Identity::Token(principal) => (
+                "token",
+                principal.token_uid.map(|uid| uid.to_string()),
+                Some(principal.name.clone()),
+            ),
+            Identity::TelegramUser { user_uid } => ("telegram", Some(user_uid.to_string()), None),
+        };
+
+        let meta = request_meta(request);
+
+        Outcome::Success(AuditActor {
+            kind,
+            id,
+            name,
+            request_id: meta.request_id.clone(),
+            ip: meta.ip.clone(),
+        })
+    }
+}
+
+impl<'a> OpenApiFromRequest<'a> for AuditActor {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        Ok(RequestHeaderInput::None)
+    }
+}
@@ -1,3 +1,59 @@
 pub mod guards;
+pub mod pagination;
 pub mod routes;
 pub mod server;
+
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::gateway::GatewayError;
+use crate::web::guards::{Caller, RequiredScope};
+
+/// Ошибка маршрутов API: статус и сообщение. Чужие и несуществующие пользователи/объекты отдают 404
+pub(crate) type ApiError = (Status, Json<String>);
+
+pub(crate) fn not_found(message: &str) -> ApiError {
+    (Status::NotFound, Json(message.to_string()))
+}
+
+pub(crate) fn internal(Json(message): Json<String>) -> ApiError {
+    (Status::InternalServerError, Json(message))
+}
+
+pub(crate) fn db_error(e: sqlx::Error) -> ApiError {
+    internal(Json(format!("Database error: {:?}", e)))
+}
+
+/// Проверяет доступ к пользователю и что он существует
+pub(crate) async fn ensure_owner<S: RequiredScope>(pool: &PgPool, caller: &Caller<S>, user_uid: Uuid) -> Result<(), ApiError> {
+    caller.ensure_user(user_uid).map_err(|e| (Status::NotFound, e))?;
+
+    let exists = sqlx::query_scalar!("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)", user_uid)
+        .fetch_one(pool)
+        .await
+        .map_err(db_error)?
+        .unwrap_or(false);
+
+    if !exists {
+        return Err(not_found("User not found"));
+    }
+    Ok(())
+}
+
+/// Ошибка записи в журнал аудита (изменение при этом откатывается вместе с транзакцией)
+pub fn audit_error(e: sqlx::Error) -> Json<String> {
+    Json(format!("Audit error: {e}"))
+}
+
+/// Статус ответа по ошибке шлюза: 503 — запрос отклонён без обращения к бирже
+/// (предохранитель или перегрузка), 502 — биржа недоступна, 422 — биржа отказала
+pub fn gateway_error(e: GatewayError) -> (Status, Json<String>) {
+    let status = match e {
+        GatewayError::CircuitOpen { .. } | GatewayError::Overloaded(_) => Status::ServiceUnavailable,
+        GatewayError::Unavailable(_) | GatewayError::InvalidResponse(_) => Status::BadGateway,
+        GatewayError::Rejected(_) => Status::UnprocessableEntity,
+    };
+    (status, Json(e.to_string()))
+}
@@ -0,0 +1,123 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use sqlx::{Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::types::{PageInfo, SortOrder};
+
+pub const DEFAULT_LIMIT: i64 = 50;
+pub const MAX_LIMIT: i64 = 200;
+
+/// Колонка, по которой можно сортировать список
+pub struct SortColumn {
+    /// Выражение в SQL (`strategies.created_at`)
+    pub expr: &'static str,
+    /// Тип, к которому приводится значение из курсора (`timestamp`, `text`, ...)
+    pub sql_type: &'static str,
+}
+
+/// Позиция в списке: значение колонки сортировки (в текстовом виде) и id последней записи.
+///
+/// Наружу отдаётся как непрозрачная hex-строка.
+pub struct Cursor {
+    pub key: String,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        hex::encode(format!("{}|{}", self.id, self.key))
+    }
+
+    pub fn decode(raw: &str) -> Result<Self, String> {
+        let bytes = hex::decode(raw).map_err(|_| "Invalid cursor".to_string())?;
+        let text = String::from_utf8(bytes).map_err(|_| "Invalid cursor".to_string())?;
+        let (id, key) = text.split_once('|').ok_or("Invalid cursor")?;
+        let id = Uuid::parse_str(id).map_err(|_| "Invalid cursor".to_string())?;
+
+        Ok(Cursor { key: key.to_string(), id })
+    }
+}
+
+/// Ограничивает `limit` из запроса разумными рамками
+pub fn clamp_limit(limit: Option<i64>) -> i64 {
+    limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+}
+
+/// Разбирает границу диапазона дат: RFC 3339, `YYYY-MM-DDTHH:MM:SS` или `YYYY-MM-DD` (UTC)
+pub fn parse_datetime(raw: &str) -> Result<NaiveDateTime, String> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(raw) {
+        return Ok(dt.naive_utc());
+    }
+    if let Ok(dt) = NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M:%S%.f") {
+        return Ok(dt);
+    }
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .map(|d| d.and_hms_opt(0, 0, 0).expect("midnight is a valid time"))
+        .map_err(|_| format!("Invalid date: {raw}"))
+}
+
+/// Добавляет в запрос `AND created_at >= from AND created_at < to`
+pub fn push_created_range(
+    qb: &mut QueryBuilder<'_, Postgres>,
+    column: &'static str,
+    created_from: Option<&str>,
+    created_to: Option<&str>,
+) -> Result<(), String> {
+    if let Some(from) = created_from {
+        qb.push(format!(" AND {column} >= ")).push_bind(parse_datetime(from)?);
+    }
+    if let Some(to) = created_to {
+        qb.push(format!(" AND {column} < ")).push_bind(parse_datetime(to)?);
+    }
+    Ok(())
+}
+
+/// Добавляет условие keyset-пагинации, `ORDER BY` и `LIMIT` (на одну запись больше,
+/// чтобы понять, есть ли следующая страница).
+///
+/// `id_expr` — колонка id, используемая как тай-брейкер при одинаковых значениях сортировки.
+pub fn push_page(
+    qb: &mut QueryBuilder<'_, Postgres>,
+    sort: &SortColumn,
+    id_expr: &'static str,
+    order: SortOrder,
+    cursor: Option<&str>,
+    limit: i64,
+) -> Result<(), String> {
+    let (cmp, dir) = match order {
+        SortOrder::Asc => (">", "ASC"),
+        SortOrder::Desc => ("<", "DESC"),
+    };
+
+    if let Some(raw) = cursor {
+        let cursor = Cursor::decode(raw)?;
+        qb.push(format!(" AND ({}, {id_expr}) {cmp} (", sort.expr))
+            .push_bind(cursor.key)
+            .push(format!("::{}, ", sort.sql_type))
+            .push_bind(cursor.id)
+            .push(")");
+    }
+
+    qb.push(format!(" ORDER BY {} {dir}, {id_expr} {dir} LIMIT ", sort.expr))
+        .push_bind(limit + 1);
+
+    Ok(())
+}
+
+/// Обрезает лишнюю запись, выбранную `push_page`, и формирует метаданные страницы
+pub fn finish_page<T>(
+    mut rows: Vec<T>,
+    limit: i64,
+    cursor_of: impl Fn(&T) -> Cursor,
+) -> (Vec<T>, PageInfo) {
+    let has_more = rows.len() as i64 > limit;
+    rows.truncate(limit as usize);
+
+    let next_cursor = if has_more {
+        rows.last().map(|row| cursor_of(row).encode())
+    } else {
+        None
+    };
+
+    (rows, PageInfo { next_cursor, has_more, limit })
+}
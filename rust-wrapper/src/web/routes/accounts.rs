@@ -0,0 +1,422 @@
+use rocket::{delete, get, post, put, serde::json::Json, State};
+use rocket_okapi::openapi;
+use chrono::NaiveDateTime;
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::audit::{self, decrypt_credentials, AuditActor, CredentialAccess, CredentialPurpose};
+use crate::config::Config;
+use crate::crypto::encrypt_secret;
+use crate::types::{
+    AccountSortField, AccountsQuery, CreateAccountRequest, CreateAccountResponse, CredentialAccessEvent,
+    CredentialAccessQuery, ExchangeAccount, Page, SortOrder, UpdateAccountRequest, ValidateAccountResponse,
+};
+use crate::web::{audit_error, db_error, gateway_error, not_found, ApiError};
+use crate::gateway::{
+    capabilities, validate_connector, ExchangeCredentials, GatewayError, MarketType, SharedGateway, GATEWAY_CONNECTOR,
+};
+use crate::web::pagination::{clamp_limit, finish_page, push_created_range, push_page, Cursor, SortColumn};
+use crate::web::guards::scopes::{UsersDelete, UsersRead, UsersWrite};
+use crate::web::guards::Caller;
+
+/// **POST /api/user/<user_uid>/account** — Добавление биржевого аккаунта пользователю
+#[openapi(tag = "Account Management")]
+#[post("/user/<user_uid>/account", format = "json", data = "<account_data>")]
+pub async fn create_account(
+    pool: &State<PgPool>,
+    config: &State<Config>,
+    caller: Caller<UsersWrite>,
+    actor: AuditActor,
+    user_uid: Uuid,
+    account_data: Json<CreateAccountRequest>,
+) -> Result<Json<CreateAccountResponse>, Json<String>> {
+    caller.ensure_user(user_uid)?;
+    let connector = account_data.connector.as_deref().unwrap_or(GATEWAY_CONNECTOR);
+    validate_connector(&account_data.exchange, connector).map_err(Json)?;
+
+    let account_uid = Uuid::new_v4();
+
+    let encrypted_secret = encrypt_secret(&account_data.secret_key, &config.salt_key)
+        .map_err(|e| Json(format!("Encryption error: {e}")))?;
+
+    let mut tx = pool.inner().begin().await.map_err(|e| Json(format!("Transaction error: {e}")))?;
+
+    let inserted = sqlx::query!(
+        "INSERT INTO exchange_accounts (id, user_id, exchange, label, api_key, encrypted_secret, connector)
+         SELECT $1, id, $3, $4, $5, $6, $7 FROM users WHERE id = $2",
+        account_uid,
+        user_uid,
+        account_data.exchange,
+        account_data.label,
+        account_data.api_key,
+        encrypted_secret,
+        connector
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| Json(format!("Database error: {:?}", e)))?
+    .rows_affected();
+
+    if inserted == 0 {
+        return Err(Json("User not found".to_string()));
+    }
+
+    let after = audit::snapshot(&mut tx, "exchange_accounts", account_uid).await.map_err(audit_error)?;
+    audit::record(&mut tx, &actor, "account.create", "account", Some(account_uid), None, after)
+        .await
+        .map_err(audit_error)?;
+
+    tx.commit().await.map_err(|e| Json(format!("Commit error: {e}")))?;
+
+    Ok(Json(CreateAccountResponse { account_uid }))
+}
+
+#[derive(FromRow)]
+struct AccountRow {
+    account_uid: Uuid,
+    user_uid: Uuid,
+    exchange: String,
+    label: Option<String>,
+    connector: String,
+    created_at: NaiveDateTime,
+    cursor_key: String,
+}
+
+fn account_sort_column(sort: AccountSortField) -> SortColumn {
+    match sort {
+        AccountSortField::CreatedAt => SortColumn { expr: "created_at", sql_type: "timestamp" },
+        AccountSortField::Exchange => SortColumn { expr: "exchange", sql_type: "text" },
+        AccountSortField::Label => SortColumn { expr: "COALESCE(label, '')", sql_type: "text" },
+    }
+}
+
+/// **GET /api/user/<user_uid>/accounts** — Список биржевых аккаунтов пользователя (постранично)
+#[openapi(tag = "Account Management")]
+#[get("/user/<user_uid>/accounts?<query..>")]
+pub async fn get_accounts(
+    pool: &State<PgPool>,
+    caller: Caller<UsersRead>,
+    user_uid: Uuid,
+    query: AccountsQuery,
+) -> Result<Json<Page<ExchangeAccount>>, Json<String>> {
+    caller.ensure_user(user_uid)?;
+
+    let limit = clamp_limit(query.limit);
+    let sort = account_sort_column(query.sort.unwrap_or_default());
+
+    let mut qb = QueryBuilder::<Postgres>::new(format!(
+        "SELECT id AS account_uid, user_id AS user_uid, exchange, label, connector, created_at, {}::text AS cursor_key
+         FROM exchange_accounts WHERE user_id = ",
+        sort.expr
+    ));
+    qb.push_bind(user_uid);
+    if let Some(exchange) = &query.exchange {
+        qb.push(" AND exchange = ").push_bind(exchange);
+    }
+    if let Some(name) = &query.name {
+        qb.push(" AND label ILIKE ").push_bind(format!("%{name}%"));
+    }
+    push_created_range(&mut qb, "created_at", query.created_from.as_deref(), query.created_to.as_deref())
+        .map_err(Json)?;
+    push_page(&mut qb, &sort, "id", query.order.unwrap_or_default(), query.cursor.as_deref(), limit)
+        .map_err(Json)?;
+
+    let rows: Vec<AccountRow> = qb
+        .build_query_as()
+        .fetch_all(pool.inner())
+        .await
+        .map_err(|_| Json("Failed to fetch accounts".to_string()))?;
+
+    let (rows, page) = finish_page(rows, limit, |row| Cursor { key: row.cursor_key.clone(), id: row.account_uid });
+
+    let items = rows
+        .into_iter()
+        .map(|row| ExchangeAccount {
+            account_uid: row.account_uid,
+            user_uid: row.user_uid,
+            exchange: row.exchange,
+            label: row.label,
+            connector: row.connector,
+            created_at: row.created_at,
+        })
+        .collect();
+
+    Ok(Json(Page { items, page }))
+}
+
+/// **GET /api/account/<account_uid>** — Получение информации об аккаунте
+#[openapi(tag = "Account Management")]
+#[get("/account/<account_uid>")]
+pub async fn get_account(
+    pool: &State<PgPool>,
+    caller: Caller<UsersRead>,
+    account_uid: Uuid,
+) -> Result<Json<ExchangeAccount>, Json<String>> {
+    let account = sqlx::query_as!(
+        ExchangeAccount,
+        "SELECT id AS account_uid, user_id AS user_uid, exchange, label, connector, created_at
+         FROM exchange_accounts WHERE id = $1 AND ($2::uuid IS NULL OR user_id = $2)",
+        account_uid,
+        caller.owner_filter()
+    )
+    .fetch_one(pool.inner())
+    .await
+    .map_err(|_| Json("Account not found".to_string()))?;
+
+    Ok(Json(account))
+}
+
+/// **PUT /api/account/<account_uid>** — Обновление ключей и названия аккаунта
+#[openapi(tag = "Account Management")]
+#[put("/account/<account_uid>", format = "json", data = "<update_data>")]
+pub async fn update_account(
+    pool: &State<PgPool>,
+    config: &State<Config>,
+    caller: Caller<UsersWrite>,
+    actor: AuditActor,
+    account_uid: Uuid,
+    update_data: Json<UpdateAccountRequest>,
+) -> Result<Json<String>, Json<String>> {
+    let encrypted_secret = if let Some(secret) = &update_data.secret_key {
+        Some(encrypt_secret(secret, &config.salt_key).map_err(|e| Json(format!("Encryption error: {e}")))?)
+    } else {
+        None
+    };
+
+    let mut tx = pool.inner().begin().await.map_err(|e| Json(format!("Transaction error: {e}")))?;
+
+    let before = audit::snapshot(&mut tx, "exchange_accounts", account_uid).await.map_err(audit_error)?;
+
+    if let Some(connector) = &update_data.connector {
+        let exchange = sqlx::query_scalar!(
+            "SELECT exchange FROM exchange_accounts WHERE id = $1 AND ($2::uuid IS NULL OR user_id = $2)",
+            account_uid,
+            caller.owner_filter()
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| Json(format!("Database error: {:?}", e)))?;
+        if let Some(exchange) = exchange {
+            validate_connector(&exchange, connector).map_err(Json)?;
+
+            // Смена коннектора не должна оставить стратегии и подписки аккаунта с рынком, которым он не торгует
+            let market_types = sqlx::query_scalar!(
+                r#"SELECT DISTINCT COALESCE(config->>'marketType', 'spot') AS "market_type!"
+                   FROM strategies
+                   WHERE account_id = $1
+                      OR id IN (SELECT strategy_id FROM strategy_subscriptions WHERE account_id = $1 AND active)"#,
+                account_uid
+            )
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| Json(format!("Database error: {:?}", e)))?;
+            let supported = capabilities(&exchange, connector).market_types;
+            if let Some(unsupported) = market_types
+                .iter()
+                .filter_map(|raw| MarketType::parse(raw))
+                .find(|market_type| !supported.contains(market_type))
+            {
+                return Err(Json(format!(
+                    "Connector {connector} does not support {} trading used by this account's strategies",
+                    unsupported.as_str()
+                )));
+            }
+        }
+    }
+
+    let updated = sqlx::query!(
+        "UPDATE exchange_accounts
+         SET label = $1,
+             api_key = $2,
+             encrypted_secret = COALESCE($3, encrypted_secret),
+             connector = COALESCE($6, connector)
+         WHERE id = $4 AND ($5::uuid IS NULL OR user_id = $5)",
+        update_data.label,
+        update_data.api_key,
+        encrypted_secret,
+        account_uid,
+        caller.owner_filter(),
+        update_data.connector
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| Json(format!("Database error: {:?}", e)))?
+    .rows_affected();
+
+    if updated == 0 {
+        return Err(Json("Account not found".to_string()));
+    }
+
+    let after = audit::snapshot(&mut tx, "exchange_accounts", account_uid).await.map_err(audit_error)?;
+    audit::record(&mut tx, &actor, "account.update", "account", Some(account_uid), before, after)
+        .await
+        .map_err(audit_error)?;
+
+    tx.commit().await.map_err(|e| Json(format!("Commit error: {e}")))?;
+
+    Ok(Json("Account updated successfully".to_string()))
+}
+
+/// **DELETE /api/account/<account_uid>** — Удаление аккаунта и всех его стратегий
+#[openapi(tag = "Account Management")]
+#[delete("/account/<account_uid>")]
+pub async fn delete_account(
+    pool: &State<PgPool>,
+    caller: Caller<UsersDelete>,
+    actor: AuditActor,
+    account_uid: Uuid,
+) -> Result<Json<String>, Json<String>> {
+    let mut tx = pool.inner().begin().await.map_err(|e| Json(format!("Transaction error: {e}")))?;
+
+    let before = audit::snapshot(&mut tx, "exchange_accounts", account_uid).await.map_err(audit_error)?;
+
+    let deleted = sqlx::query!(
+        "DELETE FROM exchange_accounts WHERE id = $1 AND ($2::uuid IS NULL OR user_id = $2)",
+        account_uid,
+        caller.owner_filter()
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| Json(format!("Database error: {:?}", e)))?
+    .rows_affected();
+
+    if deleted == 0 {
+        return Err(Json("Account not found".to_string()));
+    }
+
+    audit::record(&mut tx, &actor, "account.delete", "account", Some(account_uid), before, None)
+        .await
+        .map_err(audit_error)?;
+
+    tx.commit().await.map_err(|e| Json(format!("Commit error: {e}")))?;
+
+    Ok(Json("Account deleted successfully".to_string()))
+}
+
+/// **POST /api/account/<account_uid>/validate** — Проверка, что ключи расшифровываются и принимаются биржей
+///
+/// Если биржа недоступна, ответ — ошибка шлюза (502/503), а не `valid: false`.
+#[openapi(tag = "Account Management")]
+#[post("/account/<account_uid>/validate")]
+pub async fn validate_account(
+    pool: &State<PgPool>,
+    config: &State<Config>,
+    gateway: &State<SharedGateway>,
+    caller: Caller<UsersWrite>,
+    actor: AuditActor,
+    account_uid: Uuid,
+) -> Result<Json<ValidateAccountResponse>, ApiError> {
+    let account = sqlx::query!(
+        "SELECT id, user_id, exchange, connector, api_key, encrypted_secret
+         FROM exchange_accounts WHERE id = $1 AND ($2::uuid IS NULL OR user_id = $2)",
+        account_uid,
+        caller.owner_filter()
+    )
+    .fetch_optional(pool.inner())
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| not_found("Account not found"))?;
+
+    let access = CredentialAccess {
+        user_uid: account.user_id,
+        account_uid: account.id,
+        purpose: CredentialPurpose::Validation,
+        strategy_uid: None,
+        signal_id: None,
+    };
+    let secret = match decrypt_credentials(pool.inner(), &actor, access, &account.encrypted_secret, &config.salt_key).await {
+        Ok(secret) => secret,
+        Err(e) => return Ok(Json(ValidateAccountResponse { valid: false, message: Some(e) })),
+    };
+
+    let credentials = ExchangeCredentials {
+        account_uid: account.id,
+        exchange: &account.exchange,
+        connector: &account.connector,
+        api_key: &account.api_key,
+        secret: &secret,
+    };
+    // Отказ биржи — это ответ «ключи невалидны», а недоступность шлюза — ошибка
+    let response = match gateway.balance(&credentials).await {
+        Ok(_) => ValidateAccountResponse { valid: true, message: None },
+        Err(GatewayError::Rejected(message)) => ValidateAccountResponse { valid: false, message: Some(message) },
+        Err(e) => return Err(gateway_error(e)),
+    };
+
+    Ok(Json(response))
+}
+
+#[derive(FromRow)]
+struct CredentialAccessRow {
+    id: Uuid,
+    accessed_at: NaiveDateTime,
+    account_id: Uuid,
+    purpose: String,
+    success: bool,
+    caller_kind: String,
+    caller_id: Option<String>,
+    caller_name: Option<String>,
+    strategy_id: Option<Uuid>,
+    signal_id: Option<String>,
+    request_id: String,
+    ip: Option<String>,
+    cursor_key: String,
+}
+
+/// **GET /api/user/<user_uid>/credential-access** — Когда и зачем расшифровывались ключи пользователя (новые сверху)
+#[openapi(tag = "Account Management")]
+#[get("/user/<user_uid>/credential-access?<query..>")]
+pub async fn get_credential_access(
+    pool: &State<PgPool>,
+    caller: Caller<UsersRead>,
+    user_uid: Uuid,
+    query: CredentialAccessQuery,
+) -> Result<Json<Page<CredentialAccessEvent>>, Json<String>> {
+    caller.ensure_user(user_uid)?;
+
+    let limit = clamp_limit(query.limit);
+    let sort = SortColumn { expr: "accessed_at", sql_type: "timestamp" };
+
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "SELECT id, accessed_at, account_id, purpose, success, caller_kind, caller_id, caller_name,
+                strategy_id, signal_id, request_id, ip, accessed_at::text AS cursor_key
+         FROM credential_access WHERE user_id = ",
+    );
+    qb.push_bind(user_uid);
+    if let Some(account_uid) = query.account_uid {
+        qb.push(" AND account_id = ").push_bind(account_uid);
+    }
+    if let Some(purpose) = &query.purpose {
+        qb.push(" AND purpose = ").push_bind(purpose);
+    }
+    push_created_range(&mut qb, "accessed_at", query.from.as_deref(), query.to.as_deref()).map_err(Json)?;
+    push_page(&mut qb, &sort, "id", SortOrder::Desc, query.cursor.as_deref(), limit).map_err(Json)?;
+
+    let rows: Vec<CredentialAccessRow> = qb
+        .build_query_as()
+        .fetch_all(pool.inner())
+        .await
+        .map_err(|_| Json("Failed to fetch credential access history".to_string()))?;
+
+    let (rows, page) = finish_page(rows, limit, |row| Cursor { key: row.cursor_key.clone(), id: row.id });
+
+    let items = rows
+        .into_iter()
+        .map(|row| CredentialAccessEvent {
+            access_uid: row.id,
+            accessed_at: row.accessed_at,
+            account_uid: row.account_id,
+            purpose: row.purpose,
+            success: row.success,
+            caller_kind: row.caller_kind,
+            caller_id: row.caller_id,
+            caller_name: row.caller_name,
+            strategy_uid: row.strategy_id,
+            signal_id: row.signal_id,
+            request_id: row.request_id,
+            ip: row.ip,
+        })
+        .collect();
+
+    Ok(Json(Page { items, page }))
+}
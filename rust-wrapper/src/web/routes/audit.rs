@@ -0,0 +1,92 @@
+use chrono::NaiveDateTime;
+use rocket::{get, serde::json::Json, State};
+use rocket_okapi::openapi;
+use serde_json::Value;
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::types::{AuditEvent, AuditQuery, Page, SortOrder};
+use crate::web::guards::scopes::AuditRead;
+use crate::web::guards::TokenGuard;
+use crate::web::pagination::{clamp_limit, finish_page, push_created_range, push_page, Cursor, SortColumn};
+
+#[derive(FromRow)]
+struct AuditRow {
+    id: Uuid,
+    occurred_at: NaiveDateTime,
+    actor_kind: String,
+    actor_id: Option<String>,
+    actor_name: Option<String>,
+    action: String,
+    target_type: String,
+    target_id: Option<Uuid>,
+    before: Option<Value>,
+    after: Option<Value>,
+    request_id: String,
+    ip: Option<String>,
+    cursor_key: String,
+}
+
+/// **GET /api/audit** — Журнал действий (по умолчанию новые сверху)
+#[openapi(tag = "Audit")]
+#[get("/audit?<query..>")]
+pub async fn get_audit_events(
+    pool: &State<PgPool>,
+    _auth: TokenGuard<AuditRead>,
+    query: AuditQuery,
+) -> Result<Json<Page<AuditEvent>>, Json<String>> {
+    let limit = clamp_limit(query.limit);
+    let sort = SortColumn { expr: "occurred_at", sql_type: "timestamp" };
+
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "SELECT id, occurred_at, actor_kind, actor_id, actor_name, action, target_type, target_id,
+                before, after, request_id, ip, occurred_at::text AS cursor_key
+         FROM audit_events WHERE TRUE",
+    );
+    if let Some(actor_id) = &query.actor_id {
+        qb.push(" AND actor_id = ").push_bind(actor_id);
+    }
+    if let Some(action) = &query.action {
+        qb.push(" AND action = ").push_bind(action);
+    }
+    if let Some(target_type) = &query.target_type {
+        qb.push(" AND target_type = ").push_bind(target_type);
+    }
+    if let Some(target_id) = query.target_id {
+        qb.push(" AND target_id = ").push_bind(target_id);
+    }
+    if let Some(request_id) = &query.request_id {
+        qb.push(" AND request_id = ").push_bind(request_id);
+    }
+    push_created_range(&mut qb, "occurred_at", query.from.as_deref(), query.to.as_deref()).map_err(Json)?;
+    push_page(&mut qb, &sort, "id", query.order.unwrap_or(SortOrder::Desc), query.cursor.as_deref(), limit)
+        .map_err(Json)?;
+
+    let rows: Vec<AuditRow> = qb
+        .build_query_as()
+        .fetch_all(pool.inner())
+        .await
+        .map_err(|_| Json("Failed to fetch audit events".to_string()))?;
+
+    let (rows, page) = finish_page(rows, limit, |row| Cursor { key: row.cursor_key.clone(), id: row.id });
+
+    let items = rows
+        .into_iter()
+        .map(|row| AuditEvent {
+            event_uid: row.id,
+            occurred_at: row.occurred_at,
+            actor_kind: row.actor_kind,
+            actor_id: row.actor_id,
+            actor_name: row.actor_name,
+            action: row.action,
+            target_type: row.target_type,
+            target_id: row.target_id,
+            before: row.before,
+            after: row.after,
+            request_id: row.request_id,
+            ip: row.ip,
+        })
+        .collect();
+
+    Ok(Json(Page { items, page }))
+}
@@ -0,0 +1,33 @@
+use rocket::{get, serde::json::Json};
+use rocket_okapi::openapi;
+
+use crate::types::CallerPermissions;
+use crate::web::guards::Identity;
+
+/// **GET /api/auth/permissions** — Права текущего вызывающего
+#[openapi(tag = "Access Control")]
+#[get("/auth/permissions")]
+pub async fn get_permissions(identity: Identity) -> Json<CallerPermissions> {
+    let permissions = identity.permissions();
+
+    Json(match identity {
+        Identity::Token(principal) => CallerPermissions {
+            kind: "token".to_string(),
+            token_uid: principal.token_uid,
+            token_name: Some(principal.name),
+            role: Some(principal.role),
+            user_uid: None,
+            permissions,
+            own_data_only: false,
+        },
+        Identity::TelegramUser { user_uid } => CallerPermissions {
+            kind: "telegram".to_string(),
+            token_uid: None,
+            token_name: None,
+            role: None,
+            user_uid: Some(user_uid),
+            permissions,
+            own_data_only: true,
+        },
+    })
+}
@@ -1,48 +1,89 @@
+use rocket::http::Status;
 use rocket::{post, serde::json::Json, State};
 use rocket_okapi::openapi;
-use reqwest::Client;
-use serde_json::Value;
 use sqlx::PgPool;
-use crate::crypto::decrypt_secret;
+use crate::audit::{decrypt_credentials, AuditActor, CredentialAccess, CredentialPurpose};
+use crate::gateway::{Balance, ExchangeCredentials, SharedGateway};
 use crate::types::BalanceRequest;
-use crate::web::guards::AdminGuard;
+use crate::web::guards::scopes::BalanceRead;
+use crate::web::gateway_error;
+use crate::web::guards::Caller;
+use crate::web::{db_error, internal, not_found, ApiError};
 use crate::config::Config;
 
 /// **POST /api/balance** 
+///
+/// Ошибки шлюза отдаются со статусом: 503 — биржа временно отключена предохранителем или перегружена
+/// (запрос к ней не отправлялся), 502 — биржа недоступна, 422 — биржа отказала.
 #[openapi(tag = "Balance Management")]
 #[post("/balance", format = "json", data = "<balance_req>")]
 pub async fn get_balance_route(
     pool: &State<PgPool>,
     config: &State<Config>,
-    _admin: AdminGuard,
+    gateway: &State<SharedGateway>,
+    caller: Caller<BalanceRead>,
+    actor: AuditActor,
     balance_req: Json<BalanceRequest>,
-) -> Result<Json<Value>, Json<String>> {
-    let user = sqlx::query!(
-        "SELECT api_key, encrypted_secret, exchange FROM users WHERE user_telegram_id = $1",
-        balance_req.user_telegram_id
+) -> Result<Json<Balance>, ApiError> {
+    let owner = caller.owner_filter();
+    if owner.is_none() && balance_req.account_uid.is_none() && balance_req.user_telegram_id.is_none() {
+        return Err((Status::BadRequest, Json("Either accountUid or userTelegramId is required".to_string())));
+    }
+
+    // Аккаунт ищется по id, либо по Telegram id (+ бирже, если аккаунтов несколько).
+    // Пользователь mini-app видит только свои аккаунты.
+    let accounts = sqlx::query!(
+        "SELECT exchange_accounts.id, exchange_accounts.user_id, exchange_accounts.api_key,
+                exchange_accounts.encrypted_secret, exchange_accounts.exchange, exchange_accounts.connector
+         FROM exchange_accounts
+         JOIN users ON exchange_accounts.user_id = users.id
+         WHERE ($1::uuid IS NULL OR exchange_accounts.id = $1)
+           AND ($2::bigint IS NULL OR users.user_telegram_id = $2)
+           AND ($3::text IS NULL OR exchange_accounts.exchange = $3)
+           AND ($4::uuid IS NULL OR users.id = $4)
+         ORDER BY exchange_accounts.created_at
+         LIMIT 2",
+        balance_req.account_uid,
+        balance_req.user_telegram_id,
+        balance_req.exchange,
+        owner
     )
-    .fetch_one(pool.inner())
+    .fetch_all(pool.inner())
     .await
-    .map_err(|_| Json("User not found".to_string()))?;
+    .map_err(db_error)?;
 
-    let real_secret = match decrypt_secret(&user.encrypted_secret, &config.salt_key) {
-        Ok(sec) => sec,
-        Err(e) => return Err(Json(format!("Decryption error: {e}"))),
+    let account = match accounts.as_slice() {
+        [account] => account,
+        [] => return Err(not_found("Account not found")),
+        _ => {
+            return Err((
+                Status::BadRequest,
+                Json("User has several accounts, specify accountUid or exchange".to_string()),
+            ))
+        }
     };
 
-    let url = "http://localhost:3000/get_balance";
-    let client = Client::new();
-    let body = serde_json::json!({
-        "exchange": user.exchange,
-        "apiKey": user.api_key,
-        "secret": real_secret
-    });
-
-    let resp = client.post(url).json(&body).send().await
-        .map_err(|e| Json(format!("Request error: {:?}", e)))?;
-
-    let json_value: Value = resp.json().await
-        .map_err(|e| Json(format!("JSON parse error: {:?}", e)))?;
+    let access = CredentialAccess {
+        user_uid: account.user_id,
+        account_uid: account.id,
+        purpose: CredentialPurpose::Balance,
+        strategy_uid: None,
+        signal_id: None,
+    };
+    let real_secret = decrypt_credentials(pool.inner(), &actor, access, &account.encrypted_secret, &config.salt_key)
+        .await
+        .map_err(|e| internal(Json(e)))?;
 
-    Ok(Json(json_value))
+    let credentials = ExchangeCredentials {
+        account_uid: account.id,
+        exchange: &account.exchange,
+        connector: &account.connector,
+        api_key: &account.api_key,
+        secret: &real_secret,
+    };
+    gateway
+        .balance(&credentials)
+        .await
+        .map(Json)
+        .map_err(gateway_error)
 }
@@ -0,0 +1,69 @@
+use rocket::http::Status;
+use rocket::{get, serde::json::Json, State};
+use rocket_okapi::openapi;
+use std::sync::Arc;
+
+use crate::gateway::{capabilities, CircuitBreakers, ExchangeCapabilities, Market, GATEWAY_CONNECTOR};
+use crate::market_data::{Quote, QuoteBook};
+use crate::markets::MarketCache;
+use crate::types::BreakerStatus;
+use crate::web::gateway_error;
+use crate::web::guards::scopes::{AuditRead, BalanceRead};
+use crate::web::guards::{Caller, TokenGuard};
+
+/// **GET /api/markets/{exchange}** — Торгуемые пары биржи и их ограничения (из кэша)
+#[openapi(tag = "Markets")]
+#[get("/markets/<exchange>")]
+pub async fn get_markets(
+    markets: &State<Arc<MarketCache>>,
+    _caller: Caller<BalanceRead>,
+    exchange: &str,
+) -> Result<Json<Vec<Market>>, (Status, Json<String>)> {
+    markets.markets(exchange).await.map(Json).map_err(gateway_error)
+}
+
+/// **GET /api/markets/{exchange}/capabilities?connector=** — Типы ордеров и флаги, которые принимает биржа
+/// для аккаунта с данным коннектором, по умолчанию `gateway`
+#[openapi(tag = "Markets")]
+#[get("/markets/<exchange>/capabilities?<connector>")]
+pub async fn get_capabilities(
+    _caller: Caller<BalanceRead>,
+    exchange: &str,
+    connector: Option<&str>,
+) -> Json<ExchangeCapabilities> {
+    Json(capabilities(exchange, connector.unwrap_or(GATEWAY_CONNECTOR)))
+}
+
+/// **GET /api/quotes** — Текущие котировки всех символов источника рыночных данных
+#[openapi(tag = "Markets")]
+#[get("/quotes")]
+pub async fn get_quotes(quotes: &State<Arc<QuoteBook>>, _caller: Caller<BalanceRead>) -> Json<Vec<Quote>> {
+    Json(quotes.all())
+}
+
+/// **GET /api/quotes/{symbol}?exchange=** — Спотовая котировка символа (`BTCUSDT` или `BTC-USDT`),
+/// по умолчанию на Binance
+#[openapi(tag = "Markets")]
+#[get("/quotes/<symbol>?<exchange>")]
+pub async fn get_quote(
+    quotes: &State<Arc<QuoteBook>>,
+    _caller: Caller<BalanceRead>,
+    symbol: &str,
+    exchange: Option<&str>,
+) -> Result<Json<Quote>, (Status, Json<String>)> {
+    let exchange = exchange.unwrap_or("binance");
+    quotes
+        .get(exchange, symbol)
+        .map(Json)
+        .ok_or_else(|| (Status::NotFound, Json(format!("No quote for {symbol} on {exchange}"))))
+}
+
+/// **GET /api/gateway/breakers** — Предохранители бирж по коннекторам: состояние, ошибки, занятые слоты
+#[openapi(tag = "Markets")]
+#[get("/gateway/breakers")]
+pub async fn get_breakers(
+    breakers: &State<Arc<CircuitBreakers>>,
+    _auth: TokenGuard<AuditRead>,
+) -> Json<Vec<BreakerStatus>> {
+    Json(breakers.snapshot())
+}
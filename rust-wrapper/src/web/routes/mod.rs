@@ -1,6 +1,16 @@
+pub mod accounts;
+pub mod audit;
+pub mod auth;
 pub mod balance;
+pub mod markets;
 pub mod nats;
+pub mod orders;
+pub mod positions;
+pub mod reconciliation;
 pub mod strategies;
+pub mod subscriptions;
+pub mod tokens;
+pub mod transfer;
 pub mod users;
 pub mod webhook;
 
@@ -12,13 +22,40 @@ pub fn get_routes() -> Vec<Route> {
         // Balance
         balance::get_balance_route,
 
+        // Orders
+        orders::get_order,
+
+        // Positions
+        positions::get_positions,
+        positions::rebuild_positions,
+
+        // Reconciliation
+        reconciliation::get_discrepancies,
+
+        // Markets
+        markets::get_markets,
+        markets::get_capabilities,
+        markets::get_quotes,
+        markets::get_quote,
+        markets::get_breakers,
+
         // Users 
         users::register_user,
         users::update_user,
         users::get_user,
+        users::get_me,
         users::get_all_users,
         users::delete_user,
 
+        // Exchange accounts
+        accounts::create_account,
+        accounts::get_accounts,
+        accounts::get_account,
+        accounts::update_account,
+        accounts::delete_account,
+        accounts::validate_account,
+        accounts::get_credential_access,
+
         // NATS
         nats::publish_nats_event,
 
@@ -31,7 +68,37 @@ pub fn get_routes() -> Vec<Route> {
         strategies::get_strategy,
         strategies::get_strategies,
         strategies::update_strategy, 
-        strategies::toggle_strategies
+        strategies::toggle_strategies,
+        strategies::update_strategy_config,
+        strategies::get_strategy_config_versions,
+        strategies::publish_strategy,
+        strategies::get_catalogue,
+        strategies::rotate_webhook,
+        strategies::get_strategy_stats,
+        strategies::get_strategy_signals,
+
+        // Copy trading
+        subscriptions::create_subscription,
+        subscriptions::get_subscriptions,
+        subscriptions::update_subscription,
+        subscriptions::delete_subscription,
+
+        // Access control
+        auth::get_permissions,
+
+        // Audit
+        audit::get_audit_events,
+
+        // API tokens
+        tokens::issue_token,
+        tokens::get_tokens,
+        tokens::revoke_token,
+
+        // Import / export
+        transfer::export_json,
+        transfer::export_csv,
+        transfer::import_json,
+        transfer::import_csv
     ]
 }
 
@@ -5,12 +5,16 @@ use serde_json::Value;
 use tokio::sync::Mutex;
 use std::sync::Arc;
 
+use crate::web::guards::scopes::SignalsReplay;
+use crate::web::guards::TokenGuard;
+
 /// **POST /api/nats/event**  
 /// Отправляет сообщение в NATS-топик `order-notifications`
 #[openapi(tag = "NATS Integration")]
 #[post("/nats/event", format = "json", data = "<event>")]
 pub async fn publish_nats_event(
     nats_client: &State<Arc<Mutex<Client>>>,
+    _auth: TokenGuard<SignalsReplay>,
     event: Json<Value>,
 ) -> Result<Json<String>, Json<String>> {
     let topic = "order-notifications".to_string(); // Явно указываем тип String
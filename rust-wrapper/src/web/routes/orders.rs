@@ -0,0 +1,61 @@
+use rocket::{get, serde::json::Json, State};
+use rocket_okapi::openapi;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::audit::{decrypt_credentials, AuditActor, CredentialAccess, CredentialPurpose};
+use crate::config::Config;
+use crate::gateway::{ExchangeCredentials, Order, SharedGateway};
+use crate::types::OrderStatusQuery;
+use crate::web::guards::scopes::BalanceRead;
+use crate::web::gateway_error;
+use crate::web::guards::Caller;
+use crate::web::{db_error, internal, not_found, ApiError};
+
+/// **GET /api/account/{accountUid}/order?order_id=&symbol=** — Состояние ордера на бирже
+#[openapi(tag = "Orders")]
+#[get("/account/<account_uid>/order?<query..>")]
+pub async fn get_order(
+    pool: &State<PgPool>,
+    config: &State<Config>,
+    gateway: &State<SharedGateway>,
+    caller: Caller<BalanceRead>,
+    actor: AuditActor,
+    account_uid: Uuid,
+    query: OrderStatusQuery,
+) -> Result<Json<Order>, ApiError> {
+    let account = sqlx::query!(
+        "SELECT id, user_id, exchange, connector, api_key, encrypted_secret
+         FROM exchange_accounts WHERE id = $1 AND ($2::uuid IS NULL OR user_id = $2)",
+        account_uid,
+        caller.owner_filter()
+    )
+    .fetch_optional(pool.inner())
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| not_found("Account not found"))?;
+
+    let access = CredentialAccess {
+        user_uid: account.user_id,
+        account_uid: account.id,
+        purpose: CredentialPurpose::Order,
+        strategy_uid: None,
+        signal_id: None,
+    };
+    let secret = decrypt_credentials(pool.inner(), &actor, access, &account.encrypted_secret, &config.salt_key)
+        .await
+        .map_err(|e| internal(Json(e)))?;
+
+    let credentials = ExchangeCredentials {
+        account_uid: account.id,
+        exchange: &account.exchange,
+        connector: &account.connector,
+        api_key: &account.api_key,
+        secret: &secret,
+    };
+    gateway
+        .order_status(&credentials, &query.symbol, &query.order_id)
+        .await
+        .map(Json)
+        .map_err(gateway_error)
+}
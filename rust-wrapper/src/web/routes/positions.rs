@@ -0,0 +1,167 @@
+use rocket::http::Status;
+use rocket::{get, post, serde::json::Json, State};
+use rocket_okapi::openapi;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::audit::{self, AuditActor};
+use crate::gateway::{symbol_key, MarketType};
+use crate::positions::rebuild;
+use crate::types::{Page, Position, PositionsQuery, RebuildPositionsRequest, RebuildPositionsResponse};
+use crate::web::{audit_error, db_error, internal, not_found, ApiError};
+use crate::web::pagination::{clamp_limit, finish_page, Cursor};
+use crate::web::guards::scopes::{BalanceRead, PositionsRebuild};
+use crate::web::guards::{Caller, TokenGuard};
+
+/// **GET /api/positions** — Позиции по исполнениям: объём, средняя цена входа, реализованный PnL
+///
+/// По умолчанию только открытые; пользователь mini-app видит позиции своих аккаунтов.
+/// Постранично, по аккаунту, символу, типу рынка и стратегии.
+#[openapi(tag = "Positions")]
+#[get("/positions?<query..>")]
+pub async fn get_positions(
+    pool: &State<PgPool>,
+    caller: Caller<BalanceRead>,
+    query: PositionsQuery,
+) -> Result<Json<Page<Position>>, ApiError> {
+    let limit = clamp_limit(query.limit);
+    let after = query.cursor.as_deref().map(PositionKey::decode).transpose().map_err(|e| (Status::BadRequest, Json(e)))?;
+
+    // Позиция без стратегии идёт первой: NULL заменяется наименьшим uuid
+    let rows = sqlx::query!(
+        "SELECT positions.id, positions.account_id, positions.strategy_id, positions.symbol, positions.symbol_key,
+                positions.market_type, positions.quantity,
+                positions.avg_entry_price, positions.realized_pnl, positions.fees, positions.fill_count,
+                positions.last_fill_at, positions.updated_at
+         FROM positions
+         JOIN exchange_accounts ON positions.account_id = exchange_accounts.id
+         WHERE ($1::uuid IS NULL OR exchange_accounts.user_id = $1)
+           AND ($2::uuid IS NULL OR positions.account_id = $2)
+           AND ($3::uuid IS NULL OR positions.strategy_id = $3)
+           AND ($4::text IS NULL OR positions.symbol_key = $4)
+           AND ($5 OR positions.quantity <> 0)
+           AND ($6::uuid IS NULL OR (positions.account_id, positions.symbol_key, positions.market_type,
+                                     COALESCE(positions.strategy_id, '00000000-0000-0000-0000-000000000000'))
+                                    > ($6, $7, $8, $9))
+         ORDER BY positions.account_id, positions.symbol_key, positions.market_type,
+                  COALESCE(positions.strategy_id, '00000000-0000-0000-0000-000000000000')
+         LIMIT $10",
+        caller.owner_filter(),
+        query.account_uid,
+        query.strategy_uid,
+        query.symbol.as_deref().map(symbol_key),
+        query.include_closed.unwrap_or(false),
+        after.as_ref().map(|key| key.account_uid),
+        after.as_ref().map(|key| key.symbol_key.as_str()),
+        after.as_ref().map(|key| key.market_type.as_str()),
+        after.as_ref().map(|key| key.strategy_uid.unwrap_or(Uuid::nil())),
+        limit + 1
+    )
+    .fetch_all(pool.inner())
+    .await
+    .map_err(db_error)?;
+
+    let (rows, page) = finish_page(rows, limit, |row| {
+        PositionKey {
+            account_uid: row.account_id,
+            symbol_key: row.symbol_key.clone(),
+            market_type: row.market_type.clone(),
+            strategy_uid: row.strategy_id,
+        }
+        .cursor()
+    });
+
+    let items = rows
+        .into_iter()
+        .map(|p| {
+            let market_type = MarketType::parse(&p.market_type)
+                .ok_or_else(|| internal(Json(format!("Position {} has unknown market type {}", p.id, p.market_type))))?;
+            Ok(Position {
+                position_uid: p.id,
+                account_uid: p.account_id,
+                strategy_uid: p.strategy_id,
+                symbol: p.symbol,
+                market_type,
+                quantity: p.quantity,
+                avg_entry_price: p.avg_entry_price,
+                realized_pnl: p.realized_pnl,
+                fees: p.fees,
+                fill_count: p.fill_count,
+                last_fill_at: p.last_fill_at,
+                updated_at: p.updated_at,
+            })
+        })
+        .collect::<Result<Vec<_>, ApiError>>()?;
+
+    Ok(Json(Page { items, page }))
+}
+
+/// Ключ позиции в порядке списка — курсор `GET /api/positions`
+struct PositionKey {
+    account_uid: Uuid,
+    symbol_key: String,
+    market_type: String,
+    strategy_uid: Option<Uuid>,
+}
+
+impl PositionKey {
+    fn cursor(&self) -> Cursor {
+        let strategy = self.strategy_uid.map(|uid| uid.to_string()).unwrap_or_default();
+        Cursor { key: format!("{}|{}|{strategy}", self.symbol_key, self.market_type), id: self.account_uid }
+    }
+
+    fn decode(raw: &str) -> Result<Self, String> {
+        let cursor = Cursor::decode(raw)?;
+        let mut parts = cursor.key.splitn(3, '|');
+        let (Some(symbol_key), Some(market_type), Some(strategy)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err("Invalid cursor".to_string());
+        };
+        let strategy_uid = match strategy {
+            "" => None,
+            uid => Some(Uuid::parse_str(uid).map_err(|_| "Invalid cursor".to_string())?),
+        };
+
+        Ok(PositionKey {
+            account_uid: cursor.id,
+            symbol_key: symbol_key.to_string(),
+            market_type: market_type.to_string(),
+            strategy_uid,
+        })
+    }
+}
+
+/// **POST /api/positions/rebuild** — Перестроить журнал позиций из истории исполнений
+///
+/// Нужен, если журнал разошёлся с `fills` (например, после ручной правки или сбоя). Позиции
+/// пересчитываются в порядке времени исполнений, всего журнала или одного аккаунта.
+#[openapi(tag = "Positions")]
+#[post("/positions/rebuild", format = "json", data = "<rebuild_req>")]
+pub async fn rebuild_positions(
+    pool: &State<PgPool>,
+    _auth: TokenGuard<PositionsRebuild>,
+    actor: AuditActor,
+    rebuild_req: Json<RebuildPositionsRequest>,
+) -> Result<Json<RebuildPositionsResponse>, ApiError> {
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    if let Some(account_uid) = rebuild_req.account_uid {
+        let exists = sqlx::query_scalar!("SELECT EXISTS(SELECT 1 FROM exchange_accounts WHERE id = $1)", account_uid)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(db_error)?
+            .unwrap_or(false);
+        if !exists {
+            return Err(not_found("Account not found"));
+        }
+    }
+
+    let summary = rebuild(&mut tx, rebuild_req.account_uid).await.map_err(db_error)?;
+    let after = json!({ "fills": summary.fills, "positions": summary.positions });
+    audit::record(&mut tx, &actor, "positions.rebuild", "exchange_account", rebuild_req.account_uid, None, Some(after))
+        .await
+        .map_err(|e| internal(audit_error(e)))?;
+    tx.commit().await.map_err(|e| internal(Json(format!("Commit error: {e}"))))?;
+
+    Ok(Json(RebuildPositionsResponse { fills: summary.fills, positions: summary.positions }))
+}
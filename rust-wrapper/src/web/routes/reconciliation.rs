@@ -0,0 +1,79 @@
+use chrono::NaiveDateTime;
+use rocket::http::Status;
+use rocket::{get, serde::json::Json, State};
+use rocket_okapi::openapi;
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::types::{DiscrepanciesQuery, Discrepancy, DiscrepancySeverity, Page, SortOrder};
+use crate::web::guards::scopes::AuditRead;
+use crate::web::guards::TokenGuard;
+use crate::web::pagination::{clamp_limit, finish_page, push_page, Cursor, SortColumn};
+use crate::web::{db_error, internal, ApiError};
+
+#[derive(FromRow)]
+struct DiscrepancyRow {
+    id: Uuid,
+    run_id: Uuid,
+    account_id: Uuid,
+    asset: String,
+    expected: f64,
+    actual: f64,
+    difference_pct: f64,
+    severity: String,
+    auto_disabled: bool,
+    detected_at: NaiveDateTime,
+    cursor_key: String,
+}
+
+/// **GET /api/reconciliation/discrepancies** — Расхождения журнала позиций с балансами на биржах
+/// (новые сверху, постранично)
+#[openapi(tag = "Audit")]
+#[get("/reconciliation/discrepancies?<query..>")]
+pub async fn get_discrepancies(
+    pool: &State<PgPool>,
+    _auth: TokenGuard<AuditRead>,
+    query: DiscrepanciesQuery,
+) -> Result<Json<Page<Discrepancy>>, ApiError> {
+    let limit = clamp_limit(query.limit);
+    let sort = SortColumn { expr: "detected_at", sql_type: "timestamp" };
+
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "SELECT id, run_id, account_id, asset, expected, actual, difference_pct, severity, auto_disabled, detected_at,
+                detected_at::text AS cursor_key
+         FROM reconciliation_discrepancies WHERE TRUE",
+    );
+    if let Some(account_uid) = query.account_uid {
+        qb.push(" AND account_id = ").push_bind(account_uid);
+    }
+    if let Some(severity) = query.severity {
+        qb.push(" AND severity = ").push_bind(severity.as_str());
+    }
+    push_page(&mut qb, &sort, "id", SortOrder::Desc, query.cursor.as_deref(), limit)
+        .map_err(|e| (Status::BadRequest, Json(e)))?;
+
+    let rows: Vec<DiscrepancyRow> = qb.build_query_as().fetch_all(pool.inner()).await.map_err(db_error)?;
+    let (rows, page) = finish_page(rows, limit, |row| Cursor { key: row.cursor_key.clone(), id: row.id });
+
+    let items = rows
+        .into_iter()
+        .map(|row| {
+            let severity = DiscrepancySeverity::parse(&row.severity)
+                .ok_or_else(|| internal(Json(format!("Discrepancy {} has unknown severity {}", row.id, row.severity))))?;
+            Ok(Discrepancy {
+                discrepancy_uid: row.id,
+                run_uid: row.run_id,
+                account_uid: row.account_id,
+                asset: row.asset,
+                expected: row.expected,
+                actual: row.actual,
+                difference_pct: row.difference_pct,
+                severity,
+                auto_disabled: row.auto_disabled,
+                detected_at: row.detected_at,
+            })
+        })
+        .collect::<Result<Vec<_>, ApiError>>()?;
+
+    Ok(Json(Page { items, page }))
+}
@@ -1,132 +1,306 @@
+use rocket::http::Status;
 use rocket::{get, post, put, delete, serde::json::Json, State};
 use rocket_okapi::openapi;
-use sqlx::PgPool;
+use chrono::NaiveDateTime;
+use sqlx::types::Json as SqlJson;
+use sqlx::{FromRow, PgExecutor, PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
 
-use crate::types::{CreateStrategyRequest, CreateStrategyResponse, ToggleStrategiesRequest, Strategy, StrategiesResponse};
-use crate::web::guards::AdminGuard;
+use crate::audit::{self, AuditActor};
+use crate::crypto::generate_webhook_token;
+use crate::gateway::{capabilities, MarketType};
+use crate::types::{
+    CatalogueQuery, CatalogueStrategy, CreateStrategyRequest, CreateStrategyResponse, Page, PositionAction, RotateWebhookRequest,
+    RotateWebhookResponse, SignalRecord, SignalsQuery, SlippageDecision, StatsQuery, StrategyStats, StrategiesQuery, StrategiesResponse, Strategy, StrategyConfig,
+    StrategyConfigVersion, StrategySortField, ToggleStrategiesRequest, UpdateStrategyConfigRequest,
+    UpdateStrategyRequest,
+};
+use crate::web::{audit_error, db_error, ensure_owner, internal, not_found, ApiError};
+use crate::web::pagination::{clamp_limit, finish_page, push_created_range, push_page, Cursor, SortColumn};
+use crate::web::guards::scopes::{StrategiesDelete, StrategiesRead, StrategiesWrite};
+use crate::web::guards::Caller;
 use crate::config::Config;
+use crate::stats::{compute_stats, StatsCache};
+use crate::web::pagination::parse_datetime;
+use std::sync::Arc;
 
-/// **POST /api/strategy** — Создание стратегии
+/// Проверяет, что аккаунт торгует типом рынка из настроек стратегии
+/// (например, встроенный коннектор Binance — только спот)
+async fn ensure_market_supported(
+    conn: impl PgExecutor<'_>,
+    account_uid: Uuid,
+    user_uid: Uuid,
+    config: &StrategyConfig,
+) -> Result<(), ApiError> {
+    let account = sqlx::query!(
+        "SELECT exchange, connector FROM exchange_accounts WHERE id = $1 AND user_id = $2",
+        account_uid,
+        user_uid
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| not_found("Account not found for this user"))?;
+
+    let market_type = config.market_type.unwrap_or_default();
+    if !capabilities(&account.exchange, &account.connector).market_types.contains(&market_type) {
+        return Err((
+            Status::BadRequest,
+            Json(format!(
+                "Account on {} with {} connector does not support {} trading",
+                account.exchange,
+                account.connector,
+                market_type.as_str()
+            )),
+        ));
+    }
+    Ok(())
+}
+
+/// **POST /api/user/{userUid}/strategy** — Создание стратегии
 #[openapi(tag = "Strategy Management")]
-#[post("/strategy", format = "json", data = "<strategy_data>")]
+#[post("/user/<user_uid>/strategy", format = "json", data = "<strategy_data>")]
 pub async fn create_strategy(
     pool: &State<PgPool>,
     config: &State<Config>,
-    _admin: AdminGuard,
+    caller: Caller<StrategiesWrite>,
+    actor: AuditActor,
+    user_uid: Uuid,
     strategy_data: Json<CreateStrategyRequest>,
-) -> Result<Json<CreateStrategyResponse>, Json<String>> {
+) -> Result<Json<CreateStrategyResponse>, ApiError> {
+    ensure_owner(pool.inner(), &caller, user_uid).await?;
+    strategy_data.config.validate().map_err(|e| (Status::BadRequest, Json(e)))?;
+    ensure_market_supported(pool.inner(), strategy_data.account_uid, user_uid, &strategy_data.config).await?;
+
     let strategy_uid = Uuid::new_v4();
+    let webhook_token = generate_webhook_token();
 
-    sqlx::query!(
-        "INSERT INTO strategies (id, user_id, strategy_name) VALUES ($1, $2, $3)",
+    let mut tx = pool.inner().begin().await.map_err(|e| internal(Json(format!("Transaction error: {e}"))))?;
+
+    let inserted = sqlx::query!(
+        "INSERT INTO strategies (id, user_id, account_id, strategy_name, config, webhook_token)
+         SELECT $1, user_id, id, $4, $5, $6 FROM exchange_accounts WHERE id = $3 AND user_id = $2",
         strategy_uid,
-        strategy_data.user_uid,
-        strategy_data.strategy_name
+        user_uid,
+        strategy_data.account_uid,
+        strategy_data.strategy_name,
+        SqlJson(&strategy_data.config) as _,
+        webhook_token
     )
-    .execute(pool.inner())
+    .execute(&mut *tx)
     .await
-    .map_err(|e| Json(format!("Database error: {:?}", e)))?;
+    .map_err(db_error)?
+    .rows_affected();
 
-    let webhook = format!("{}/webhook/{}", config.domain, strategy_uid);
+    if inserted == 0 {
+        return Err(not_found("Account not found for this user"));
+    }
+
+    sqlx::query!(
+        "INSERT INTO strategy_config_versions (strategy_id, version, config)
+         SELECT id, config_version, config FROM strategies WHERE id = $1",
+        strategy_uid
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    let after = audit::snapshot(&mut tx, "strategies", strategy_uid).await.map_err(|e| internal(audit_error(e)))?;
+    audit::record(&mut tx, &actor, "strategy.create", "strategy", Some(strategy_uid), None, after)
+        .await
+        .map_err(|e| internal(audit_error(e)))?;
+
+    tx.commit().await.map_err(|e| internal(Json(format!("Commit error: {e}"))))?;
+
+    let webhook = format!("{}/webhook/{}", config.domain, webhook_token);
 
     Ok(Json(CreateStrategyResponse { webhook, strategy_uid }))
 }
 
-/// **DELETE /api/strategy/{strategyUid}** — Удаление стратегии
+/// **DELETE /api/user/{userUid}/strategy/{strategyUid}** — Удаление стратегии
 #[openapi(tag = "Strategy Management")]
-#[delete("/strategy/<strategy_uid>")]
+#[delete("/user/<user_uid>/strategy/<strategy_uid>")]
 pub async fn delete_strategy(
     pool: &State<PgPool>,
-    _admin: AdminGuard,
+    caller: Caller<StrategiesDelete>,
+    actor: AuditActor,
+    user_uid: Uuid,
     strategy_uid: Uuid,
-) -> Result<Json<String>, Json<String>> {
-    let deleted = sqlx::query!("DELETE FROM strategies WHERE id = $1", strategy_uid)
-        .execute(pool.inner())
-        .await
-        .map_err(|e| Json(format!("Database error: {:?}", e)))?
-        .rows_affected();
+) -> Result<Json<String>, ApiError> {
+    caller.ensure_user(user_uid).map_err(|e| (Status::NotFound, e))?;
+
+    let mut tx = pool.inner().begin().await.map_err(|e| internal(Json(format!("Transaction error: {e}"))))?;
+
+    let before = audit::snapshot(&mut tx, "strategies", strategy_uid).await.map_err(|e| internal(audit_error(e)))?;
+
+    let deleted = sqlx::query!(
+        "DELETE FROM strategies WHERE id = $1 AND user_id = $2",
+        strategy_uid,
+        user_uid
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .rows_affected();
 
     if deleted == 0 {
-        return Err(Json("Strategy not found".to_string()));
+        return Err(not_found("Strategy not found"));
     }
 
+    audit::record(&mut tx, &actor, "strategy.delete", "strategy", Some(strategy_uid), before, None)
+        .await
+        .map_err(|e| internal(audit_error(e)))?;
+
+    tx.commit().await.map_err(|e| internal(Json(format!("Commit error: {e}"))))?;
+
     Ok(Json("Strategy deleted successfully".to_string()))
 }
 
-/// **GET /api/strategies?userUid=...** — Получение списка стратегий
+#[derive(FromRow)]
+struct StrategyRow {
+    id: Uuid,
+    account_id: Uuid,
+    strategy_name: String,
+    enabled: bool,
+    published: bool,
+    created_at: NaiveDateTime,
+    config: SqlJson<StrategyConfig>,
+    config_version: i32,
+    cursor_key: String,
+}
+
+impl From<StrategyRow> for Strategy {
+    fn from(row: StrategyRow) -> Self {
+        Strategy {
+            strategy_uid: row.id,
+            account_uid: row.account_id,
+            strategy_name: row.strategy_name,
+            enabled: row.enabled,
+            published: row.published,
+            created_at: row.created_at,
+            config: row.config.0,
+            config_version: row.config_version,
+        }
+    }
+}
+
+fn strategy_sort_column(sort: StrategySortField) -> SortColumn {
+    match sort {
+        StrategySortField::CreatedAt => SortColumn { expr: "strategies.created_at", sql_type: "timestamp" },
+        StrategySortField::Name => SortColumn { expr: "strategies.strategy_name", sql_type: "text" },
+    }
+}
+
+/// **GET /api/user/{userUid}/strategies** — Получение списка стратегий (постранично)
+///
+/// Только свои стратегии; подписки на чужие — `GET /api/user/{userUid}/subscriptions`.
 #[openapi(tag = "Strategy Management")]
-#[get("/strategies?<user_uid>")]
+#[get("/user/<user_uid>/strategies?<query..>")]
 pub async fn get_strategies(
     pool: &State<PgPool>,
-    _admin: AdminGuard,
+    caller: Caller<StrategiesRead>,
     user_uid: Uuid,
-) -> Result<Json<StrategiesResponse>, Json<String>> {
-    let result = sqlx::query!(
-        "SELECT id, strategy_name, enabled FROM strategies WHERE user_id = $1",
-        user_uid
-    )
-    .fetch_all(pool.inner())
-    .await
-    .map_err(|_| Json("Failed to fetch strategies".to_string()))?;
+    query: StrategiesQuery,
+) -> Result<Json<StrategiesResponse>, ApiError> {
+    ensure_owner(pool.inner(), &caller, user_uid).await?;
 
-    let mut personal = Vec::new();
-    let mut other = Vec::new();
+    let limit = clamp_limit(query.limit);
+    let sort = strategy_sort_column(query.sort.unwrap_or_default());
 
-    for row in result {
-        let strategy = Strategy {
-            strategy_uid: row.id,
-            strategy_name: row.strategy_name,
-            enabled: row.enabled,
-        };
-
-        if strategy.enabled {
-            personal.push(strategy);
-        } else {
-            other.push(strategy);
-        }
+    let mut qb = QueryBuilder::<Postgres>::new(format!(
+        "SELECT strategies.id, strategies.account_id, strategies.strategy_name, strategies.enabled,
+                strategies.published, strategies.created_at, strategies.config, strategies.config_version,
+                {}::text AS cursor_key
+         FROM strategies
+         JOIN exchange_accounts ON strategies.account_id = exchange_accounts.id
+         WHERE strategies.user_id = ",
+        sort.expr
+    ));
+    qb.push_bind(user_uid);
+    if let Some(account_uid) = query.account_uid {
+        qb.push(" AND strategies.account_id = ").push_bind(account_uid);
     }
+    if let Some(exchange) = &query.exchange {
+        qb.push(" AND exchange_accounts.exchange = ").push_bind(exchange);
+    }
+    if let Some(enabled) = query.enabled {
+        qb.push(" AND strategies.enabled = ").push_bind(enabled);
+    }
+    if let Some(name) = &query.name {
+        qb.push(" AND strategies.strategy_name ILIKE ").push_bind(format!("%{name}%"));
+    }
+    push_created_range(&mut qb, "strategies.created_at", query.created_from.as_deref(), query.created_to.as_deref())
+        .map_err(|e| (Status::BadRequest, Json(e)))?;
+    push_page(&mut qb, &sort, "strategies.id", query.order.unwrap_or_default(), query.cursor.as_deref(), limit)
+        .map_err(|e| (Status::BadRequest, Json(e)))?;
+
+    let rows: Vec<StrategyRow> = qb
+        .build_query_as()
+        .fetch_all(pool.inner())
+        .await
+        .map_err(|_| internal(Json("Failed to fetch strategies".to_string())))?;
+
+    let (rows, page) = finish_page(rows, limit, |row| Cursor { key: row.cursor_key.clone(), id: row.id });
 
-    Ok(Json(StrategiesResponse { personal, other }))
+    let personal = rows.into_iter().map(Strategy::from).collect();
+
+    Ok(Json(StrategiesResponse { personal, page }))
 }
 
-/// **POST /api/strategies/{enable/disable}?userUid=...** — Включение/выключение стратегий
+/// **POST /api/user/{userUid}/strategies/{enable/disable}** — Включение/выключение стратегий
+///
+/// Стратегии других пользователей пропускаются.
 #[openapi(tag = "Strategy Management")]
-#[post("/strategies/<action>?<user_uid>", format = "json", data = "<toggle_request>")]
+#[post("/user/<user_uid>/strategies/<action>", format = "json", data = "<toggle_request>")]
 pub async fn toggle_strategies(
     pool: &State<PgPool>,
-    _admin: AdminGuard,
-    action: &str,
+    caller: Caller<StrategiesWrite>,
+    actor: AuditActor,
     user_uid: Uuid,
+    action: &str,
     toggle_request: Json<ToggleStrategiesRequest>,
-) -> Result<Json<String>, Json<String>> {
+) -> Result<Json<String>, ApiError> {
+    ensure_owner(pool.inner(), &caller, user_uid).await?;
+
     let enable = match action {
         "enable" => true,
         "disable" => false,
-        _ => return Err(Json("Invalid action. Use 'enable' or 'disable'.".to_string())),
+        _ => return Err((Status::BadRequest, Json("Invalid action. Use 'enable' or 'disable'.".to_string()))),
     };
 
     let strategy_uids = &toggle_request.strategy_uids;
 
     if strategy_uids.is_empty() {
-        return Err(Json("No strategies provided".to_string()));
+        return Err((Status::BadRequest, Json("No strategies provided".to_string())));
     }
 
-    let mut tx = pool.inner().begin().await.map_err(|e| Json(format!("Transaction error: {e}")))?;
+    let mut tx = pool.inner().begin().await.map_err(|e| internal(Json(format!("Transaction error: {e}"))))?;
 
     for strategy_uid in strategy_uids {
-        sqlx::query!(
-            "UPDATE strategies SET enabled = $1 WHERE id = $2 AND user_id = $3",
+        let before = audit::snapshot(&mut tx, "strategies", *strategy_uid).await.map_err(|e| internal(audit_error(e)))?;
+
+        let updated = sqlx::query!(
+            "UPDATE strategies
+             SET enabled = $1, auto_disabled_at = CASE WHEN $1 THEN NULL ELSE auto_disabled_at END
+             WHERE id = $2 AND user_id = $3",
             enable,
             strategy_uid,
             user_uid
         )
         .execute(&mut *tx)
         .await
-        .map_err(|e| Json(format!("Database error: {:?}", e)))?;
+        .map_err(db_error)?
+        .rows_affected();
+
+        if updated > 0 {
+            let after = audit::snapshot(&mut tx, "strategies", *strategy_uid).await.map_err(|e| internal(audit_error(e)))?;
+            audit::record(&mut tx, &actor, "strategy.toggle", "strategy", Some(*strategy_uid), before, after)
+                .await
+                .map_err(|e| internal(audit_error(e)))?;
+        }
     }
 
-    tx.commit().await.map_err(|e| Json(format!("Commit error: {e}")))?;
+    tx.commit().await.map_err(|e| internal(Json(format!("Commit error: {e}"))))?;
 
     Ok(Json(format!(
         "Strategies successfully {}d",
@@ -134,53 +308,478 @@ pub async fn toggle_strategies(
     )))
 }
 
-
-/// **PUT /api/strategy/{strategyUid}** 
+/// **PUT /api/user/{userUid}/strategy/{strategyUid}** — Переименование стратегии или перенос на другой аккаунт
 #[openapi(tag = "Strategy Management")]
-#[put("/strategy/<strategy_uid>", format = "json", data = "<update_data>")]
+#[put("/user/<user_uid>/strategy/<strategy_uid>", format = "json", data = "<update_data>")]
 pub async fn update_strategy(
     pool: &State<PgPool>,
-    _admin: AdminGuard,
+    caller: Caller<StrategiesWrite>,
+    actor: AuditActor,
+    user_uid: Uuid,
     strategy_uid: Uuid,
-    update_data: Json<CreateStrategyRequest>,
-) -> Result<Json<String>, Json<String>> {
+    update_data: Json<UpdateStrategyRequest>,
+) -> Result<Json<String>, ApiError> {
+    caller.ensure_user(user_uid).map_err(|e| (Status::NotFound, e))?;
+
+    let mut tx = pool.inner().begin().await.map_err(|e| internal(Json(format!("Transaction error: {e}"))))?;
+
+    let current = sqlx::query!(
+        r#"SELECT to_jsonb(s) AS snapshot, config AS "config: SqlJson<StrategyConfig>"
+           FROM strategies s WHERE id = $1 AND user_id = $2 FOR UPDATE"#,
+        strategy_uid,
+        user_uid
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| not_found("Strategy not found"))?;
+
+    ensure_market_supported(&mut *tx, update_data.account_uid, user_uid, &current.config.0).await?;
+
     let updated = sqlx::query!(
-        "UPDATE strategies SET strategy_name = $1 WHERE id = $2",
+        "UPDATE strategies
+         SET strategy_name = $1, account_id = exchange_accounts.id
+         FROM exchange_accounts
+         WHERE strategies.id = $2
+           AND exchange_accounts.id = $3
+           AND exchange_accounts.user_id = strategies.user_id",
         update_data.strategy_name,
-        strategy_uid
+        strategy_uid,
+        update_data.account_uid
     )
-    .execute(pool.inner())
+    .execute(&mut *tx)
     .await
-    .map_err(|e| Json(format!("Database error: {:?}", e)))?
+    .map_err(db_error)?
     .rows_affected();
 
     if updated == 0 {
-        return Err(Json("Strategy not found".to_string()));
+        return Err(not_found("Account not found for this user"));
     }
 
-    Ok(Json("Strategy name updated successfully".to_string()))
-}
+    let after = audit::snapshot(&mut tx, "strategies", strategy_uid).await.map_err(|e| internal(audit_error(e)))?;
+    audit::record(&mut tx, &actor, "strategy.update", "strategy", Some(strategy_uid), current.snapshot, after)
+        .await
+        .map_err(|e| internal(audit_error(e)))?;
+
+    tx.commit().await.map_err(|e| internal(Json(format!("Commit error: {e}"))))?;
 
+    Ok(Json("Strategy updated successfully".to_string()))
+}
 
-/// **GET /api/strategy/{strategyUid}** — Получение информации о стратегии
+/// **GET /api/user/{userUid}/strategy/{strategyUid}** — Получение информации о стратегии
 #[openapi(tag = "Strategy Management")]
-#[get("/strategy/<strategy_uid>")]
+#[get("/user/<user_uid>/strategy/<strategy_uid>")]
 pub async fn get_strategy(
     pool: &State<PgPool>,
-    _admin: AdminGuard,
+    caller: Caller<StrategiesRead>,
+    user_uid: Uuid,
     strategy_uid: Uuid,
-) -> Result<Json<Strategy>, Json<String>> {
+) -> Result<Json<Strategy>, ApiError> {
+    caller.ensure_user(user_uid).map_err(|e| (Status::NotFound, e))?;
+
     let strategy = sqlx::query!(
-        "SELECT id, strategy_name, enabled FROM strategies WHERE id = $1",
-        strategy_uid
+        r#"SELECT id, account_id, strategy_name, enabled, published, created_at,
+                config AS "config: SqlJson<StrategyConfig>", config_version
+         FROM strategies WHERE id = $1 AND user_id = $2"#,
+        strategy_uid,
+        user_uid
     )
-    .fetch_one(pool.inner())
+    .fetch_optional(pool.inner())
     .await
-    .map_err(|_| Json("Strategy not found".to_string()))?;
+    .map_err(db_error)?
+    .ok_or_else(|| not_found("Strategy not found"))?;
 
     Ok(Json(Strategy {
         strategy_uid: strategy.id,
+        account_uid: strategy.account_id,
         strategy_name: strategy.strategy_name,
         enabled: strategy.enabled,
+        published: strategy.published,
+        created_at: strategy.created_at,
+        config: strategy.config.0,
+        config_version: strategy.config_version,
+    }))
+}
+
+/// **PUT /api/user/{userUid}/strategy/{strategyUid}/config** — Изменение настроек стратегии
+///
+/// Каждое изменение сохраняется как новая версия.
+#[openapi(tag = "Strategy Management")]
+#[put("/user/<user_uid>/strategy/<strategy_uid>/config", format = "json", data = "<config_data>")]
+pub async fn update_strategy_config(
+    pool: &State<PgPool>,
+    caller: Caller<StrategiesWrite>,
+    actor: AuditActor,
+    user_uid: Uuid,
+    strategy_uid: Uuid,
+    config_data: Json<UpdateStrategyConfigRequest>,
+) -> Result<Json<StrategyConfigVersion>, ApiError> {
+    caller.ensure_user(user_uid).map_err(|e| (Status::NotFound, e))?;
+    config_data.config.validate().map_err(|e| (Status::BadRequest, Json(e)))?;
+
+    let mut tx = pool.inner().begin().await.map_err(|e| internal(Json(format!("Transaction error: {e}"))))?;
+
+    let current = sqlx::query!(
+        "SELECT to_jsonb(s) AS snapshot, config_version, account_id
+         FROM strategies s WHERE id = $1 AND user_id = $2 FOR UPDATE",
+        strategy_uid,
+        user_uid
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| not_found("Strategy not found"))?;
+
+    if config_data.expected_version.is_some_and(|v| v != current.config_version) {
+        return Err((
+            Status::Conflict,
+            Json(format!("Config was changed concurrently, current version is {}", current.config_version)),
+        ));
+    }
+    ensure_market_supported(&mut *tx, current.account_id, user_uid, &config_data.config).await?;
+
+    let created = sqlx::query!(
+        "WITH updated AS (
+             UPDATE strategies SET config = $2, config_version = config_version + 1
+             WHERE id = $1 RETURNING id, config_version, config
+         )
+         INSERT INTO strategy_config_versions (strategy_id, version, config)
+         SELECT id, config_version, config FROM updated
+         RETURNING version, created_at",
+        strategy_uid,
+        SqlJson(&config_data.config) as _
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    let after = audit::snapshot(&mut tx, "strategies", strategy_uid).await.map_err(|e| internal(audit_error(e)))?;
+    audit::record(&mut tx, &actor, "strategy.configure", "strategy", Some(strategy_uid), current.snapshot, after)
+        .await
+        .map_err(|e| internal(audit_error(e)))?;
+
+    tx.commit().await.map_err(|e| internal(Json(format!("Commit error: {e}"))))?;
+
+    Ok(Json(StrategyConfigVersion {
+        version: created.version,
+        config: config_data.into_inner().config,
+        created_at: created.created_at,
+    }))
+}
+
+/// **GET /api/user/{userUid}/strategy/{strategyUid}/config/versions** — История настроек (новые сверху)
+#[openapi(tag = "Strategy Management")]
+#[get("/user/<user_uid>/strategy/<strategy_uid>/config/versions")]
+pub async fn get_strategy_config_versions(
+    pool: &State<PgPool>,
+    caller: Caller<StrategiesRead>,
+    user_uid: Uuid,
+    strategy_uid: Uuid,
+) -> Result<Json<Vec<StrategyConfigVersion>>, ApiError> {
+    caller.ensure_user(user_uid).map_err(|e| (Status::NotFound, e))?;
+
+    let versions = sqlx::query!(
+        r#"SELECT v.version, v.config AS "config: SqlJson<StrategyConfig>", v.created_at
+         FROM strategy_config_versions v
+         JOIN strategies ON strategies.id = v.strategy_id
+         WHERE v.strategy_id = $1 AND strategies.user_id = $2
+         ORDER BY v.version DESC"#,
+        strategy_uid,
+        user_uid
+    )
+    .fetch_all(pool.inner())
+    .await
+    .map_err(db_error)?;
+
+    if versions.is_empty() {
+        return Err(not_found("Strategy not found"));
+    }
+
+    Ok(Json(
+        versions
+            .into_iter()
+            .map(|v| StrategyConfigVersion { version: v.version, config: v.config.0, created_at: v.created_at })
+            .collect(),
+    ))
+}
+
+/// **POST /api/user/{userUid}/strategy/{strategyUid}/{publish/unpublish}** — Публикация стратегии в каталоге
+///
+/// Пока стратегия снята с публикации, сигналы подписчикам не рассылаются; подписки сохраняются.
+#[openapi(tag = "Strategy Management")]
+#[post("/user/<user_uid>/strategy/<strategy_uid>/<action>")]
+pub async fn publish_strategy(
+    pool: &State<PgPool>,
+    caller: Caller<StrategiesWrite>,
+    actor: AuditActor,
+    user_uid: Uuid,
+    strategy_uid: Uuid,
+    action: &str,
+) -> Result<Json<String>, ApiError> {
+    caller.ensure_user(user_uid).map_err(|e| (Status::NotFound, e))?;
+
+    let published = match action {
+        "publish" => true,
+        "unpublish" => false,
+        _ => return Err((Status::BadRequest, Json("Invalid action. Use 'publish' or 'unpublish'.".to_string()))),
+    };
+
+    let mut tx = pool.inner().begin().await.map_err(|e| internal(Json(format!("Transaction error: {e}"))))?;
+
+    let before = audit::snapshot(&mut tx, "strategies", strategy_uid).await.map_err(|e| internal(audit_error(e)))?;
+
+    let updated = sqlx::query!(
+        "UPDATE strategies SET published = $1 WHERE id = $2 AND user_id = $3",
+        published,
+        strategy_uid,
+        user_uid
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .rows_affected();
+
+    if updated == 0 {
+        return Err(not_found("Strategy not found"));
+    }
+
+    let after = audit::snapshot(&mut tx, "strategies", strategy_uid).await.map_err(|e| internal(audit_error(e)))?;
+    audit::record(&mut tx, &actor, &format!("strategy.{action}"), "strategy", Some(strategy_uid), before, after)
+        .await
+        .map_err(|e| internal(audit_error(e)))?;
+
+    tx.commit().await.map_err(|e| internal(Json(format!("Commit error: {e}"))))?;
+
+    Ok(Json(format!("Strategy successfully {action}ed")))
+}
+
+#[derive(FromRow)]
+struct CatalogueRow {
+    id: Uuid,
+    strategy_name: String,
+    exchange: String,
+    config: SqlJson<StrategyConfig>,
+    subscribers: i64,
+    created_at: NaiveDateTime,
+    cursor_key: String,
+}
+
+/// **GET /api/strategies/catalogue** — Каталог опубликованных стратегий (постранично)
+#[openapi(tag = "Strategy Management")]
+#[get("/strategies/catalogue?<query..>")]
+pub async fn get_catalogue(
+    pool: &State<PgPool>,
+    _caller: Caller<StrategiesRead>,
+    query: CatalogueQuery,
+) -> Result<Json<Page<CatalogueStrategy>>, ApiError> {
+    let limit = clamp_limit(query.limit);
+    let sort = SortColumn { expr: "strategies.created_at", sql_type: "timestamp" };
+
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "SELECT strategies.id, strategies.strategy_name, exchange_accounts.exchange, strategies.config,
+                (SELECT count(*) FROM strategy_subscriptions
+                 WHERE strategy_id = strategies.id AND active) AS subscribers,
+                strategies.created_at, strategies.created_at::text AS cursor_key
+         FROM strategies
+         JOIN exchange_accounts ON strategies.account_id = exchange_accounts.id
+         WHERE strategies.published",
+    );
+    if let Some(exchange) = &query.exchange {
+        qb.push(" AND exchange_accounts.exchange = ").push_bind(exchange);
+    }
+    if let Some(name) = &query.name {
+        qb.push(" AND strategies.strategy_name ILIKE ").push_bind(format!("%{name}%"));
+    }
+    push_page(&mut qb, &sort, "strategies.id", query.order.unwrap_or_default(), query.cursor.as_deref(), limit)
+        .map_err(|e| (Status::BadRequest, Json(e)))?;
+
+    let rows: Vec<CatalogueRow> = qb
+        .build_query_as()
+        .fetch_all(pool.inner())
+        .await
+        .map_err(|_| internal(Json("Failed to fetch catalogue".to_string())))?;
+
+    let (rows, page) = finish_page(rows, limit, |row| Cursor { key: row.cursor_key.clone(), id: row.id });
+
+    let items = rows
+        .into_iter()
+        .map(|row| CatalogueStrategy {
+            strategy_uid: row.id,
+            strategy_name: row.strategy_name,
+            exchange: row.exchange,
+            config: row.config.0,
+            subscribers: row.subscribers,
+            created_at: row.created_at,
+        })
+        .collect();
+
+    Ok(Json(Page { items, page }))
+}
+
+/// Максимальный срок, в течение которого после ротации принимается старый URL вебхука
+const MAX_WEBHOOK_GRACE_PERIOD_SECS: u32 = 7 * 24 * 60 * 60;
+
+/// **POST /api/user/{userUid}/strategy/{strategyUid}/webhook/rotate** — Новый URL вебхука
+///
+/// Старый URL перестаёт работать сразу или по окончании `gracePeriodSecs`.
+/// Токен, оставшийся от предыдущей ротации, отключается в любом случае.
+#[openapi(tag = "Strategy Management")]
+#[post("/user/<user_uid>/strategy/<strategy_uid>/webhook/rotate", format = "json", data = "<rotate_data>")]
+pub async fn rotate_webhook(
+    pool: &State<PgPool>,
+    config: &State<Config>,
+    caller: Caller<StrategiesWrite>,
+    actor: AuditActor,
+    user_uid: Uuid,
+    strategy_uid: Uuid,
+    rotate_data: Json<RotateWebhookRequest>,
+) -> Result<Json<RotateWebhookResponse>, ApiError> {
+    caller.ensure_user(user_uid).map_err(|e| (Status::NotFound, e))?;
+
+    let grace_period_secs = rotate_data.grace_period_secs.unwrap_or(0);
+    if grace_period_secs > MAX_WEBHOOK_GRACE_PERIOD_SECS {
+        return Err((
+            Status::BadRequest,
+            Json(format!("gracePeriodSecs must not exceed {MAX_WEBHOOK_GRACE_PERIOD_SECS}")),
+        ));
+    }
+
+    let webhook_token = generate_webhook_token();
+
+    let mut tx = pool.inner().begin().await.map_err(|e| internal(Json(format!("Transaction error: {e}"))))?;
+
+    let before = audit::snapshot(&mut tx, "strategies", strategy_uid).await.map_err(|e| internal(audit_error(e)))?;
+
+    let rotated = sqlx::query!(
+        "UPDATE strategies
+         SET previous_webhook_token = CASE WHEN $3 > 0 THEN webhook_token END,
+             previous_webhook_token_expires_at = CASE WHEN $3 > 0 THEN now() + make_interval(secs => $3) END,
+             webhook_token = $4
+         WHERE id = $1 AND user_id = $2
+         RETURNING previous_webhook_token_expires_at",
+        strategy_uid,
+        user_uid,
+        grace_period_secs as f64,
+        webhook_token
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| not_found("Strategy not found"))?;
+
+    let after = audit::snapshot(&mut tx, "strategies", strategy_uid).await.map_err(|e| internal(audit_error(e)))?;
+    audit::record(&mut tx, &actor, "strategy.rotate_webhook", "strategy", Some(strategy_uid), before, after)
+        .await
+        .map_err(|e| internal(audit_error(e)))?;
+
+    tx.commit().await.map_err(|e| internal(Json(format!("Commit error: {e}"))))?;
+
+    Ok(Json(RotateWebhookResponse {
+        webhook: format!("{}/webhook/{}", config.domain, webhook_token),
+        previous_valid_until: rotated.previous_webhook_token_expires_at,
     }))
 }
+
+/// **GET /api/user/{userUid}/strategy/{strategyUid}/stats** — Доходность стратегии
+///
+/// Результат кэшируется на минуту; новое исполнение по стратегии сбрасывает кэш.
+#[openapi(tag = "Strategy Management")]
+#[get("/user/<user_uid>/strategy/<strategy_uid>/stats?<query..>")]
+pub async fn get_strategy_stats(
+    pool: &State<PgPool>,
+    stats_cache: &State<Arc<StatsCache>>,
+    caller: Caller<StrategiesRead>,
+    user_uid: Uuid,
+    strategy_uid: Uuid,
+    query: StatsQuery,
+) -> Result<Json<StrategyStats>, ApiError> {
+    caller.ensure_user(user_uid).map_err(|e| (Status::NotFound, e))?;
+
+    let parse = |raw: Option<&str>| raw.map(parse_datetime).transpose().map_err(|e| (Status::BadRequest, Json(e)));
+    let from = parse(query.from.as_deref())?;
+    let to = parse(query.to.as_deref())?;
+
+    let exists = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM strategies WHERE id = $1 AND user_id = $2)",
+        strategy_uid,
+        user_uid
+    )
+    .fetch_one(pool.inner())
+    .await
+    .map_err(db_error)?
+    .unwrap_or(false);
+    if !exists {
+        return Err(not_found("Strategy not found"));
+    }
+
+    let key = (strategy_uid, from, to);
+    if let Some(stats) = stats_cache.get(&key) {
+        return Ok(Json(stats));
+    }
+
+    let stats = compute_stats(pool.inner(), strategy_uid, from, to).await.map_err(db_error)?;
+    stats_cache.put(key, stats.clone());
+
+    Ok(Json(stats))
+}
+
+/// **GET /api/user/{userUid}/strategy/{strategyUid}/signals** — Последние принятые сигналы (новые сверху)
+///
+/// У каждого сигнала — решение проверки отклонения цены: `unchecked`, `passed`, `rejected` или `limited`.
+#[openapi(tag = "Strategy Management")]
+#[get("/user/<user_uid>/strategy/<strategy_uid>/signals?<query..>")]
+pub async fn get_strategy_signals(
+    pool: &State<PgPool>,
+    caller: Caller<StrategiesRead>,
+    user_uid: Uuid,
+    strategy_uid: Uuid,
+    query: SignalsQuery,
+) -> Result<Json<Vec<SignalRecord>>, ApiError> {
+    caller.ensure_user(user_uid).map_err(|e| (Status::NotFound, e))?;
+
+    let exists = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM strategies WHERE id = $1 AND user_id = $2)",
+        strategy_uid,
+        user_uid
+    )
+    .fetch_one(pool.inner())
+    .await
+    .map_err(db_error)?
+    .unwrap_or(false);
+    if !exists {
+        return Err(not_found("Strategy not found"));
+    }
+
+    let signals = sqlx::query!(
+        "SELECT id, signal_id, symbol, side, market_type, position_action, signal_price, live_price, deviation_pct,
+                slippage_decision, order_price, received_at
+         FROM signals WHERE strategy_id = $1
+         ORDER BY received_at DESC, id DESC
+         LIMIT $2",
+        strategy_uid,
+        clamp_limit(query.limit)
+    )
+    .fetch_all(pool.inner())
+    .await
+    .map_err(db_error)?;
+
+    Ok(Json(
+        signals
+            .into_iter()
+            .map(|s| SignalRecord {
+                signal_record_uid: s.id,
+                signal_id: s.signal_id,
+                symbol: s.symbol,
+                side: s.side,
+                market_type: MarketType::parse(&s.market_type).unwrap_or_default(),
+                position_action: PositionAction::parse(&s.position_action).unwrap_or(PositionAction::OpenLong),
+                signal_price: s.signal_price,
+                live_price: s.live_price,
+                deviation_pct: s.deviation_pct,
+                slippage_decision: SlippageDecision::parse(&s.slippage_decision).unwrap_or(SlippageDecision::Unchecked),
+                order_price: s.order_price,
+                received_at: s.received_at,
+            })
+            .collect(),
+    ))
+}
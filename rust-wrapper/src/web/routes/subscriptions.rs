@@ -0,0 +1,257 @@
+use rocket::http::Status;
+use rocket::{delete, get, post, put, serde::json::Json, State};
+use chrono::NaiveDateTime;
+use rocket_okapi::openapi;
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::audit::{self, AuditActor};
+use crate::types::{CreateSubscriptionRequest, Page, Subscription, SubscriptionsQuery, UpdateSubscriptionRequest};
+use crate::web::{audit_error, db_error, ensure_owner, internal, not_found, ApiError};
+use crate::web::guards::scopes::{StrategiesRead, StrategiesWrite};
+use crate::web::guards::Caller;
+use crate::web::pagination::{clamp_limit, finish_page, push_page, Cursor, SortColumn};
+
+fn validate_multiplier(multiplier: Option<f64>) -> Result<(), ApiError> {
+    match multiplier {
+        Some(m) if !(m.is_finite() && m > 0.0) => {
+            Err((Status::BadRequest, Json("sizingMultiplier must be positive".to_string())))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// **POST /api/user/{userUid}/subscriptions** — Подписка аккаунта на опубликованную стратегию
+#[openapi(tag = "Copy Trading")]
+#[post("/user/<user_uid>/subscriptions", format = "json", data = "<subscription_data>")]
+pub async fn create_subscription(
+    pool: &State<PgPool>,
+    caller: Caller<StrategiesWrite>,
+    actor: AuditActor,
+    user_uid: Uuid,
+    subscription_data: Json<CreateSubscriptionRequest>,
+) -> Result<Json<Subscription>, ApiError> {
+    ensure_owner(pool.inner(), &caller, user_uid).await?;
+    validate_multiplier(subscription_data.sizing_multiplier)?;
+
+    let strategy = sqlx::query!(
+        "SELECT user_id, strategy_name FROM strategies WHERE id = $1 AND published",
+        subscription_data.strategy_uid
+    )
+    .fetch_optional(pool.inner())
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| not_found("Strategy not found"))?;
+
+    if strategy.user_id == user_uid {
+        return Err((Status::BadRequest, Json("Cannot subscribe to your own strategy".to_string())));
+    }
+
+    let subscription_uid = Uuid::new_v4();
+
+    let mut tx = pool.inner().begin().await.map_err(|e| internal(Json(format!("Transaction error: {e}"))))?;
+
+    let created = sqlx::query!(
+        "INSERT INTO strategy_subscriptions (id, strategy_id, user_id, account_id, sizing_multiplier)
+         SELECT $1, $2, user_id, id, $4 FROM exchange_accounts WHERE id = $3 AND user_id = $5
+         ON CONFLICT (strategy_id, account_id) DO NOTHING
+         RETURNING account_id, sizing_multiplier, active, created_at",
+        subscription_uid,
+        subscription_data.strategy_uid,
+        subscription_data.account_uid,
+        subscription_data.sizing_multiplier.unwrap_or(1.0),
+        user_uid
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    let Some(created) = created else {
+        let exists = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM exchange_accounts WHERE id = $1 AND user_id = $2)",
+            subscription_data.account_uid,
+            user_uid
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?
+        .unwrap_or(false);
+
+        return Err(if exists {
+            (Status::Conflict, Json("This account is already subscribed to the strategy".to_string()))
+        } else {
+            not_found("Account not found for this user")
+        });
+    };
+
+    let after = audit::snapshot(&mut tx, "strategy_subscriptions", subscription_uid)
+        .await
+        .map_err(|e| internal(audit_error(e)))?;
+    audit::record(&mut tx, &actor, "subscription.create", "subscription", Some(subscription_uid), None, after)
+        .await
+        .map_err(|e| internal(audit_error(e)))?;
+
+    tx.commit().await.map_err(|e| internal(Json(format!("Commit error: {e}"))))?;
+
+    Ok(Json(Subscription {
+        subscription_uid,
+        strategy_uid: subscription_data.strategy_uid,
+        strategy_name: strategy.strategy_name,
+        account_uid: created.account_id,
+        sizing_multiplier: created.sizing_multiplier,
+        active: created.active,
+        created_at: created.created_at,
+    }))
+}
+
+#[derive(FromRow)]
+struct SubscriptionRow {
+    subscription_uid: Uuid,
+    strategy_uid: Uuid,
+    strategy_name: String,
+    account_uid: Uuid,
+    sizing_multiplier: f64,
+    active: bool,
+    created_at: NaiveDateTime,
+    cursor_key: String,
+}
+
+/// **GET /api/user/{userUid}/subscriptions** — Подписки пользователя (постранично)
+#[openapi(tag = "Copy Trading")]
+#[get("/user/<user_uid>/subscriptions?<query..>")]
+pub async fn get_subscriptions(
+    pool: &State<PgPool>,
+    caller: Caller<StrategiesRead>,
+    user_uid: Uuid,
+    query: SubscriptionsQuery,
+) -> Result<Json<Page<Subscription>>, ApiError> {
+    ensure_owner(pool.inner(), &caller, user_uid).await?;
+
+    let limit = clamp_limit(query.limit);
+    let sort = SortColumn { expr: "strategy_subscriptions.created_at", sql_type: "timestamp" };
+
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "SELECT strategy_subscriptions.id AS subscription_uid, strategy_subscriptions.strategy_id AS strategy_uid,
+                strategies.strategy_name, strategy_subscriptions.account_id AS account_uid,
+                strategy_subscriptions.sizing_multiplier, strategy_subscriptions.active,
+                strategy_subscriptions.created_at, strategy_subscriptions.created_at::text AS cursor_key
+         FROM strategy_subscriptions
+         JOIN strategies ON strategies.id = strategy_subscriptions.strategy_id
+         WHERE strategy_subscriptions.user_id = ",
+    );
+    qb.push_bind(user_uid);
+    if let Some(active) = query.active {
+        qb.push(" AND strategy_subscriptions.active = ").push_bind(active);
+    }
+    push_page(&mut qb, &sort, "strategy_subscriptions.id", query.order.unwrap_or_default(), query.cursor.as_deref(), limit)
+        .map_err(|e| (Status::BadRequest, Json(e)))?;
+
+    let rows: Vec<SubscriptionRow> = qb.build_query_as().fetch_all(pool.inner()).await.map_err(db_error)?;
+    let (rows, page) = finish_page(rows, limit, |row| Cursor { key: row.cursor_key.clone(), id: row.subscription_uid });
+
+    let items = rows
+        .into_iter()
+        .map(|row| Subscription {
+            subscription_uid: row.subscription_uid,
+            strategy_uid: row.strategy_uid,
+            strategy_name: row.strategy_name,
+            account_uid: row.account_uid,
+            sizing_multiplier: row.sizing_multiplier,
+            active: row.active,
+            created_at: row.created_at,
+        })
+        .collect();
+
+    Ok(Json(Page { items, page }))
+}
+
+/// **PUT /api/user/{userUid}/subscription/{subscriptionUid}** — Изменение множителя или приостановка подписки
+#[openapi(tag = "Copy Trading")]
+#[put("/user/<user_uid>/subscription/<subscription_uid>", format = "json", data = "<update_data>")]
+pub async fn update_subscription(
+    pool: &State<PgPool>,
+    caller: Caller<StrategiesWrite>,
+    actor: AuditActor,
+    user_uid: Uuid,
+    subscription_uid: Uuid,
+    update_data: Json<UpdateSubscriptionRequest>,
+) -> Result<Json<String>, ApiError> {
+    caller.ensure_user(user_uid).map_err(|e| (Status::NotFound, e))?;
+    validate_multiplier(update_data.sizing_multiplier)?;
+
+    let mut tx = pool.inner().begin().await.map_err(|e| internal(Json(format!("Transaction error: {e}"))))?;
+
+    let before = audit::snapshot(&mut tx, "strategy_subscriptions", subscription_uid)
+        .await
+        .map_err(|e| internal(audit_error(e)))?;
+
+    let updated = sqlx::query!(
+        "UPDATE strategy_subscriptions
+         SET sizing_multiplier = COALESCE($1, sizing_multiplier), active = COALESCE($2, active)
+         WHERE id = $3 AND user_id = $4",
+        update_data.sizing_multiplier,
+        update_data.active,
+        subscription_uid,
+        user_uid
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .rows_affected();
+
+    if updated == 0 {
+        return Err(not_found("Subscription not found"));
+    }
+
+    let after = audit::snapshot(&mut tx, "strategy_subscriptions", subscription_uid)
+        .await
+        .map_err(|e| internal(audit_error(e)))?;
+    audit::record(&mut tx, &actor, "subscription.update", "subscription", Some(subscription_uid), before, after)
+        .await
+        .map_err(|e| internal(audit_error(e)))?;
+
+    tx.commit().await.map_err(|e| internal(Json(format!("Commit error: {e}"))))?;
+
+    Ok(Json("Subscription updated successfully".to_string()))
+}
+
+/// **DELETE /api/user/{userUid}/subscription/{subscriptionUid}** — Отписка
+#[openapi(tag = "Copy Trading")]
+#[delete("/user/<user_uid>/subscription/<subscription_uid>")]
+pub async fn delete_subscription(
+    pool: &State<PgPool>,
+    caller: Caller<StrategiesWrite>,
+    actor: AuditActor,
+    user_uid: Uuid,
+    subscription_uid: Uuid,
+) -> Result<Json<String>, ApiError> {
+    caller.ensure_user(user_uid).map_err(|e| (Status::NotFound, e))?;
+
+    let mut tx = pool.inner().begin().await.map_err(|e| internal(Json(format!("Transaction error: {e}"))))?;
+
+    let before = audit::snapshot(&mut tx, "strategy_subscriptions", subscription_uid)
+        .await
+        .map_err(|e| internal(audit_error(e)))?;
+
+    let deleted = sqlx::query!(
+        "DELETE FROM strategy_subscriptions WHERE id = $1 AND user_id = $2",
+        subscription_uid,
+        user_uid
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .rows_affected();
+
+    if deleted == 0 {
+        return Err(not_found("Subscription not found"));
+    }
+
+    audit::record(&mut tx, &actor, "subscription.delete", "subscription", Some(subscription_uid), before, None)
+        .await
+        .map_err(|e| internal(audit_error(e)))?;
+
+    tx.commit().await.map_err(|e| internal(Json(format!("Commit error: {e}"))))?;
+
+    Ok(Json("Subscription deleted successfully".to_string()))
+}
@@ -0,0 +1,132 @@
+use rocket::{delete, get, post, serde::json::Json, State};
+use rocket_okapi::openapi;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::audit::{self, AuditActor};
+use crate::crypto::{generate_api_token, hash_api_token};
+use crate::types::{ApiToken, IssueTokenRequest, IssueTokenResponse, Role, Scope};
+use crate::web::guards::scopes::TokensManage;
+use crate::web::audit_error;
+use crate::web::guards::TokenGuard;
+
+/// **POST /api/tokens** — Выпуск API-токена
+///
+/// Токен возвращается один раз; сохраните его сразу.
+#[openapi(tag = "API Tokens")]
+#[post("/tokens", format = "json", data = "<token_data>")]
+pub async fn issue_token(
+    pool: &State<PgPool>,
+    _auth: TokenGuard<TokensManage>,
+    actor: AuditActor,
+    token_data: Json<IssueTokenRequest>,
+) -> Result<Json<IssueTokenResponse>, Json<String>> {
+    let role = token_data.role;
+    let scopes = if token_data.scopes.is_empty() {
+        role.scopes().to_vec()
+    } else {
+        token_data.scopes.clone()
+    };
+
+    if let Some(scope) = scopes.iter().find(|s| !role.scopes().contains(s)) {
+        return Err(Json(format!("Role {} cannot have scope {}", role.as_str(), scope.as_str())));
+    }
+
+    let token_uid = Uuid::new_v4();
+    let token = generate_api_token();
+    let scopes: Vec<String> = scopes.iter().map(|s| s.as_str().to_string()).collect();
+
+    let mut tx = pool.inner().begin().await.map_err(|e| Json(format!("Transaction error: {e}")))?;
+
+    sqlx::query!(
+        "INSERT INTO api_tokens (id, name, token_hash, role, scopes, expires_at) VALUES ($1, $2, $3, $4, $5, $6)",
+        token_uid,
+        token_data.name,
+        hash_api_token(&token),
+        role.as_str(),
+        &scopes,
+        token_data.expires_at
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| Json(format!("Database error: {:?}", e)))?;
+
+    let after = audit::snapshot(&mut tx, "api_tokens", token_uid).await.map_err(audit_error)?;
+    audit::record(&mut tx, &actor, "token.issue", "api_token", Some(token_uid), None, after)
+        .await
+        .map_err(audit_error)?;
+
+    tx.commit().await.map_err(|e| Json(format!("Commit error: {e}")))?;
+
+    Ok(Json(IssueTokenResponse { token_uid, token }))
+}
+
+/// **GET /api/tokens** — Список API-токенов
+#[openapi(tag = "API Tokens")]
+#[get("/tokens")]
+pub async fn get_tokens(
+    pool: &State<PgPool>,
+    _auth: TokenGuard<TokensManage>,
+) -> Result<Json<Vec<ApiToken>>, Json<String>> {
+    let rows = sqlx::query!(
+        "SELECT id, name, role, scopes, created_at, expires_at, last_used_at, revoked_at
+         FROM api_tokens ORDER BY created_at"
+    )
+    .fetch_all(pool.inner())
+    .await
+    .map_err(|_| Json("Failed to fetch tokens".to_string()))?;
+
+    let tokens = rows
+        .into_iter()
+        .filter_map(|row| {
+            Some(ApiToken {
+                token_uid: row.id,
+                name: row.name,
+                role: Role::parse(&row.role)?,
+                scopes: row.scopes.iter().filter_map(|s| Scope::parse(s)).collect(),
+                created_at: row.created_at,
+                expires_at: row.expires_at,
+                last_used_at: row.last_used_at,
+                revoked_at: row.revoked_at,
+            })
+        })
+        .collect();
+
+    Ok(Json(tokens))
+}
+
+/// **DELETE /api/tokens/<token_uid>** — Отзыв API-токена
+#[openapi(tag = "API Tokens")]
+#[delete("/tokens/<token_uid>")]
+pub async fn revoke_token(
+    pool: &State<PgPool>,
+    _auth: TokenGuard<TokensManage>,
+    actor: AuditActor,
+    token_uid: Uuid,
+) -> Result<Json<String>, Json<String>> {
+    let mut tx = pool.inner().begin().await.map_err(|e| Json(format!("Transaction error: {e}")))?;
+
+    let before = audit::snapshot(&mut tx, "api_tokens", token_uid).await.map_err(audit_error)?;
+
+    let revoked = sqlx::query!(
+        "UPDATE api_tokens SET revoked_at = now() WHERE id = $1 AND revoked_at IS NULL",
+        token_uid
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| Json(format!("Database error: {:?}", e)))?
+    .rows_affected();
+
+    if revoked == 0 {
+        return Err(Json("Token not found".to_string()));
+    }
+
+    let after = audit::snapshot(&mut tx, "api_tokens", token_uid).await.map_err(audit_error)?;
+    audit::record(&mut tx, &actor, "token.revoke", "api_token", Some(token_uid), before, after)
+        .await
+        .map_err(audit_error)?;
+
+    tx.commit().await.map_err(|e| Json(format!("Commit error: {e}")))?;
+
+    Ok(Json("Token revoked successfully".to_string()))
+}
@@ -0,0 +1,471 @@
+use rocket::http::ContentType;
+use rocket::{post, serde::json::Json, State};
+use rocket_okapi::openapi;
+use serde_json::json;
+use sqlx::types::Json as SqlJson;
+use sqlx::{Acquire, PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::audit::{self, decrypt_credentials, AuditActor, CredentialAccess, CredentialPurpose};
+use crate::config::Config;
+use crate::crypto::{decrypt_secret, encrypt_secret};
+use crate::gateway::validate_connector;
+use crate::transfer::{from_csv, to_csv};
+use crate::types::{
+    ExportAccount, ExportBundle, ExportCsvRequest, ExportRequest, ExportStrategy, ExportUser, ImportAction,
+    ImportCsvRequest, ImportItem, ImportReport, ImportRequest, StrategyConfig, TransferEntity,
+};
+use crate::web::audit_error;
+use crate::web::guards::scopes::DataTransfer;
+use crate::web::guards::TokenGuard;
+
+/// Читает пользователей, их аккаунты и стратегии; секреты при необходимости перешифровываются `export_key`
+async fn load_bundle(
+    pool: &PgPool,
+    config: &Config,
+    actor: &AuditActor,
+    user_uids: Option<&[Uuid]>,
+    export_key: Option<&str>,
+) -> Result<ExportBundle, Json<String>> {
+    let users = sqlx::query_as!(
+        ExportUser,
+        "SELECT id AS user_uid, user_telegram_id, created_at FROM users
+         WHERE ($1::uuid[] IS NULL OR id = ANY($1))
+         ORDER BY created_at, id",
+        user_uids
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| Json(format!("Database error: {e}")))?;
+
+    let mut accounts = sqlx::query_as!(
+        ExportAccount,
+        "SELECT id AS account_uid, user_id AS user_uid, exchange, label, connector, api_key, encrypted_secret, created_at
+         FROM exchange_accounts
+         WHERE ($1::uuid[] IS NULL OR user_id = ANY($1))
+         ORDER BY created_at, id",
+        user_uids
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| Json(format!("Database error: {e}")))?;
+
+    let strategies = sqlx::query!(
+        r#"SELECT id AS strategy_uid, user_id AS user_uid, account_id AS account_uid, strategy_name,
+                  enabled, published, config AS "config: SqlJson<StrategyConfig>", webhook_token, created_at
+           FROM strategies
+           WHERE ($1::uuid[] IS NULL OR user_id = ANY($1))
+           ORDER BY created_at, id"#,
+        user_uids
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| Json(format!("Database error: {e}")))?
+    .into_iter()
+    .map(|row| ExportStrategy {
+        strategy_uid: row.strategy_uid,
+        user_uid: row.user_uid,
+        account_uid: row.account_uid,
+        strategy_name: row.strategy_name,
+        enabled: row.enabled,
+        published: row.published,
+        config: row.config.0,
+        webhook_token: row.webhook_token,
+        created_at: row.created_at,
+    })
+    .collect();
+
+    // Обращения к ключам и запись о выгрузке сохраняются вместе
+    let mut tx = pool.begin().await.map_err(|e| Json(format!("Transaction error: {e}")))?;
+
+    if let Some(export_key) = export_key {
+        for account in &mut accounts {
+            let access = CredentialAccess {
+                user_uid: account.user_uid,
+                account_uid: account.account_uid,
+                purpose: CredentialPurpose::Transfer,
+                strategy_uid: None,
+                signal_id: None,
+            };
+            let secret = decrypt_credentials(&mut *tx, actor, access, &account.encrypted_secret, &config.salt_key)
+                .await
+                .map_err(|e| Json(format!("Account {}: {e}", account.account_uid)))?;
+            account.encrypted_secret =
+                encrypt_secret(&secret, export_key).map_err(|e| Json(format!("Encryption error: {e}")))?;
+        }
+    }
+
+    let bundle = ExportBundle { secrets_wrapped: export_key.is_some(), users, accounts, strategies };
+
+    let summary = json!({
+        "users": bundle.users.len(),
+        "accounts": bundle.accounts.len(),
+        "strategies": bundle.strategies.len(),
+        "secretsWrapped": bundle.secrets_wrapped,
+    });
+    audit::record(&mut tx, actor, "data.export", "bundle", None, None, Some(summary))
+        .await
+        .map_err(audit_error)?;
+    tx.commit().await.map_err(|e| Json(format!("Commit error: {e}")))?;
+
+    Ok(bundle)
+}
+
+/// **POST /api/export** — Выгрузка пользователей, аккаунтов и стратегий в JSON
+///
+/// Без `exportKey` секреты остаются зашифрованными `SALT_KEY` этого окружения.
+#[openapi(tag = "Data Transfer")]
+#[post("/export", format = "json", data = "<export_data>")]
+pub async fn export_json(
+    pool: &State<PgPool>,
+    config: &State<Config>,
+    _auth: TokenGuard<DataTransfer>,
+    actor: AuditActor,
+    export_data: Json<ExportRequest>,
+) -> Result<Json<ExportBundle>, Json<String>> {
+    let bundle = load_bundle(
+        pool.inner(),
+        config.inner(),
+        &actor,
+        export_data.user_uids.as_deref(),
+        export_data.export_key.as_deref(),
+    )
+    .await?;
+
+    Ok(Json(bundle))
+}
+
+/// **POST /api/export/csv** — Выгрузка одной сущности в CSV
+#[openapi(tag = "Data Transfer")]
+#[post("/export/csv", format = "json", data = "<export_data>")]
+pub async fn export_csv(
+    pool: &State<PgPool>,
+    config: &State<Config>,
+    _auth: TokenGuard<DataTransfer>,
+    actor: AuditActor,
+    export_data: Json<ExportCsvRequest>,
+) -> Result<(ContentType, String), Json<String>> {
+    // Секреты нужны только в выгрузке аккаунтов
+    let export_key = match export_data.entity {
+        TransferEntity::Accounts => export_data.export_key.as_deref(),
+        _ => None,
+    };
+    let bundle =
+        load_bundle(pool.inner(), config.inner(), &actor, export_data.user_uids.as_deref(), export_key).await?;
+
+    Ok((ContentType::CSV, to_csv(&bundle, export_data.entity)))
+}
+
+/// `(xmax = 0)` в `RETURNING` отличает вставку от обновления; отсутствие строки — данные не изменились
+fn upsert_action(inserted: Option<bool>) -> ImportAction {
+    match inserted {
+        Some(true) => ImportAction::Created,
+        Some(false) => ImportAction::Updated,
+        None => ImportAction::Unchanged,
+    }
+}
+
+async fn import_user(conn: &mut PgConnection, user: &ExportUser) -> Result<ImportAction, String> {
+    let inserted = sqlx::query_scalar!(
+        r#"INSERT INTO users (id, user_telegram_id, created_at) VALUES ($1, $2, $3)
+           ON CONFLICT (id) DO UPDATE SET user_telegram_id = EXCLUDED.user_telegram_id
+           WHERE users.user_telegram_id IS DISTINCT FROM EXCLUDED.user_telegram_id
+           RETURNING (xmax::text = '0') AS "inserted!""#,
+        user.user_uid,
+        user.user_telegram_id,
+        user.created_at
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(|e| format!("Database error: {e}"))?;
+
+    Ok(upsert_action(inserted))
+}
+
+async fn import_account(
+    conn: &mut PgConnection,
+    config: &Config,
+    actor: &AuditActor,
+    account: &ExportAccount,
+    import_key: Option<&str>,
+) -> Result<ImportAction, String> {
+    validate_connector(&account.exchange, &account.connector)?;
+
+    let existing = sqlx::query!(
+        "SELECT user_id, encrypted_secret FROM exchange_accounts WHERE id = $1",
+        account.account_uid
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| format!("Database error: {e}"))?;
+
+    if existing.as_ref().is_some_and(|e| e.user_id != account.user_uid) {
+        return Err("Account belongs to another user".to_string());
+    }
+
+    let encrypted_secret = match import_key {
+        None => account.encrypted_secret.clone(),
+        Some(import_key) => {
+            // Расшифровывается файл выгрузки, а не сохранённые ключи, поэтому напрямую
+            let secret = decrypt_secret(&account.encrypted_secret, import_key)
+                .map_err(|e| format!("Cannot unwrap secret with importKey: {e}"))?;
+
+            // Если секрет не изменился, остаётся прежний шифротекст, чтобы повторный импорт ничего не менял
+            let unchanged = match &existing {
+                Some(existing) => {
+                    let access = CredentialAccess {
+                        user_uid: account.user_uid,
+                        account_uid: account.account_uid,
+                        purpose: CredentialPurpose::Transfer,
+                        strategy_uid: None,
+                        signal_id: None,
+                    };
+                    decrypt_credentials(&mut *conn, actor, access, &existing.encrypted_secret, &config.salt_key)
+                        .await
+                        .is_ok_and(|current| current == secret)
+                }
+                None => false,
+            };
+
+            match existing {
+                Some(existing) if unchanged => existing.encrypted_secret,
+                _ => encrypt_secret(&secret, &config.salt_key).map_err(|e| format!("Encryption error: {e}"))?,
+            }
+        }
+    };
+
+    let inserted = sqlx::query_scalar!(
+        r#"INSERT INTO exchange_accounts (id, user_id, exchange, label, api_key, encrypted_secret, created_at, connector)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+           ON CONFLICT (id) DO UPDATE SET
+               exchange = EXCLUDED.exchange,
+               label = EXCLUDED.label,
+               api_key = EXCLUDED.api_key,
+               encrypted_secret = EXCLUDED.encrypted_secret,
+               connector = EXCLUDED.connector
+           WHERE (exchange_accounts.exchange, exchange_accounts.label, exchange_accounts.api_key,
+                  exchange_accounts.encrypted_secret, exchange_accounts.connector)
+                 IS DISTINCT FROM (EXCLUDED.exchange, EXCLUDED.label, EXCLUDED.api_key, EXCLUDED.encrypted_secret,
+                                   EXCLUDED.connector)
+           RETURNING (xmax::text = '0') AS "inserted!""#,
+        account.account_uid,
+        account.user_uid,
+        account.exchange,
+        account.label,
+        account.api_key,
+        encrypted_secret,
+        account.created_at,
+        account.connector
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(|e| format!("Database error: {e}"))?;
+
+    Ok(upsert_action(inserted))
+}
+
+async fn import_strategy(conn: &mut PgConnection, strategy: &ExportStrategy) -> Result<ImportAction, String> {
+    strategy.config.validate()?;
+
+    let owner = sqlx::query_scalar!("SELECT user_id FROM strategies WHERE id = $1", strategy.strategy_uid)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| format!("Database error: {e}"))?;
+    if owner.is_some_and(|owner| owner != strategy.user_uid) {
+        return Err("Strategy belongs to another user".to_string());
+    }
+
+    let account_ok = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM exchange_accounts WHERE id = $1 AND user_id = $2)",
+        strategy.account_uid,
+        strategy.user_uid
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| format!("Database error: {e}"))?
+    .unwrap_or(false);
+    if !account_ok {
+        return Err("Account not found for this user".to_string());
+    }
+
+    let inserted = sqlx::query_scalar!(
+        r#"INSERT INTO strategies
+               (id, user_id, account_id, strategy_name, enabled, published, config, webhook_token, created_at)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+           ON CONFLICT (id) DO UPDATE SET
+               account_id = EXCLUDED.account_id,
+               strategy_name = EXCLUDED.strategy_name,
+               enabled = EXCLUDED.enabled,
+               auto_disabled_at = CASE WHEN EXCLUDED.enabled THEN NULL ELSE strategies.auto_disabled_at END,
+               published = EXCLUDED.published,
+               config = EXCLUDED.config,
+               webhook_token = EXCLUDED.webhook_token,
+               config_version = CASE WHEN strategies.config IS DISTINCT FROM EXCLUDED.config
+                                     THEN strategies.config_version + 1
+                                     ELSE strategies.config_version END
+           WHERE (strategies.account_id, strategies.strategy_name, strategies.enabled, strategies.published,
+                  strategies.config, strategies.webhook_token)
+                 IS DISTINCT FROM (EXCLUDED.account_id, EXCLUDED.strategy_name, EXCLUDED.enabled,
+                                   EXCLUDED.published, EXCLUDED.config, EXCLUDED.webhook_token)
+           RETURNING (xmax::text = '0') AS "inserted!""#,
+        strategy.strategy_uid,
+        strategy.user_uid,
+        strategy.account_uid,
+        strategy.strategy_name,
+        strategy.enabled,
+        strategy.published,
+        SqlJson(&strategy.config) as _,
+        strategy.webhook_token,
+        strategy.created_at
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| format!("Database error: {e}"))?;
+
+    sqlx::query!(
+        "INSERT INTO strategy_config_versions (strategy_id, version, config)
+         SELECT id, config_version, config FROM strategies WHERE id = $1
+         ON CONFLICT DO NOTHING",
+        strategy.strategy_uid
+    )
+    .execute(conn)
+    .await
+    .map_err(|e| format!("Database error: {e}"))?;
+
+    Ok(upsert_action(inserted))
+}
+
+/// Загружает выгрузку в одной транзакции; каждая строка — в своей точке сохранения,
+/// так что ошибка в строке не мешает остальным. При `dry_run` транзакция откатывается
+/// вместе с записями об обращении к ключам.
+async fn import_bundle(
+    pool: &PgPool,
+    config: &Config,
+    actor: &AuditActor,
+    bundle: &ExportBundle,
+    import_key: Option<&str>,
+    dry_run: bool,
+) -> Result<ImportReport, Json<String>> {
+    if bundle.secrets_wrapped && import_key.is_none() && !bundle.accounts.is_empty() {
+        return Err(Json("importKey is required: secrets are wrapped with an export key".to_string()));
+    }
+    if !bundle.secrets_wrapped && import_key.is_some() {
+        return Err(Json("importKey is set, but secrets in the bundle are not wrapped".to_string()));
+    }
+
+    let mut items = Vec::new();
+    let mut tx = pool.begin().await.map_err(|e| Json(format!("Transaction error: {e}")))?;
+
+    macro_rules! import_row {
+        ($entity:expr, $uid:expr, $conn:ident => $import:expr) => {{
+            let mut savepoint = tx.begin().await.map_err(|e| Json(format!("Transaction error: {e}")))?;
+            let $conn: &mut PgConnection = &mut savepoint;
+            let result = $import.await;
+            let (action, error) = match result {
+                Ok(action) => {
+                    savepoint.commit().await.map_err(|e| Json(format!("Commit error: {e}")))?;
+                    (action, None)
+                }
+                Err(e) => {
+                    savepoint.rollback().await.map_err(|e| Json(format!("Rollback error: {e}")))?;
+                    (ImportAction::Failed, Some(e))
+                }
+            };
+            items.push(ImportItem { entity: $entity, uid: $uid, action, error });
+        }};
+    }
+
+    for user in &bundle.users {
+        import_row!(TransferEntity::Users, user.user_uid, conn => import_user(conn, user));
+    }
+    for account in &bundle.accounts {
+        import_row!(TransferEntity::Accounts, account.account_uid, conn => {
+            import_account(conn, config, actor, account, import_key)
+        });
+    }
+    for strategy in &bundle.strategies {
+        import_row!(TransferEntity::Strategies, strategy.strategy_uid, conn => import_strategy(conn, strategy));
+    }
+
+    let count = |action: ImportAction| items.iter().filter(|item| item.action == action).count();
+    let report = ImportReport {
+        dry_run,
+        created: count(ImportAction::Created),
+        updated: count(ImportAction::Updated),
+        unchanged: count(ImportAction::Unchanged),
+        failed: count(ImportAction::Failed),
+        items,
+    };
+
+    if dry_run {
+        tx.rollback().await.map_err(|e| Json(format!("Rollback error: {e}")))?;
+        return Ok(report);
+    }
+
+    let summary = json!({
+        "created": report.created,
+        "updated": report.updated,
+        "unchanged": report.unchanged,
+        "failed": report.failed,
+    });
+    audit::record(&mut tx, actor, "data.import", "bundle", None, None, Some(summary))
+        .await
+        .map_err(audit_error)?;
+
+    tx.commit().await.map_err(|e| Json(format!("Commit error: {e}")))?;
+
+    Ok(report)
+}
+
+/// **POST /api/import** — Загрузка JSON-выгрузки
+///
+/// Строки сопоставляются по uid: отсутствующие создаются, отличающиеся обновляются,
+/// поэтому повторная загрузка того же файла ничего не меняет. `dryRun` возвращает отчёт без изменений.
+#[openapi(tag = "Data Transfer")]
+#[post("/import", format = "json", data = "<import_data>")]
+pub async fn import_json(
+    pool: &State<PgPool>,
+    config: &State<Config>,
+    _auth: TokenGuard<DataTransfer>,
+    actor: AuditActor,
+    import_data: Json<ImportRequest>,
+) -> Result<Json<ImportReport>, Json<String>> {
+    let report = import_bundle(
+        pool.inner(),
+        config.inner(),
+        &actor,
+        &import_data.bundle,
+        import_data.import_key.as_deref(),
+        import_data.dry_run,
+    )
+    .await?;
+
+    Ok(Json(report))
+}
+
+/// **POST /api/import/csv** — Загрузка CSV одной сущности
+///
+/// Пользователи, аккаунты и стратегии загружаются отдельными запросами именно в этом порядке.
+#[openapi(tag = "Data Transfer")]
+#[post("/import/csv", format = "json", data = "<import_data>")]
+pub async fn import_csv(
+    pool: &State<PgPool>,
+    config: &State<Config>,
+    _auth: TokenGuard<DataTransfer>,
+    actor: AuditActor,
+    import_data: Json<ImportCsvRequest>,
+) -> Result<Json<ImportReport>, Json<String>> {
+    let bundle = from_csv(&import_data.csv, import_data.entity, import_data.import_key.is_some()).map_err(Json)?;
+
+    let report = import_bundle(
+        pool.inner(),
+        config.inner(),
+        &actor,
+        &bundle,
+        import_data.import_key.as_deref(),
+        import_data.dry_run,
+    )
+    .await?;
+
+    Ok(Json(report))
+}
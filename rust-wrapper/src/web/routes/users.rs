@@ -1,42 +1,75 @@
 use rocket::delete;
 use rocket::{get, post, put, serde::json::Json, State};
 use rocket_okapi::openapi;
-use sqlx::{PgPool, Row};
+use chrono::NaiveDateTime;
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder};
+use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::audit::{self, AuditActor};
 use crate::crypto::encrypt_secret;
-use crate::types::{RegisterUserRequest, RegisterUserResponse, UpdateUserRequest, User};
-use crate::web::guards::AdminGuard;
+use crate::types::{
+    ExchangeAccount, Page, RegisterUserRequest, RegisterUserResponse, UpdateUserRequest, User, UserSortField,
+    UsersQuery,
+};
+use crate::web::audit_error;
+use crate::web::pagination::{clamp_limit, finish_page, push_created_range, push_page, Cursor, SortColumn};
+use crate::web::guards::scopes::{UsersDelete, UsersRead, UsersWrite};
+use crate::web::guards::{Caller, TokenGuard};
 use crate::config::Config;
 
-/// **POST /api/user** — Регистрация пользователя**
+/// **POST /api/user** — Регистрация пользователя и его первого биржевого аккаунта**
 #[openapi(tag = "User Management")]
 #[post("/user", format = "json", data = "<user_data>")]
 pub async fn register_user(
     pool: &State<PgPool>,
     config: &State<Config>,
-    _admin: AdminGuard,
+    _auth: TokenGuard<UsersWrite>,
+    actor: AuditActor,
     user_data: Json<RegisterUserRequest>,
 ) -> Result<Json<RegisterUserResponse>, Json<String>> {
     let user_uid = Uuid::new_v4();
+    let account_uid = Uuid::new_v4();
 
     let encrypted_secret = encrypt_secret(&user_data.secret_key, &config.salt_key)
         .map_err(|e| Json(format!("Encryption error: {e}")))?;
 
+    let mut tx = pool.inner().begin().await.map_err(|e| Json(format!("Transaction error: {e}")))?;
+
+    sqlx::query!(
+        "INSERT INTO users (id, user_telegram_id) VALUES ($1, $2)",
+        user_uid,
+        user_data.user_telegram_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| Json(format!("Database error: {:?}", e)))?;
+
     sqlx::query!(
-        "INSERT INTO users (id, user_telegram_id, api_key, encrypted_secret, exchange) 
+        "INSERT INTO exchange_accounts (id, user_id, exchange, api_key, encrypted_secret)
          VALUES ($1, $2, $3, $4, $5)",
+        account_uid,
         user_uid,
-        user_data.user_telegram_id,
+        user_data.exchange,
         user_data.api_key,
-        encrypted_secret,
-        user_data.exchange
+        encrypted_secret
     )
-    .execute(pool.inner())
+    .execute(&mut *tx)
     .await
     .map_err(|e| Json(format!("Database error: {:?}", e)))?;
 
-    Ok(Json(RegisterUserResponse { user_uid }))
+    let user_after = audit::snapshot(&mut tx, "users", user_uid).await.map_err(audit_error)?;
+    audit::record(&mut tx, &actor, "user.create", "user", Some(user_uid), None, user_after)
+        .await
+        .map_err(audit_error)?;
+    let account_after = audit::snapshot(&mut tx, "exchange_accounts", account_uid).await.map_err(audit_error)?;
+    audit::record(&mut tx, &actor, "account.create", "account", Some(account_uid), None, account_after)
+        .await
+        .map_err(audit_error)?;
+
+    tx.commit().await.map_err(|e| Json(format!("Commit error: {e}")))?;
+
+    Ok(Json(RegisterUserResponse { user_uid, account_uid }))
 }
 
 /// **GET /api/user/<user_uid>** — Получение информации о пользователе**
@@ -44,100 +77,186 @@ pub async fn register_user(
 #[get("/user/<user_uid>")]
 pub async fn get_user(
     pool: &State<PgPool>,
-    _admin: AdminGuard,
+    _auth: TokenGuard<UsersRead>,
     user_uid: Uuid,
 ) -> Result<Json<User>, Json<String>> {
+    load_user(pool.inner(), user_uid).await.map(Json)
+}
+
+/// **GET /api/me** — Пользователь, от имени которого открыт Telegram mini-app**
+#[openapi(tag = "User Management")]
+#[get("/me")]
+pub async fn get_me(
+    pool: &State<PgPool>,
+    caller: Caller<UsersRead>,
+) -> Result<Json<User>, Json<String>> {
+    let Some(user_uid) = caller.owner_filter() else {
+        return Err(Json("Available only for Telegram users".to_string()));
+    };
+
+    load_user(pool.inner(), user_uid).await.map(Json)
+}
+
+async fn load_user(pool: &PgPool, user_uid: Uuid) -> Result<User, Json<String>> {
     let result = sqlx::query!(
-        "SELECT id, user_telegram_id, exchange FROM users WHERE id = $1",
+        "SELECT id, user_telegram_id, created_at FROM users WHERE id = $1",
         user_uid
     )
-    .fetch_one(pool.inner())
+    .fetch_one(pool)
     .await
     .map_err(|_| Json("User not found".to_string()))?;
 
-    Ok(Json(User {
+    let accounts = sqlx::query_as!(
+        ExchangeAccount,
+        "SELECT id AS account_uid, user_id AS user_uid, exchange, label, connector, created_at
+         FROM exchange_accounts WHERE user_id = $1 ORDER BY created_at",
+        user_uid
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|_| Json("Failed to fetch accounts".to_string()))?;
+
+    Ok(User {
         id: result.id,
         user_telegram_id: result.user_telegram_id,
-        exchange: result.exchange,
-    }))
+        created_at: result.created_at,
+        accounts,
+    })
 }
 
-/// **GET /api/users** — Получение всех пользователей**
+#[derive(FromRow)]
+struct UserRow {
+    id: Uuid,
+    user_telegram_id: i64,
+    created_at: NaiveDateTime,
+    cursor_key: String,
+}
+
+fn user_sort_column(sort: UserSortField) -> SortColumn {
+    match sort {
+        UserSortField::CreatedAt => SortColumn { expr: "users.created_at", sql_type: "timestamp" },
+        UserSortField::TelegramId => SortColumn { expr: "users.user_telegram_id", sql_type: "bigint" },
+    }
+}
+
+/// **GET /api/users** — Получение пользователей (постранично)**
 #[openapi(tag = "User Management")]
-#[get("/users")]
+#[get("/users?<query..>")]
 pub async fn get_all_users(
     pool: &State<PgPool>,
-    _admin: AdminGuard,
-) -> Result<Json<Vec<User>>, Json<String>> {
-    let result = sqlx::query!(
-        "SELECT id, user_telegram_id, exchange FROM users"
+    _auth: TokenGuard<UsersRead>,
+    query: UsersQuery,
+) -> Result<Json<Page<User>>, Json<String>> {
+    let limit = clamp_limit(query.limit);
+    let sort = user_sort_column(query.sort.unwrap_or_default());
+
+    let mut qb = QueryBuilder::<Postgres>::new(format!(
+        "SELECT users.id, users.user_telegram_id, users.created_at, {}::text AS cursor_key
+         FROM users WHERE TRUE",
+        sort.expr
+    ));
+    if let Some(exchange) = &query.exchange {
+        qb.push(" AND EXISTS (SELECT 1 FROM exchange_accounts WHERE exchange_accounts.user_id = users.id AND exchange_accounts.exchange = ")
+            .push_bind(exchange)
+            .push(")");
+    }
+    push_created_range(&mut qb, "users.created_at", query.created_from.as_deref(), query.created_to.as_deref())
+        .map_err(Json)?;
+    push_page(&mut qb, &sort, "users.id", query.order.unwrap_or_default(), query.cursor.as_deref(), limit)
+        .map_err(Json)?;
+
+    let rows: Vec<UserRow> = qb
+        .build_query_as()
+        .fetch_all(pool.inner())
+        .await
+        .map_err(|_| Json("Failed to fetch users".to_string()))?;
+
+    let (rows, page) = finish_page(rows, limit, |row| Cursor { key: row.cursor_key.clone(), id: row.id });
+
+    let user_uids: Vec<Uuid> = rows.iter().map(|row| row.id).collect();
+    let accounts = sqlx::query_as!(
+        ExchangeAccount,
+        "SELECT id AS account_uid, user_id AS user_uid, exchange, label, connector, created_at
+         FROM exchange_accounts WHERE user_id = ANY($1) ORDER BY created_at",
+        &user_uids
     )
     .fetch_all(pool.inner())
     .await
-    .map_err(|_| Json("Failed to fetch users".to_string()))?;
+    .map_err(|_| Json("Failed to fetch accounts".to_string()))?;
 
-    let users: Vec<User> = result
-        .iter()
+    let mut accounts_by_user: HashMap<Uuid, Vec<ExchangeAccount>> = HashMap::new();
+    for account in accounts {
+        accounts_by_user.entry(account.user_uid).or_default().push(account);
+    }
+
+    let items: Vec<User> = rows
+        .into_iter()
         .map(|user| User {
             id: user.id,
             user_telegram_id: user.user_telegram_id,
-            exchange: user.exchange.clone(),
+            created_at: user.created_at,
+            accounts: accounts_by_user.remove(&user.id).unwrap_or_default(),
         })
         .collect();
 
-    Ok(Json(users))
+    Ok(Json(Page { items, page }))
 }
 
 /// **PUT /api/user/<user_uid>** — Обновление пользователя**
+///
+/// Ключи бирж обновляются через `PUT /api/account/<account_uid>`.
 #[openapi(tag = "User Management")]
 #[put("/user/<user_uid>", format = "json", data = "<update_data>")]
 pub async fn update_user(
     pool: &State<PgPool>,
-    config: &State<Config>,
-    _admin: AdminGuard,
+    _auth: TokenGuard<UsersWrite>,
+    actor: AuditActor,
     user_uid: Uuid,
     update_data: Json<UpdateUserRequest>,
 ) -> Result<Json<String>, Json<String>> {
-    let encrypted_secret = if let Some(secret) = &update_data.secret_key {
-        Some(encrypt_secret(secret, &config.salt_key).map_err(|e| Json(format!("Encryption error: {e}")))?)
-    } else {
-        None
-    };
-
     let mut tx = pool.inner().begin().await.map_err(|e| Json(format!("Transaction error: {e}")))?;
 
-    sqlx::query!(
-        "UPDATE users 
-         SET api_key = $1, 
-             encrypted_secret = COALESCE($2, encrypted_secret),
-             exchange = $3
-         WHERE id = $4",
-        update_data.api_key,
-        encrypted_secret,
-        update_data.exchange,
+    let before = audit::snapshot(&mut tx, "users", user_uid).await.map_err(audit_error)?;
+
+    let updated = sqlx::query!(
+        "UPDATE users SET user_telegram_id = $1 WHERE id = $2",
+        update_data.user_telegram_id,
         user_uid
     )
     .execute(&mut *tx)
     .await
-    .map_err(|e| Json(format!("Database error: {:?}", e)))?;
+    .map_err(|e| Json(format!("Database error: {:?}", e)))?
+    .rows_affected();
+
+    if updated == 0 {
+        return Err(Json("User not found".to_string()));
+    }
+
+    let after = audit::snapshot(&mut tx, "users", user_uid).await.map_err(audit_error)?;
+    audit::record(&mut tx, &actor, "user.update", "user", Some(user_uid), before, after)
+        .await
+        .map_err(audit_error)?;
 
     tx.commit().await.map_err(|e| Json(format!("Commit error: {e}")))?;
 
     Ok(Json("User updated successfully".to_string()))
 }
 
-
-
 /// **DELETE /api/user/{userUid}** — Удаление пользователя и всех его стратегий
 #[openapi(tag = "User Management")]
 #[delete("/user/<user_uid>")]
 pub async fn delete_user(
     pool: &State<PgPool>,
-    _admin: AdminGuard,
+    _auth: TokenGuard<UsersDelete>,
+    actor: AuditActor,
     user_uid: Uuid,
 ) -> Result<Json<String>, Json<String>> {
+    let mut tx = pool.inner().begin().await.map_err(|e| Json(format!("Transaction error: {e}")))?;
+
+    let before = audit::snapshot(&mut tx, "users", user_uid).await.map_err(audit_error)?;
+
     let deleted = sqlx::query!("DELETE FROM users WHERE id = $1", user_uid)
-        .execute(pool.inner())
+        .execute(&mut *tx)
         .await
         .map_err(|e| Json(format!("Database error: {:?}", e)))?
         .rows_affected();
@@ -146,5 +265,12 @@ pub async fn delete_user(
         return Err(Json("User not found".to_string()));
     }
 
+    audit::record(&mut tx, &actor, "user.delete", "user", Some(user_uid), before, None)
+        .await
+        .map_err(audit_error)?;
+
+    tx.commit().await.map_err(|e| Json(format!("Commit error: {e}")))?;
+
     Ok(Json("User deleted successfully".to_string()))
 }
+
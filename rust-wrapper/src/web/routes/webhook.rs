@@ -1,61 +1,783 @@
 use rocket::{post, serde::json::Json, State};
 use rocket_okapi::openapi;
 use serde_json::json;
+use sqlx::types::Json as SqlJson;
 use sqlx::PgPool;
 use uuid::Uuid;
 use async_nats::Client;
 use tokio::sync::Mutex;
 use std::sync::Arc;
+use std::time::Duration;
 
-use crate::crypto::decrypt_secret;
+use crate::audit::{decrypt_credentials, AuditActor, CredentialAccess, CredentialPurpose};
 use crate::config::Config;
-use crate::types::TradingViewSignal;
+use crate::fills::{FillEvent, FILLS_TOPIC, SIGNALS_TOPIC};
+use crate::gateway::{
+    capabilities, validate_order, ExchangeCredentials, ExchangeGateway, MarketType, OrderKind, OrderSide, PaperExchange,
+    PositionSettings, PositionSettingsCache, SharedGateway, TradeRequest, PAPER_EXCHANGE,
+};
+use crate::market_data::QuoteBook;
+use crate::markets::MarketCache;
+use crate::positions::position_quantity;
+use crate::types::{
+    DeviationAction, PositionAction, SignalMode, SizingMode, SlippageDecision, StrategyConfig, StrategyOrderType,
+    TradingViewSignal,
+};
+use crate::web::guards::RequestMeta;
 
-/// **POST /webhook/<strategy_uid>**  
+/// Котировка старше этого не годится для проверки отклонения цены
+const QUOTE_MAX_AGE: Duration = Duration::from_secs(30);
+
+/// Относительный допуск, в пределах которого позиция уже равна целевой
+const POSITION_EPSILON: f64 = 1e-9;
+
+/// Зависимости конвейера сигналов, которым не место в отдельных параметрах обработчика
+pub struct SignalServices {
+    pub paper: Arc<PaperExchange>,
+    /// Фильтры пар: ордер округляется или отклоняется до публикации в NATS
+    pub markets: Arc<MarketCache>,
+    /// Текущие котировки — ориентир цены для рыночных сигналов без цены
+    pub quotes: Arc<QuoteBook>,
+    /// Шлюз для настройки плеча и режима маржи перед открытием позиции
+    pub gateway: SharedGateway,
+    pub position_settings: PositionSettingsCache,
+}
+
+/// Аккаунт, на котором исполняется сигнал: автора стратегии или подписчика
+struct SignalTarget {
+    subscription_uid: Option<Uuid>,
+    user_id: Uuid,
+    account_id: Uuid,
+    api_key: String,
+    encrypted_secret: String,
+    exchange: String,
+    connector: String,
+    action: PositionAction,
+    amount: f64,
+}
+
+/// Что сигнал делает с позицией. `signal` — явное действие (`open_long`, `close_short`, ...) или сторона;
+/// для стороны действие выводится из `marketPosition`/`prevMarketPosition` TradingView:
+/// продажа в шорт открывает шорт, покупка из шорта в ноль закрывает его. Без позиций покупка
+/// открывает лонг, продажа закрывает его — как у спотовых стратегий.
+fn position_action(payload: &TradingViewSignal) -> Result<PositionAction, String> {
+    let signal = payload.signal.to_lowercase();
+    if let Some(action) = PositionAction::parse(&signal) {
+        return Ok(action);
+    }
+
+    let position = payload.market_position.as_deref().map(str::to_lowercase);
+    let prev_position = payload.prev_market_position.as_deref().map(str::to_lowercase);
+    match signal.as_str() {
+        "buy" if position.as_deref() == Some("flat") && prev_position.as_deref() == Some("short") => {
+            Ok(PositionAction::CloseShort)
+        }
+        "buy" => Ok(PositionAction::OpenLong),
+        "sell" if position.as_deref() == Some("short") => Ok(PositionAction::OpenShort),
+        "sell" => Ok(PositionAction::CloseLong),
+        other => Err(format!("Invalid signal side: {other}")),
+    }
+}
+
+/// Объём ордера в режиме `delta`: объём автора до множителя подписчика
+fn signal_amount(config: &StrategyConfig, payload: &TradingViewSignal) -> Result<f64, String> {
+    let contracts = || {
+        payload
+            .contracts
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid contracts value: {}", payload.contracts))
+    };
+
+    let amount = match (config.sizing_mode, config.sizing_value) {
+        (SizingMode::Fixed, Some(value)) => value,
+        (SizingMode::Multiplier, Some(value)) => contracts()? * value,
+        _ => contracts()?,
+    };
+    if amount <= 0.0 {
+        return Err("Order amount must be positive".to_string());
+    }
+    if let Some(max) = config.max_position {
+        if amount > max {
+            return Err(format!("Order amount {amount} exceeds maxPosition {max}"));
+        }
+    }
+    Ok(amount)
+}
+
+/// Желаемая позиция автора в режиме `target_position`: плюс — лонг, минус — шорт
+fn target_position(config: &StrategyConfig, payload: &TradingViewSignal) -> Result<f64, String> {
+    let contracts = payload
+        .contracts
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| format!("Invalid contracts value: {}", payload.contracts))?;
+    // `strategy.market_position_size` приходит без знака — направление тогда из `marketPosition`
+    let short = payload.market_position.as_deref().is_some_and(|position| position.eq_ignore_ascii_case("short"));
+    let contracts = if short && contracts > 0.0 { -contracts } else { contracts };
+
+    let target = match (config.sizing_mode, config.sizing_value) {
+        _ if contracts == 0.0 => 0.0,
+        (SizingMode::Fixed, Some(value)) => value.copysign(contracts),
+        (SizingMode::Multiplier, Some(value)) => contracts * value,
+        _ => contracts,
+    };
+    if let Some(max) = config.max_position {
+        if target.abs() > max {
+            return Err(format!("Target position {target} exceeds maxPosition {max}"));
+        }
+    }
+    Ok(target)
+}
+
+/// Действие, переводящее позицию из `current` в `target`; переворот (лонг → шорт и наоборот) —
+/// одно открытие на всю разницу. `None` — позиция уже целевая.
+fn target_action(current: f64, target: f64) -> Option<PositionAction> {
+    let delta = target - current;
+    if delta.abs() <= POSITION_EPSILON * current.abs().max(target.abs()).max(1.0) {
+        return None;
+    }
+    Some(if delta > 0.0 {
+        if current < 0.0 && target <= 0.0 { PositionAction::CloseShort } else { PositionAction::OpenLong }
+    } else if current > 0.0 && target >= 0.0 {
+        PositionAction::CloseLong
+    } else {
+        PositionAction::OpenShort
+    })
+}
+
+/// Закрытие позиции на фьючерсах не должно развернуть её в обратную
+fn reduce_only(config: &StrategyConfig, market_type: MarketType, action: PositionAction) -> bool {
+    config.reduce_only || (market_type == MarketType::Perpetual && action.is_close())
+}
+
+/// Сторона ордера для действия с позицией
+fn action_side(action: PositionAction) -> OrderSide {
+    match action {
+        PositionAction::OpenLong | PositionAction::CloseShort => OrderSide::Buy,
+        PositionAction::CloseLong | PositionAction::OpenShort => OrderSide::Sell,
+    }
+}
+
+/// Ордер по сигналу с учётом настроек стратегии, без `clientOrderId`
+fn order_request(
+    config: &StrategyConfig,
+    payload: &TradingViewSignal,
+    action: PositionAction,
+    amount: f64,
+) -> Result<TradeRequest, String> {
+    let side = action_side(action);
+    // `orderType` сигнала задаёт только тип ордера: рынок определяет стратегия
+    let market_type = config.market_type.unwrap_or_default();
+    if market_type == MarketType::Spot && action.is_short() {
+        return Err("Short positions require marketType perpetual or margin".to_string());
+    }
+
+    let signal_price = if payload.order_price == "market" {
+        None
+    } else {
+        Some(
+            payload
+                .order_price
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid orderPrice value: {}", payload.order_price))?,
+        )
+    };
+
+    // Если тип оставлен сигналу, лимитный или стоп-лимитный ордер нужно запросить явно в `orderType`
+    // (`limit`, `stop_limit`); иначе ордер рыночный, даже если в сигнале есть цена — TradingView присылает её всегда
+    let kind = match config.order_type {
+        StrategyOrderType::Signal => OrderKind::parse(&payload.order_type.to_lowercase()).unwrap_or(OrderKind::Market),
+        StrategyOrderType::Market => OrderKind::Market,
+        StrategyOrderType::Limit => OrderKind::Limit,
+        StrategyOrderType::StopLimit => OrderKind::StopLimit,
+    };
+
+    let (price, stop_price) = match kind {
+        OrderKind::Market => (None, None),
+        OrderKind::Limit => (Some(signal_price.ok_or("Strategy requires limit orders, but signal has no price")?), None),
+        OrderKind::StopLimit => {
+            let stop = signal_price.ok_or("Stop-limit orders require a signal price")?;
+            let offset = config.stop_limit_offset_pct / 100.0;
+            let limit = match side {
+                OrderSide::Buy => stop * (1.0 + offset),
+                OrderSide::Sell => stop * (1.0 - offset),
+            };
+            (Some(limit), Some(stop))
+        }
+    };
+
+    Ok(TradeRequest {
+        symbol: payload.ticker.clone(),
+        side,
+        amount,
+        price,
+        order_type: Some(kind),
+        stop_price,
+        time_in_force: config.time_in_force.filter(|_| kind != OrderKind::Market),
+        post_only: config.post_only && kind == OrderKind::Limit,
+        reduce_only: reduce_only(config, market_type, action),
+        client_order_id: None,
+        market_type,
+        margin_mode: Some(config.margin_mode).filter(|_| market_type != MarketType::Spot),
+    })
+}
+
+/// Детерминированный id ордера: повтор того же сигнала на том же аккаунте биржа отклонит как дубликат
+fn client_order_id(strategy_uid: Uuid, signal_id: &str, account_uid: Uuid) -> String {
+    let hash = blake3::hash(format!("{strategy_uid}:{signal_id}:{account_uid}").as_bytes());
+    format!("mw{}", &hash.to_hex()[..30])
+}
+
+/// Итог проверки отклонения живой цены от цены сигнала
+struct SlippageCheck {
+    deviation_pct: Option<f64>,
+    decision: SlippageDecision,
+    /// Цена ордера после проверки (`None` — рыночный)
+    price: Option<f64>,
+}
+
+/// Проверяет рыночный ордер: если живая цена ушла от цены сигнала дальше `maxPriceDeviationPct`,
+/// ордер отклоняется или превращается в лимитный по границе (для покупки выше, для продажи ниже цены сигнала).
+/// Лимитные и стоп-лимитные ордера дороже своей цены не исполнятся, их проверять незачем.
+fn check_slippage(
+    config: &StrategyConfig,
+    order: &TradeRequest,
+    signal_price: Option<f64>,
+    live_price: Option<f64>,
+) -> SlippageCheck {
+    let deviation_pct = signal_price.zip(live_price).map(|(signal, live)| (live - signal) / signal * 100.0);
+    let unchecked = SlippageCheck { deviation_pct, decision: SlippageDecision::Unchecked, price: order.price };
+
+    let (Some(max_pct), Some(deviation), Some(signal), OrderKind::Market) =
+        (config.max_price_deviation_pct, deviation_pct, signal_price, order.kind())
+    else {
+        return unchecked;
+    };
+    if deviation.abs() <= max_pct {
+        return SlippageCheck { decision: SlippageDecision::Passed, ..unchecked };
+    }
+
+    match config.deviation_action {
+        DeviationAction::Reject => SlippageCheck { decision: SlippageDecision::Rejected, ..unchecked },
+        DeviationAction::Limit => {
+            let bound = match order.side {
+                OrderSide::Buy => signal * (1.0 + max_pct / 100.0),
+                OrderSide::Sell => signal * (1.0 - max_pct / 100.0),
+            };
+            SlippageCheck { deviation_pct, decision: SlippageDecision::Limited, price: Some(bound) }
+        }
+    }
+}
+
+/// Исполняет сигнал на бумажном аккаунте и, как внешний исполнитель, сообщает об исполнении в `order-fills`
+async fn execute_paper(
+    nats: &Client,
+    paper: &PaperExchange,
+    credentials: &ExchangeCredentials<'_>,
+    trade: &TradeRequest,
+    strategy_uid: Uuid,
+    subscription_uid: Option<Uuid>,
+) -> Result<(), String> {
+    let order = paper.trade(credentials, trade).await.map_err(|e| e.to_string())?;
+    let fill = FillEvent::from_order(&order, trade, credentials.account_uid, Some(strategy_uid), subscription_uid);
+    let payload = serde_json::to_vec(&fill).map_err(|e| format!("Fill serialization error: {e}"))?;
+    nats.publish(FILLS_TOPIC.to_string(), payload.into())
+        .await
+        .map_err(|e| format!("Error sending to NATS: {e}"))
+}
+
+/// **POST /webhook/<webhook_token>** — Сигнал TradingView
+///
+/// Токен выдаётся при создании стратегии и меняется ротацией; после ротации старый токен
+/// принимается до конца льготного периода.
 #[openapi(tag = "Webhook")]
-#[post("/webhook/<strategy_uid>", format = "json", data = "<payload>")]
+#[post("/webhook/<webhook_token>", format = "json", data = "<payload>")]
 pub async fn webhook_handler(
     pool: &State<PgPool>,
     config: &State<Config>,
     nats_client: &State<Arc<Mutex<Client>>>,
-    strategy_uid: Uuid,
+    services: &State<SignalServices>,
+    meta: RequestMeta,
+    webhook_token: &str,
     payload: Json<TradingViewSignal>,
 ) -> Result<Json<String>, Json<String>> {
-    // 1. Находим стратегию и её пользователя
+    // 1. Находим стратегию и её биржевой аккаунт
     let strategy = sqlx::query!(
-        "SELECT strategies.id, users.api_key, users.encrypted_secret, users.exchange
+        r#"SELECT strategies.id, strategies.published, strategies.config AS "config: SqlJson<StrategyConfig>",
+                strategies.enabled, strategies.auto_disabled_at, exchange_accounts.id AS account_id, exchange_accounts.user_id,
+                exchange_accounts.api_key, exchange_accounts.encrypted_secret, exchange_accounts.exchange,
+                exchange_accounts.connector
          FROM strategies
-         JOIN users ON strategies.user_id = users.id
-         WHERE strategies.id = $1",
-        strategy_uid
+         JOIN exchange_accounts ON strategies.account_id = exchange_accounts.id
+         WHERE strategies.webhook_token = $1
+            OR (strategies.previous_webhook_token = $1 AND strategies.previous_webhook_token_expires_at > now())"#,
+        webhook_token
     )
     .fetch_one(pool.inner())
     .await
     .map_err(|_| Json("Strategy not found".to_string()))?;
 
-    // 2. Расшифровываем secret_key
-    let real_secret = decrypt_secret(&strategy.encrypted_secret, &config.salt_key)
-        .map_err(|e| Json(format!("Decryption error: {e}")))?;
-
-    // 3. Формируем сообщение для NATS
-    let order_data = json!({
-        "exchange": strategy.exchange,
-        "apiKey": strategy.api_key,
-        "secret": real_secret,
-        "order_id": payload.id,
-        "side": payload.signal.to_lowercase(),
-        "symbol": payload.ticker,
-        "amount": payload.contracts.parse::<f64>().unwrap_or(0.0),
-        "price": if payload.order_price == "market" { None } else { Some(payload.order_price.parse::<f64>().unwrap_or(0.0)) },
-        "strategyUid": strategy_uid,
-        "title": payload.title
-    });
-
-    let topic = "trading-signals".to_string();
-    let nats = nats_client.lock().await;
-    nats.publish(topic, serde_json::to_string(&order_data).expect("invalid nats topic order data").into())
+    // Выключенная стратегия сигналы не исполняет; если её остановила сверка с биржей — сообщаем об этом
+    if !strategy.enabled {
+        return Err(Json(match strategy.auto_disabled_at {
+            Some(disabled_at) => format!(
+                "Strategy was disabled by reconciliation at {disabled_at}; re-enable it after checking the account"
+            ),
+            None => "Strategy is disabled".to_string(),
+        }));
+    }
+
+    // 2. Применяем настройки стратегии
+    let settings = &strategy.config.0;
+    if !settings.allows_symbol(&payload.ticker) {
+        return Err(Json(format!("Symbol {} is not allowed for this strategy", payload.ticker)));
+    }
+    let (action, amount, target_size) = match settings.signal_mode {
+        SignalMode::Delta => {
+            (position_action(&payload).map_err(Json)?, signal_amount(settings, &payload).map_err(Json)?, None)
+        }
+        SignalMode::TargetPosition => {
+            let target = target_position(settings, &payload).map_err(Json)?;
+            let market_type = settings.market_type.unwrap_or_default();
+            let current =
+                position_quantity(pool.inner(), strategy.account_id, strategy.id, market_type, &payload.ticker)
+                    .await
+                    .map_err(Json)?;
+            match target_action(current, target) {
+                Some(action) => (action, (target - current).abs(), Some(target)),
+                None => return Ok(Json(format!("Position {current} is already at target, no order placed"))),
+            }
+        }
+    };
+    let mut order = order_request(settings, &payload, action, amount).map_err(Json)?;
+    let side = order.side;
+
+    // Цена сигнала — источник цены бумажной биржи в режиме `last_signal`
+    // и ориентир для проверки минимальной суммы рыночного ордера
+    let signal_price = payload.order_price.parse::<f64>().ok().filter(|price| *price > 0.0);
+    if let Some(signal_price) = signal_price {
+        services.paper.record_signal_price(&payload.ticker, signal_price);
+    }
+    // Котировки есть только у спотовых пар (маржа торгуется по ним же) той биржи, которую транслирует источник.
+    // Для бессрочных фьючерсов поток котировок не собирается, поэтому их отклонение не проверяется
+    // и сигнал записывается как `unchecked`
+    let quote = match order.market_type {
+        MarketType::Spot | MarketType::Margin => services.quotes.fresh(&strategy.exchange, &payload.ticker, QUOTE_MAX_AGE),
+        MarketType::Perpetual => None,
+    };
+    let live_price = quote.as_ref().and_then(|quote| quote.execution_price(side));
+    let reference_price = signal_price.or_else(|| quote.as_ref().and_then(|quote| quote.price()));
+
+    // Решение по отклонению цены записывается вместе с сигналом, в том числе отказ
+    let slippage = check_slippage(settings, &order, signal_price, live_price);
+    let signal_order = order.clone();
+    if slippage.decision == SlippageDecision::Limited {
+        order.order_type = Some(OrderKind::Limit);
+        order.price = slippage.price;
+    }
+    if slippage.decision != SlippageDecision::Rejected {
+        // Ордер автора проверяется до отметки cooldown: сигнал, который не исполнить, не должен её занимать
+        validate_order(&capabilities(&strategy.exchange, &strategy.connector), &order).map_err(Json)?;
+        services
+            .markets
+            .prepare(&strategy.exchange, &order, reference_price)
+            .await
+            .map_err(Json)?;
+
+        // Cooldown отмечается атомарно, чтобы два одновременных сигнала не прошли оба
+        let accepted = sqlx::query!(
+            "UPDATE strategies SET last_signal_at = now()
+             WHERE id = $1 AND (last_signal_at IS NULL OR last_signal_at <= now() - make_interval(secs => $2))",
+            strategy.id,
+            settings.cooldown_secs as f64
+        )
+        .execute(pool.inner())
         .await
-        .map_err(|e| Json(format!("Error sending to NATS: {e}")))?;
+        .map_err(|e| Json(format!("Database error: {e}")))?
+        .rows_affected();
+        if accepted == 0 {
+            return Err(Json("Strategy is in cooldown, signal ignored".to_string()));
+        }
+    }
+
+    sqlx::query!(
+        "INSERT INTO signals
+            (id, strategy_id, signal_id, symbol, side, market_type, position_action,
+             signal_price, live_price, deviation_pct, slippage_decision, order_price)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+        Uuid::new_v4(),
+        strategy.id,
+        payload.id,
+        payload.ticker,
+        if side == OrderSide::Buy { "buy" } else { "sell" },
+        order.market_type.as_str(),
+        action.as_str(),
+        signal_price,
+        live_price,
+        slippage.deviation_pct,
+        slippage.decision.as_str(),
+        order.price
+    )
+    .execute(pool.inner())
+    .await
+    .map_err(|e| Json(format!("Database error: {e}")))?;
+
+    if slippage.decision == SlippageDecision::Rejected {
+        return Err(Json(format!(
+            "Live price {} deviates from signal price by {:.2}%, more than maxPriceDeviationPct {}",
+            live_price.unwrap_or_default(),
+            slippage.deviation_pct.unwrap_or_default(),
+            settings.max_price_deviation_pct.unwrap_or_default()
+        )));
+    }
+
+    // 3. Исполнители: сам автор и, если стратегия опубликована, активные подписчики
+    let mut targets = vec![SignalTarget {
+        subscription_uid: None,
+        user_id: strategy.user_id,
+        account_id: strategy.account_id,
+        api_key: strategy.api_key,
+        encrypted_secret: strategy.encrypted_secret,
+        exchange: strategy.exchange,
+        connector: strategy.connector,
+        action,
+        amount: order.amount,
+    }];
+    if strategy.published {
+        let subscribers = sqlx::query!(
+            "SELECT strategy_subscriptions.id, strategy_subscriptions.sizing_multiplier,
+                    exchange_accounts.id AS account_id, exchange_accounts.user_id,
+                    exchange_accounts.api_key, exchange_accounts.encrypted_secret, exchange_accounts.exchange,
+                    exchange_accounts.connector
+             FROM strategy_subscriptions
+             JOIN exchange_accounts ON strategy_subscriptions.account_id = exchange_accounts.id
+             WHERE strategy_subscriptions.strategy_id = $1 AND strategy_subscriptions.active",
+            strategy.id
+        )
+        .fetch_all(pool.inner())
+        .await
+        .map_err(|e| Json(format!("Database error: {e}")))?;
+
+        for s in subscribers {
+            // В режиме целевой позиции у подписчика своя позиция: ордер — разница с его долей цели,
+            // и сторона может быть противоположной стороне автора
+            let (action, amount) = match target_size {
+                None => (action, order.amount * s.sizing_multiplier),
+                Some(target) => {
+                    let target = target * s.sizing_multiplier;
+                    let current =
+                        position_quantity(pool.inner(), s.account_id, strategy.id, order.market_type, &payload.ticker)
+                            .await
+                            .map_err(Json)?;
+                    match target_action(current, target) {
+                        Some(action) => (action, (target - current).abs()),
+                        None => continue,
+                    }
+                }
+            };
+            targets.push(SignalTarget {
+                subscription_uid: Some(s.id),
+                user_id: s.user_id,
+                account_id: s.account_id,
+                api_key: s.api_key,
+                encrypted_secret: s.encrypted_secret,
+                exchange: s.exchange,
+                connector: s.connector,
+                action,
+                amount,
+            });
+        }
+    }
+
+    // Ордер в сторону, противоположную автору: отклонение цены проверяется заново, по живой цене этой стороны
+    let counter_order = {
+        let counter_side = if side == OrderSide::Buy { OrderSide::Sell } else { OrderSide::Buy };
+        let counter = TradeRequest { side: counter_side, ..signal_order };
+        let live_price = quote.as_ref().and_then(|quote| quote.execution_price(counter_side));
+        let check = check_slippage(settings, &counter, signal_price, live_price);
+        match check.decision {
+            SlippageDecision::Rejected => Err(format!(
+                "Live price {} deviates from signal price by {:.2}%, more than maxPriceDeviationPct {}",
+                live_price.unwrap_or_default(),
+                check.deviation_pct.unwrap_or_default(),
+                settings.max_price_deviation_pct.unwrap_or_default()
+            )),
+            SlippageDecision::Limited => {
+                Ok(TradeRequest { order_type: Some(OrderKind::Limit), price: check.price, ..counter })
+            }
+            SlippageDecision::Unchecked | SlippageDecision::Passed => Ok(counter),
+        }
+    };
+
+    // 4. Для каждого исполнителя расшифровываем ключи и публикуем ордер в NATS
+    // (бумажные аккаунты исполняются сразу, в процессе). Ошибка у одного исполнителя
+    // не мешает остальным; ошибка у автора возвращается после того, как сигнал получили все.
+    let actor = AuditActor::webhook(meta.request_id, meta.ip);
+    // Клиент NATS разделяемый: блокировка не держится, пока идут расшифровка и запросы к бирже
+    let nats = nats_client.lock().await.clone();
+    let mut author_error = None;
+    let mut failed_subscribers = 0;
+
+    for target in targets {
+        let access = CredentialAccess {
+            user_uid: target.user_id,
+            account_uid: target.account_id,
+            purpose: CredentialPurpose::Signal,
+            strategy_uid: Some(strategy.id),
+            signal_id: Some(&payload.id),
+        };
+
+        let published = async {
+            let base = if action_side(target.action) == side {
+                &order
+            } else {
+                counter_order.as_ref().map_err(Clone::clone)?
+            };
+            let trade = TradeRequest {
+                amount: target.amount,
+                reduce_only: reduce_only(settings, order.market_type, target.action),
+                client_order_id: Some(client_order_id(strategy.id, &payload.id, target.account_id)),
+                ..base.clone()
+            };
+            validate_order(&capabilities(&target.exchange, &target.connector), &trade)?;
+            let trade = services.markets.prepare(&target.exchange, &trade, reference_price).await?;
+
+            let real_secret =
+                decrypt_credentials(pool.inner(), &actor, access, &target.encrypted_secret, &config.salt_key).await?;
+            let credentials = ExchangeCredentials {
+                account_uid: target.account_id,
+                exchange: &target.exchange,
+                connector: &target.connector,
+                api_key: &target.api_key,
+                secret: &real_secret,
+            };
+
+            if target.exchange == PAPER_EXCHANGE {
+                return execute_paper(&nats, &services.paper, &credentials, &trade, strategy.id, target.subscription_uid).await;
+            }
+
+            // Плечо и режим маржи выставляются до открытия позиции; закрытие идёт с теми, что уже есть
+            if trade.market_type != MarketType::Spot && !target.action.is_close() {
+                let position = PositionSettings {
+                    symbol: trade.symbol.clone(),
+                    market_type: trade.market_type,
+                    leverage: settings.leverage,
+                    margin_mode: trade.margin_mode.unwrap_or_default(),
+                };
+                services
+                    .position_settings
+                    .ensure(services.gateway.as_ref(), &credentials, &position)
+                    .await
+                    .map_err(|e| format!("Failed to configure {} position: {e}", trade.market_type.as_str()))?;
+            }
+
+            let order_data = json!({
+                "exchange": target.exchange,
+                "connector": target.connector,
+                "apiKey": target.api_key,
+                "secret": real_secret,
+                "order_id": payload.id,
+                "side": if trade.side == OrderSide::Buy { "buy" } else { "sell" },
+                "symbol": payload.ticker,
+                "amount": trade.amount,
+                "type": trade.kind().as_str(),
+                "price": trade.price,
+                "stopPrice": trade.stop_price,
+                "timeInForce": trade.time_in_force,
+                "postOnly": trade.post_only,
+                "reduceOnly": trade.reduce_only,
+                "clientOrderId": trade.client_order_id,
+                "marketType": trade.market_type,
+                "marginMode": trade.margin_mode,
+                "positionAction": target.action,
+                "leverage": settings.leverage,
+                "slippageTolerancePct": settings.slippage_tolerance_pct,
+                "accountUid": target.account_id,
+                "strategyUid": strategy.id,
+                "subscriptionUid": target.subscription_uid,
+                "title": payload.title
+            });
+
+            nats.publish(SIGNALS_TOPIC.to_string(), serde_json::to_string(&order_data).expect("invalid nats topic order data").into())
+                .await
+                .map_err(|e| format!("Error sending to NATS: {e}"))
+        }
+        .await;
+
+        match (published, target.subscription_uid) {
+            (Ok(()), _) => {}
+            (Err(e), None) => author_error = Some(e),
+            (Err(e), Some(subscription_uid)) => {
+                eprintln!("Signal {} for subscription {subscription_uid} failed: {e}", payload.id);
+                failed_subscribers += 1;
+            }
+        }
+    }
+
+    if let Some(e) = author_error {
+        return Err(Json(e));
+    }
+    if failed_subscribers > 0 {
+        return Ok(Json(format!(
+            "Webhook received and published to NATS; {failed_subscribers} subscriber(s) failed"
+        )));
+    }
 
     Ok(Json("Webhook received and published to NATS".to_string()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(signal: &str, order_price: &str, order_type: &str) -> TradingViewSignal {
+        TradingViewSignal {
+            id: "sig-1".to_string(),
+            signal: signal.to_string(),
+            contracts: "1".to_string(),
+            ticker: "BTC/USDT".to_string(),
+            order_price: order_price.to_string(),
+            deposit_pct_limit: "100".to_string(),
+            order_type: order_type.to_string(),
+            title: String::new(),
+            sl_percentage: String::new(),
+            market_position: None,
+            prev_market_position: None,
+        }
+    }
+
+    fn guarded(action: DeviationAction) -> StrategyConfig {
+        StrategyConfig { max_price_deviation_pct: Some(1.0), deviation_action: action, ..StrategyConfig::default() }
+    }
+
+    #[test]
+    fn target_action_moves_position_to_target() {
+        assert_eq!(target_action(0.0, 2.0), Some(PositionAction::OpenLong));
+        assert_eq!(target_action(2.0, 3.0), Some(PositionAction::OpenLong));
+        assert_eq!(target_action(2.0, 0.0), Some(PositionAction::CloseLong));
+        assert_eq!(target_action(2.0, 0.5), Some(PositionAction::CloseLong));
+        assert_eq!(target_action(0.0, -1.0), Some(PositionAction::OpenShort));
+        assert_eq!(target_action(-1.0, 0.0), Some(PositionAction::CloseShort));
+        assert_eq!(target_action(-2.0, -1.0), Some(PositionAction::CloseShort));
+    }
+
+    #[test]
+    fn reversal_is_a_single_order_for_the_whole_difference() {
+        // Ордер на |цель − текущая|: 2 → −1 — одна продажа 3, −1 → 2 — одна покупка 3
+        assert_eq!(target_action(2.0, -1.0), Some(PositionAction::OpenShort));
+        assert_eq!(action_side(PositionAction::OpenShort), OrderSide::Sell);
+        assert_eq!(target_action(-1.0, 2.0), Some(PositionAction::OpenLong));
+        assert_eq!(action_side(PositionAction::OpenLong), OrderSide::Buy);
+    }
+
+    #[test]
+    fn position_already_at_target_needs_no_order() {
+        assert_eq!(target_action(0.0, 0.0), None);
+        assert_eq!(target_action(1.5, 1.5), None);
+        assert_eq!(target_action(-3.0, -3.0), None);
+        // Погрешность суммирования исполнений не порождает ордер
+        assert_eq!(target_action(0.1 + 0.2, 0.3), None);
+    }
+
+    #[test]
+    fn target_position_follows_signal_and_sizing() {
+        let config = StrategyConfig { signal_mode: SignalMode::TargetPosition, ..StrategyConfig::default() };
+        let mut payload = signal("sell", "market", "market");
+        payload.contracts = "2".to_string();
+        payload.market_position = Some("short".to_string());
+        assert_eq!(target_position(&config, &payload).unwrap(), -2.0);
+
+        payload.market_position = Some("long".to_string());
+        assert_eq!(target_position(&config, &payload).unwrap(), 2.0);
+
+        payload.contracts = "0".to_string();
+        payload.market_position = Some("flat".to_string());
+        assert_eq!(target_position(&config, &payload).unwrap(), 0.0);
+
+        payload.contracts = "-3".to_string();
+        let fixed = StrategyConfig { sizing_mode: SizingMode::Fixed, sizing_value: Some(0.5), ..config.clone() };
+        assert_eq!(target_position(&fixed, &payload).unwrap(), -0.5);
+        let multiplied = StrategyConfig { sizing_mode: SizingMode::Multiplier, sizing_value: Some(2.0), ..config.clone() };
+        assert_eq!(target_position(&multiplied, &payload).unwrap(), -6.0);
+
+        let capped = StrategyConfig { max_position: Some(1.0), ..config.clone() };
+        assert!(target_position(&capped, &payload).unwrap_err().contains("maxPosition"));
+
+        payload.contracts = "abc".to_string();
+        assert!(target_position(&config, &payload).unwrap_err().contains("Invalid contracts"));
+    }
+
+    #[test]
+    fn market_order_is_checked_against_signal_price() {
+        let config = guarded(DeviationAction::Reject);
+        let order = order_request(&config, &signal("buy", "60000", "market"), PositionAction::OpenLong, 1.0).unwrap();
+        assert_eq!(order.kind(), OrderKind::Market);
+
+        let passed = check_slippage(&config, &order, Some(60000.0), Some(60300.0));
+        assert_eq!(passed.decision, SlippageDecision::Passed);
+        let rejected = check_slippage(&config, &order, Some(60000.0), Some(61200.0));
+        assert_eq!(rejected.decision, SlippageDecision::Rejected);
+        assert!((rejected.deviation_pct.unwrap() - 2.0).abs() < 1e-9);
+
+        // Без цены сигнала или котировки сравнивать не с чем
+        assert_eq!(check_slippage(&config, &order, None, Some(61200.0)).decision, SlippageDecision::Unchecked);
+        assert_eq!(check_slippage(&config, &order, Some(60000.0), None).decision, SlippageDecision::Unchecked);
+    }
+
+    #[test]
+    fn deviation_turns_market_order_into_limit_at_the_bound() {
+        let config = guarded(DeviationAction::Limit);
+        let buy = order_request(&config, &signal("buy", "100", "market"), PositionAction::OpenLong, 1.0).unwrap();
+        let limited = check_slippage(&config, &buy, Some(100.0), Some(105.0));
+        assert_eq!(limited.decision, SlippageDecision::Limited);
+        assert!((limited.price.unwrap() - 101.0).abs() < 1e-9);
+
+        let config = StrategyConfig { market_type: Some(MarketType::Perpetual), ..config };
+        let sell = order_request(&config, &signal("sell", "100", "market"), PositionAction::OpenShort, 1.0).unwrap();
+        let limited = check_slippage(&config, &sell, Some(100.0), Some(95.0));
+        assert!((limited.price.unwrap() - 99.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn signal_order_type_defaults_to_market() {
+        let config = StrategyConfig::default();
+        let order = order_request(&config, &signal("buy", "60000", "spot"), PositionAction::OpenLong, 1.0).unwrap();
+        assert_eq!(order.kind(), OrderKind::Market);
+        assert_eq!(order.price, None);
+
+        let order = order_request(&config, &signal("buy", "60000", "limit"), PositionAction::OpenLong, 1.0).unwrap();
+        assert_eq!(order.kind(), OrderKind::Limit);
+        assert_eq!(order.price, Some(60000.0));
+
+        let order = order_request(&config, &signal("sell", "60000", "stop_limit"), PositionAction::CloseLong, 1.0).unwrap();
+        assert_eq!(order.kind(), OrderKind::StopLimit);
+        assert_eq!(order.stop_price, Some(60000.0));
+
+        let err = order_request(&config, &signal("buy", "market", "limit"), PositionAction::OpenLong, 1.0).unwrap_err();
+        assert!(err.contains("no price"), "{err}");
+    }
+
+    #[test]
+    fn market_type_comes_from_strategy_only() {
+        let spot = StrategyConfig::default();
+        let order = order_request(&spot, &signal("buy", "market", "perpetual"), PositionAction::OpenLong, 1.0).unwrap();
+        assert_eq!(order.market_type, MarketType::Spot);
+        assert_eq!(order.kind(), OrderKind::Market);
+        assert!(order_request(&spot, &signal("sell", "market", "futures"), PositionAction::OpenShort, 1.0).is_err());
+
+        let perpetual = StrategyConfig { market_type: Some(MarketType::Perpetual), ..StrategyConfig::default() };
+        let order = order_request(&perpetual, &signal("sell", "100", "limit"), PositionAction::OpenShort, 1.0).unwrap();
+        assert_eq!(order.market_type, MarketType::Perpetual);
+        assert_eq!(order.kind(), OrderKind::Limit);
+    }
+
+    #[test]
+    fn limit_orders_are_not_checked() {
+        let config = StrategyConfig { order_type: StrategyOrderType::Limit, ..guarded(DeviationAction::Reject) };
+        let order = order_request(&config, &signal("buy", "60000", "spot"), PositionAction::OpenLong, 1.0).unwrap();
+        let check = check_slippage(&config, &order, Some(60000.0), Some(70000.0));
+        assert_eq!(check.decision, SlippageDecision::Unchecked);
+        assert_eq!(check.price, Some(60000.0));
+    }
+}
@@ -1,5 +1,6 @@
 use std::iter::Map;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_nats::Client;
 use rocket::fairing::{Fairing, Info, Kind};
@@ -10,6 +11,12 @@ use rocket_okapi::swagger_ui::{make_swagger_ui, SwaggerUIConfig};
 use sqlx::PgPool;
 use tokio::sync::Mutex;
 use crate::config::Config as AppConfig;
+use crate::gateway::{BinanceSpot, CircuitBreakers, GuardedGateway, HttpGateway, PaperExchange, PositionSettingsCache, RoutingGateway, SharedGateway};
+use crate::market_data::{feed_from_config, spawn_feed, QuoteBook};
+use crate::markets::{spawn_market_refresher, MarketCache};
+use crate::reconciliation::{spawn_reconciliation, Reconciler};
+use crate::stats::StatsCache;
+use crate::web::routes::webhook::SignalServices;
 use crate::web::routes::{get_routes, get_docs};
 
 pub struct CORS;
@@ -36,7 +43,12 @@ impl Fairing for CORS {
     }
 }
 
-pub async fn rocket(port: u16, pool: PgPool, nats: Arc<Mutex<Client>>) -> Rocket<Build> {
+pub async fn rocket(
+    port: u16,
+    pool: PgPool,
+    nats: Arc<Mutex<Client>>,
+    stats_cache: Arc<StatsCache>,
+) -> Rocket<Build> {
     let config = Config {
         address: "0.0.0.0".parse().unwrap(),
         port,
@@ -44,11 +56,55 @@ pub async fn rocket(port: u16, pool: PgPool, nats: Arc<Mutex<Client>>) -> Rocket
     };
 
     let app_config = AppConfig::from_env();
+    let paper = Arc::new(PaperExchange::from_config(&app_config).expect("Invalid paper exchange settings"));
+    let breakers = Arc::new(CircuitBreakers::from_config(&app_config));
+    let gateway: SharedGateway = Arc::new(GuardedGateway {
+        inner: RoutingGateway {
+            live: HttpGateway::from_config(&app_config).expect("Failed to build exchange gateway client"),
+            binance: BinanceSpot::from_config(&app_config).expect("Failed to build Binance client"),
+            paper: paper.clone(),
+        },
+        breakers: breakers.clone(),
+    });
+
+    let market_cache = Arc::new(MarketCache::new(gateway.clone()));
+    spawn_market_refresher(
+        pool.clone(),
+        market_cache.clone(),
+        Duration::from_secs(app_config.markets_refresh_secs.max(1)),
+    );
+
+    let quotes = Arc::new(QuoteBook::default());
+    if let Some(feed) = feed_from_config(&app_config).expect("Invalid market data feed settings") {
+        spawn_feed(feed, quotes.clone());
+    }
+
+    if app_config.reconcile_interval_secs > 0 {
+        spawn_reconciliation(
+            Reconciler::from_config(pool.clone(), gateway.clone(), &app_config),
+            Duration::from_secs(app_config.reconcile_interval_secs),
+        );
+    }
+
+    let signal_services = SignalServices {
+        paper: paper.clone(),
+        markets: market_cache.clone(),
+        quotes: quotes.clone(),
+        gateway: gateway.clone(),
+        position_settings: PositionSettingsCache::default(),
+    };
 
     rocket::custom(config)
         .manage(pool)
         .manage(app_config) // Передаём конфиг
         .manage(nats)
+        .manage(stats_cache)
+        .manage(gateway)
+        .manage(paper)
+        .manage(breakers)
+        .manage(market_cache)
+        .manage(quotes)
+        .manage(signal_services)
         .mount("/api", get_routes())
         .mount("/swagger", make_swagger_ui(&get_docs()))
         .attach(CORS)
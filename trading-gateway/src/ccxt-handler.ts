@@ -32,6 +32,33 @@ export async function getBalance(exchangeId: string, apiKey: string, secret: str
   }
 }
 
+// Рынок: спот, бессрочные USDT-фьючерсы или маржа
+export type MarketType = "spot" | "perpetual" | "margin";
+export type MarginMode = "isolated" | "cross";
+
+// Тип ccxt по умолчанию для рынка
+const defaultType = (marketType: MarketType) =>
+  marketType === "perpetual" ? "swap" : marketType === "margin" ? "margin" : "spot";
+
+// Символ фьючерса в формате ccxt: BTC/USDT -> BTC/USDT:USDT
+function marketSymbol(symbol: string, marketType: MarketType) {
+  if (marketType !== "perpetual" || symbol.includes(":")) return symbol;
+  const quote = symbol.split("/")[1];
+  return quote ? `${symbol}:${quote}` : symbol;
+}
+
+// Тип и флаги ордера (необязательны)
+export interface OrderOptions {
+  type?: "market" | "limit" | "stop_limit";
+  marketType?: MarketType;
+  marginMode?: MarginMode;
+  stopPrice?: number;
+  timeInForce?: "GTC" | "IOC" | "FOK";
+  postOnly?: boolean;
+  reduceOnly?: boolean;
+  clientOrderId?: string;
+}
+
 // Создание ордера
 export async function createTrade(
   exchangeId: string,
@@ -39,7 +66,9 @@ export async function createTrade(
   secret: string,
   symbol: string,
   side: "buy" | "sell",
-  amount: number
+  amount: number,
+  price?: number,
+  options: OrderOptions = {}
 ) {
   try {
     if (!ccxt.exchanges.includes(exchangeId)) {
@@ -52,14 +81,34 @@ export async function createTrade(
       throw new Error(`Exchange ${exchangeId} is not a valid constructor.`);
     }
 
+    const marketType = options.marketType ?? "spot";
     const exchange = new ExchangeClass({
       apiKey,
       secret,
       enableRateLimit: true,
+      options: { defaultType: defaultType(marketType) },
     });
 
     await exchange.loadMarkets();
-    const order = await exchange.createOrder(symbol, "market", side, amount);
+    const orderSymbol = marketSymbol(symbol, marketType);
+    // Тип по умолчанию: с ценой — лимитный, без неё — рыночный
+    const type = options.type ?? (price ? "limit" : "market");
+    const params: Record<string, unknown> = {};
+    if (options.timeInForce) params.timeInForce = options.timeInForce;
+    if (options.postOnly) params.postOnly = true;
+    if (options.reduceOnly) params.reduceOnly = true;
+    if (options.clientOrderId) params.clientOrderId = options.clientOrderId;
+    // Маржинальный ордер: ccxt выбирает кросс- или изолированный счёт по marginMode
+    if (marketType === "margin") params.marginMode = options.marginMode ?? "isolated";
+
+    // Стоп-лимитный — лимитный ордер с ценой срабатывания
+    if (type === "stop_limit") {
+      params.triggerPrice = options.stopPrice;
+    }
+    const order =
+      type === "market"
+        ? await exchange.createOrder(orderSymbol, "market", side, amount, undefined, params)
+        : await exchange.createOrder(orderSymbol, "limit", side, amount, price, params);
 
     return { status: "ok", order };
   } catch (error) {
@@ -68,3 +117,143 @@ export async function createTrade(
     return { status: "error", message: err.message };
   }
 }
+
+// Биржа отвечает ошибкой, если режим уже такой — это не ошибка
+const alreadySet = (message: string) => /no need to change|not modified|already/i.test(message);
+
+// Плечо и режим маржи по символу (идемпотентно)
+export async function setPositionSettings(
+  exchangeId: string,
+  apiKey: string,
+  secret: string,
+  symbol: string,
+  marketType: MarketType,
+  leverage: number,
+  marginMode: MarginMode
+) {
+  try {
+    if (!ccxt.exchanges.includes(exchangeId)) {
+      throw new Error(`Exchange ${exchangeId} is not supported.`);
+    }
+
+    const ExchangeClass = (ccxt as any)[exchangeId];
+
+    if (typeof ExchangeClass !== "function") {
+      throw new Error(`Exchange ${exchangeId} is not a valid constructor.`);
+    }
+
+    const exchange = new ExchangeClass({
+      apiKey,
+      secret,
+      enableRateLimit: true,
+      options: { defaultType: defaultType(marketType) },
+    });
+
+    await exchange.loadMarkets();
+    // У маржи плечо задаётся счётом, а режим — в каждом ордере; настраиваются только фьючерсы
+    if (marketType !== "perpetual") {
+      return { status: "ok" };
+    }
+
+    const contract = marketSymbol(symbol, marketType);
+    if (exchange.has["setMarginMode"]) {
+      try {
+        await exchange.setMarginMode(marginMode, contract);
+      } catch (error) {
+        if (!alreadySet((error as Error).message)) throw error;
+      }
+    }
+    if (exchange.has["setLeverage"]) {
+      await exchange.setLeverage(leverage, contract);
+    }
+
+    return { status: "ok" };
+  } catch (error) {
+    const err = error as Error;
+    console.error(`[CCXT] Error setting position settings on ${exchangeId}:`, err.message);
+    return { status: "error", message: err.message };
+  }
+}
+
+// Состояние ордера
+export async function getOrder(
+  exchangeId: string,
+  apiKey: string,
+  secret: string,
+  symbol: string,
+  orderId: string
+) {
+  try {
+    if (!ccxt.exchanges.includes(exchangeId)) {
+      throw new Error(`Exchange ${exchangeId} is not supported.`);
+    }
+
+    const ExchangeClass = (ccxt as any)[exchangeId];
+
+    if (typeof ExchangeClass !== "function") {
+      throw new Error(`Exchange ${exchangeId} is not a valid constructor.`);
+    }
+
+    const exchange = new ExchangeClass({
+      apiKey,
+      secret,
+      enableRateLimit: true,
+    });
+
+    await exchange.loadMarkets();
+    const order = await exchange.fetchOrder(orderId, symbol);
+
+    return { status: "ok", order };
+  } catch (error) {
+    const err = error as Error;
+    console.error(`[CCXT] Error fetching order ${orderId} on ${exchangeId}:`, err.message);
+    return { status: "error", message: err.message };
+  }
+}
+
+// Торгуемые пары и их ограничения (ключи не нужны)
+export async function getMarkets(exchangeId: string) {
+  try {
+    if (!ccxt.exchanges.includes(exchangeId)) {
+      throw new Error(`Exchange ${exchangeId} is not supported.`);
+    }
+
+    const ExchangeClass = (ccxt as any)[exchangeId];
+
+    if (typeof ExchangeClass !== "function") {
+      throw new Error(`Exchange ${exchangeId} is not a valid constructor.`);
+    }
+
+    const exchange = new ExchangeClass({ enableRateLimit: true });
+    const markets = await exchange.loadMarkets();
+
+    // ccxt 4 хранит precision как шаг (TICK_SIZE) у большинства бирж
+    const tickSize = (value: number | undefined) =>
+      value === undefined ? null : exchange.precisionMode === ccxt.TICK_SIZE ? value : Math.pow(10, -value);
+
+    return {
+      status: "ok",
+      markets: Object.values(markets).map((market: any) => ({
+        symbol: market.symbol,
+        exchangeSymbol: market.id,
+        base: market.base,
+        quote: market.quote,
+        // Маржа торгуется по спотовым парам; из контрактов нужны только линейные бессрочные
+        marketType: market.spot
+          ? "spot"
+          : market.swap && market.linear && !market.expiry
+            ? "perpetual"
+            : null,
+        active: market.active !== false,
+        tickSize: tickSize(market.precision?.price),
+        stepSize: tickSize(market.precision?.amount),
+        minQty: market.limits?.amount?.min ?? null,
+        minNotional: market.limits?.cost?.min ?? null,
+      })),
+    };
+  } catch (error) {
+    const err = error as Error;
+    console.error(`[CCXT] Error loading markets for ${exchangeId}:`, err.message);
+    return { status: "error", message: err.message };
+  }
+}
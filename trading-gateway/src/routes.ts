@@ -1,5 +1,5 @@
 import { Router, Request, Response } from "express";
-import { getBalance, createTrade } from "./ccxt-handler";
+import { getBalance, createTrade, getOrder, getMarkets, setPositionSettings } from "./ccxt-handler";
 
 const router = Router();
 
@@ -24,14 +24,24 @@ router.post("/get_balance", async (req: Request, res: Response) => {
 // Совершить торговую операцию
 router.post("/trade", async (req: Request, res: Response) => {
   try {
-    const { exchange, apiKey, secret, symbol, side, amount } = req.body;
+    const { exchange, apiKey, secret, symbol, side, amount, price } = req.body;
+    const { type, stopPrice, timeInForce, postOnly, reduceOnly, clientOrderId, marketType, marginMode } = req.body;
 
     if (!exchange || !apiKey || !secret || !symbol || !side || !amount) {
       res.status(400).json({ status: "error", message: "Missing required parameters." });
       return;
     }
 
-    const result = await createTrade(exchange, apiKey, secret, symbol, side, amount);
+    const result = await createTrade(exchange, apiKey, secret, symbol, side, amount, price ?? undefined, {
+      type: type ?? undefined,
+      stopPrice: stopPrice ?? undefined,
+      timeInForce: timeInForce ?? undefined,
+      postOnly: postOnly ?? false,
+      reduceOnly: reduceOnly ?? false,
+      clientOrderId: clientOrderId ?? undefined,
+      marketType: marketType ?? undefined,
+      marginMode: marginMode ?? undefined,
+    });
     res.json(result);
   } catch (error) {
     console.error("[ERROR] /trade:", error);
@@ -39,4 +49,58 @@ router.post("/trade", async (req: Request, res: Response) => {
   }
 });
 
+// Плечо и режим маржи по символу
+router.post("/position_settings", async (req: Request, res: Response) => {
+  try {
+    const { exchange, apiKey, secret, symbol, marketType, leverage, marginMode } = req.body;
+
+    if (!exchange || !apiKey || !secret || !symbol || !marketType || !leverage) {
+      res.status(400).json({ status: "error", message: "Missing required parameters." });
+      return;
+    }
+
+    const result = await setPositionSettings(exchange, apiKey, secret, symbol, marketType, leverage, marginMode ?? "isolated");
+    res.json(result);
+  } catch (error) {
+    console.error("[ERROR] /position_settings:", error);
+    res.status(500).json({ status: "error", message: "Internal server error." });
+  }
+});
+
+// Состояние ордера
+router.post("/order_status", async (req: Request, res: Response) => {
+  try {
+    const { exchange, apiKey, secret, symbol, orderId } = req.body;
+
+    if (!exchange || !apiKey || !secret || !symbol || !orderId) {
+      res.status(400).json({ status: "error", message: "Missing required parameters." });
+      return;
+    }
+
+    const result = await getOrder(exchange, apiKey, secret, symbol, orderId);
+    res.json(result);
+  } catch (error) {
+    console.error("[ERROR] /order_status:", error);
+    res.status(500).json({ status: "error", message: "Internal server error." });
+  }
+});
+
+// Торгуемые пары биржи
+router.post("/markets", async (req: Request, res: Response) => {
+  try {
+    const { exchange } = req.body;
+
+    if (!exchange) {
+      res.status(400).json({ status: "error", message: "Missing required parameters." });
+      return;
+    }
+
+    const result = await getMarkets(exchange);
+    res.json(result);
+  } catch (error) {
+    console.error("[ERROR] /markets:", error);
+    res.status(500).json({ status: "error", message: "Internal server error." });
+  }
+});
+
 export default router;